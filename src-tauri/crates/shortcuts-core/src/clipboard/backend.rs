@@ -0,0 +1,163 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub use super::content::{ClipboardContent, ContentType, CONCEALED_MARKERS};
+use crate::extensions::permissions::{Capability, Permissions};
+
+/// Access to a clipboard. The system one is returned by [`system`]; tests
+/// use [`MemoryClipboard`].
+pub trait ClipboardBackend: Send {
+  /// Every representation currently offered, or `None` when the clipboard
+  /// is empty.
+  fn read(&mut self) -> Result<Option<ClipboardContent>, String>;
+  /// Offers the representations of `content`. Backends that can offer only
+  /// some of them at once pick the most specific ones.
+  fn write(&mut self, content: &ClipboardContent) -> Result<(), String>;
+  /// Whether the clipboard may have changed since the last read. Backends
+  /// without change notifications always say yes.
+  fn has_changed(&mut self) -> bool {
+    true
+  }
+}
+
+/// A backend shared between the monitor polling it and the actions writing
+/// to it.
+pub type SharedBackend = Arc<Mutex<Box<dyn ClipboardBackend>>>;
+
+static SYSTEM: OnceLock<Result<SharedBackend, String>> = OnceLock::new();
+
+/// The clipboard of the desktop session, opened on first use.
+pub fn system() -> Result<SharedBackend, String> {
+  SYSTEM.get_or_init(|| {
+    #[cfg(target_os = "linux")]
+    let backend: Box<dyn ClipboardBackend> = Box::new(super::linux::LinuxClipboard::new()?);
+    #[cfg(not(target_os = "linux"))]
+    let backend: Box<dyn ClipboardBackend> = Box::new(NativeClipboard::new()?);
+    Ok(Arc::new(Mutex::new(backend)))
+  }).clone()
+}
+
+/// The system clipboard for a built-in extension, once the user granted it
+/// every one of `capabilities`, as extension processes need to be for the
+/// host API.
+pub fn system_for(permissions: &Permissions, extension: &str, capabilities: &[Capability]) -> Result<SharedBackend, String> {
+  for capability in capabilities {
+    permissions.check_extension(extension, capability).map_err(|denied| denied.to_string())?;
+  }
+  system()
+}
+
+/// The clipboard of macOS and Windows. It reads text, HTML, images and
+/// file lists, and writes one of them; HTML is written with its plain text.
+/// Other formats, `CONCEALED_MARKERS` included, are out of its reach.
+#[cfg(not(target_os = "linux"))]
+struct NativeClipboard(arboard::Clipboard);
+
+#[cfg(not(target_os = "linux"))]
+impl NativeClipboard {
+  fn new() -> Result<Self, String> {
+    arboard::Clipboard::new().map(NativeClipboard).map_err(|err| err.to_string())
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl ClipboardBackend for NativeClipboard {
+  fn read(&mut self) -> Result<Option<ClipboardContent>, String> {
+    let mut content = ClipboardContent::default();
+    if let Ok(text) = self.0.get_text() {
+      content = content.with(ContentType::Text, text);
+    }
+    if let Ok(html) = self.0.get().html() {
+      content = content.with(ContentType::Html, html);
+    }
+    if let Ok(files) = self.0.get().file_list() {
+      let uris: Vec<String> = files.iter().map(|file| format!("file://{}", file.display())).collect();
+      content = content.with(ContentType::Files, uris.join("\r\n"));
+    }
+    if let Ok(image) = self.0.get_image() {
+      let rgba = image::RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.into_owned())
+        .ok_or("invalid image on the clipboard")?;
+      let mut png = std::io::Cursor::new(vec![]);
+      image::DynamicImage::ImageRgba8(rgba).write_to(&mut png, image::ImageFormat::Png).map_err(|err| err.to_string())?;
+      content = content.with(ContentType::Image, png.into_inner());
+    }
+    Ok(Some(content).filter(|content| !content.representations.is_empty()))
+  }
+
+  fn write(&mut self, content: &ClipboardContent) -> Result<(), String> {
+    let files = content.files();
+    if !files.is_empty() {
+      return self.0.set().file_list(&files).map_err(|err| err.to_string());
+    }
+    if let Some(png) = content.get(ContentType::Image) {
+      let rgba = image::load_from_memory_with_format(png, image::ImageFormat::Png).map_err(|err| err.to_string())?.to_rgba8();
+      let image = arboard::ImageData {
+        width: rgba.width() as usize,
+        height: rgba.height() as usize,
+        bytes: rgba.into_raw().into(),
+      };
+      return self.0.set_image(image).map_err(|err| err.to_string());
+    }
+    if let Some(html) = content.get(ContentType::Html) {
+      let html = String::from_utf8_lossy(html);
+      return self.0.set_html(html, content.plain_text()).map_err(|err| err.to_string());
+    }
+    self.0.set_text(content.summary()).map_err(|err| err.to_string())
+  }
+}
+
+/// An in-memory clipboard. Clones share their content, so a test can keep
+/// one to copy into while the monitor polls another.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryClipboard {
+  content: Arc<Mutex<Option<ClipboardContent>>>,
+}
+
+impl MemoryClipboard {
+  pub fn new() -> Self {
+    MemoryClipboard::default()
+  }
+
+  /// Puts `text` on the clipboard as if `source` had copied it.
+  pub fn copy(&self, text: &str, source: Option<&str>) {
+    self.copy_content(ClipboardContent::text(text).with_source(source));
+  }
+
+  pub fn copy_content(&self, content: ClipboardContent) {
+    *self.content.lock().unwrap() = Some(content);
+  }
+
+  pub fn content(&self) -> Option<ClipboardContent> {
+    self.content.lock().unwrap().clone()
+  }
+
+  pub fn shared(&self) -> SharedBackend {
+    Arc::new(Mutex::new(Box::new(self.clone())))
+  }
+}
+
+impl ClipboardBackend for MemoryClipboard {
+  fn read(&mut self) -> Result<Option<ClipboardContent>, String> {
+    Ok(self.content())
+  }
+
+  fn write(&mut self, content: &ClipboardContent) -> Result<(), String> {
+    self.copy_content(ClipboardContent { representations: content.representations.clone(), ..Default::default() });
+    Ok(())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_system_for_checks_grants() {
+    let permissions = Permissions::new();
+    permissions.set("clipboard", vec![Capability::ClipboardRead]);
+    let err = system_for(&permissions, "clipboard", &[Capability::ClipboardRead, Capability::ClipboardWrite]).err().unwrap();
+    assert!(err.contains("has not been granted `clipboard:write`"));
+    assert_eq!(permissions.denials().len(), 1, "the denial is logged");
+    assert_eq!(permissions.denials()[0].capability, Capability::ClipboardWrite);
+  }
+}
@@ -0,0 +1,264 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::str::FromStr;
+
+/// Width and height thumbnails of copied images fit in.
+pub const THUMBNAIL_SIZE: u32 = 128;
+
+/// A representation of copied content. Apps usually offer several at once,
+/// e.g. a browser selection as HTML and as plain text.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+  Text,
+  Html,
+  Rtf,
+  /// A PNG image.
+  Image,
+  /// A `text/uri-list` of files.
+  Files,
+}
+
+impl ContentType {
+  pub const ALL: [ContentType; 5] = [ContentType::Text, ContentType::Html, ContentType::Rtf, ContentType::Image, ContentType::Files];
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ContentType::Text => "text",
+      ContentType::Html => "html",
+      ContentType::Rtf => "rtf",
+      ContentType::Image => "image",
+      ContentType::Files => "files",
+    }
+  }
+
+  /// The MIME type it is exchanged as.
+  pub fn mime(&self) -> &'static str {
+    match self {
+      ContentType::Text => "text/plain;charset=utf-8",
+      ContentType::Html => "text/html",
+      ContentType::Rtf => "text/rtf",
+      ContentType::Image => "image/png",
+      ContentType::Files => "text/uri-list",
+    }
+  }
+
+  /// Recognizes the MIME types and their common aliases.
+  pub fn from_mime(mime: &str) -> Option<Self> {
+    match mime {
+      "text/plain;charset=utf-8" | "text/plain" | "UTF8_STRING" => Some(ContentType::Text),
+      "text/html" => Some(ContentType::Html),
+      "text/rtf" | "application/rtf" => Some(ContentType::Rtf),
+      "image/png" => Some(ContentType::Image),
+      "text/uri-list" => Some(ContentType::Files),
+      _ => None,
+    }
+  }
+}
+
+impl FromStr for ContentType {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    ContentType::ALL.into_iter().find(|content_type| content_type.as_str() == value)
+      .ok_or_else(|| format!("unknown content type `{}`", value))
+  }
+}
+
+/// Types password managers offer next to a secret so clipboard managers
+/// leave it alone: KDE's hint, and the macOS pasteboard conventions that
+/// some cross-platform apps offer on Linux too.
+pub const CONCEALED_MARKERS: [&str; 3] = [
+  "x-kde-passwordManagerHint",
+  "org.nspasteboard.ConcealedType",
+  "org.nspasteboard.TransientType",
+];
+
+/// Everything on the clipboard at one point: every representation the
+/// copying app offered, and the app itself when the backend can tell.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ClipboardContent {
+  pub representations: BTreeMap<ContentType, Vec<u8>>,
+  pub source: Option<String>,
+  /// The copying app offered one of the `CONCEALED_MARKERS`.
+  pub concealed: bool,
+}
+
+impl ClipboardContent {
+  pub fn text(text: &str) -> Self {
+    ClipboardContent::default().with(ContentType::Text, text)
+  }
+
+  pub fn with(mut self, content_type: ContentType, data: impl Into<Vec<u8>>) -> Self {
+    self.representations.insert(content_type, data.into());
+    self
+  }
+
+  pub fn with_source(mut self, source: Option<&str>) -> Self {
+    self.source = source.map(str::to_string);
+    self
+  }
+
+  pub fn get(&self, content_type: ContentType) -> Option<&[u8]> {
+    self.representations.get(&content_type).map(Vec::as_slice)
+  }
+
+  pub fn types(&self) -> Vec<ContentType> {
+    self.representations.keys().copied().collect()
+  }
+
+  pub fn plain_text(&self) -> Option<&str> {
+    self.get(ContentType::Text).and_then(|text| std::str::from_utf8(text).ok())
+  }
+
+  /// Local paths of the file list.
+  pub fn files(&self) -> Vec<String> {
+    self.get(ContentType::Files).map(parse_uri_list).unwrap_or_default()
+  }
+
+  /// Nothing worth keeping: no representation, or only blank text.
+  pub fn is_blank(&self) -> bool {
+    self.representations.iter().all(|(content_type, data)| match content_type {
+      ContentType::Text | ContentType::Html | ContentType::Rtf | ContentType::Files => {
+        String::from_utf8_lossy(data).trim().is_empty()
+      },
+      ContentType::Image => data.is_empty(),
+    })
+  }
+
+  /// What the history shows and searches: the plain text, else the file
+  /// paths, else the text of the HTML.
+  pub fn summary(&self) -> String {
+    if let Some(text) = self.plain_text() {
+      return text.to_string();
+    }
+    let files = self.files();
+    if !files.is_empty() {
+      return files.join("\n");
+    }
+    self.get(ContentType::Html).map(|html| strip_tags(&String::from_utf8_lossy(html))).unwrap_or_default()
+  }
+
+  /// Identifies the content regardless of where it was copied from.
+  pub fn hash(&self) -> String {
+    let mut hasher = Sha256::new();
+    for (content_type, data) in &self.representations {
+      hasher.update(content_type.as_str().as_bytes());
+      hasher.update((data.len() as u64).to_le_bytes());
+      hasher.update(data);
+    }
+    hex::encode(hasher.finalize())
+  }
+}
+
+fn parse_uri_list(list: &[u8]) -> Vec<String> {
+  String::from_utf8_lossy(list).lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(|uri| uri.strip_prefix("file://").map(percent_decode).unwrap_or_else(|| uri.to_string()))
+    .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match (bytes[i], hex) {
+      (b'%', Some(byte)) => {
+        decoded.push(byte);
+        i += 3;
+      },
+      (byte, _) => {
+        decoded.push(byte);
+        i += 1;
+      },
+    }
+  }
+  String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn strip_tags(html: &str) -> String {
+  let mut text = String::new();
+  let mut in_tag = false;
+  for c in html.chars() {
+    match c {
+      '<' => in_tag = true,
+      '>' => in_tag = false,
+      c if !in_tag => text.push(c),
+      _ => {},
+    }
+  }
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A PNG fitting in `THUMBNAIL_SIZE` squared.
+pub fn thumbnail(png: &[u8]) -> Result<Vec<u8>, String> {
+  let image = image::load_from_memory_with_format(png, image::ImageFormat::Png).map_err(|err| err.to_string())?;
+  let mut thumbnail = Cursor::new(vec![]);
+  image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+    .write_to(&mut thumbnail, image::ImageFormat::Png)
+    .map_err(|err| err.to_string())?;
+  Ok(thumbnail.into_inner())
+}
+
+/// A PNG as a `data:` URL, the way icons are passed to the webview.
+pub fn png_data_url(png: &[u8]) -> String {
+  format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png))
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = Cursor::new(vec![]);
+    image::DynamicImage::ImageRgba8(image::RgbaImage::new(width, height))
+      .write_to(&mut png, image::ImageFormat::Png)
+      .unwrap();
+    png.into_inner()
+  }
+
+  #[test]
+  fn test_summary() {
+    assert_eq!(ClipboardContent::text("plain").with(ContentType::Html, "<b>rich</b>").summary(), "plain");
+    assert_eq!(ClipboardContent::default().with(ContentType::Html, "<p>Hello <b>rich</b>\n text</p>").summary(), "Hello rich text");
+    let files = ClipboardContent::default().with(ContentType::Files, "# copied\r\nfile:///home/me/a%20b.txt\r\nfile:///tmp/c\r\n");
+    assert_eq!(files.summary(), "/home/me/a b.txt\n/tmp/c");
+    assert_eq!(ClipboardContent::default().with(ContentType::Image, png(1, 1)).summary(), "");
+  }
+
+  #[test]
+  fn test_hash_and_blank() {
+    let html = ClipboardContent::text("a").with(ContentType::Html, "<i>a</i>");
+    assert_eq!(html.hash(), html.clone().with_source(Some("firefox")).hash());
+    assert_ne!(html.hash(), ClipboardContent::text("a").hash());
+    assert!(ClipboardContent::text(" \n").is_blank());
+    assert!(ClipboardContent::default().is_blank());
+    assert!(!ClipboardContent::default().with(ContentType::Image, png(1, 1)).is_blank());
+  }
+
+  #[test]
+  fn test_content_type_names() {
+    for content_type in ContentType::ALL {
+      assert_eq!(content_type.as_str().parse::<ContentType>(), Ok(content_type));
+      assert_eq!(ContentType::from_mime(content_type.mime()), Some(content_type));
+    }
+    assert_eq!(ContentType::from_mime("application/rtf"), Some(ContentType::Rtf));
+    assert!("video".parse::<ContentType>().is_err());
+  }
+
+  #[test]
+  fn test_thumbnail() {
+    let thumbnail = thumbnail(&png(512, 256)).unwrap();
+    let image = image::load_from_memory(&thumbnail).unwrap();
+    assert_eq!((image.width(), image.height()), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+    assert!(png_data_url(&thumbnail).starts_with("data:image/png;base64,"));
+    assert!(super::thumbnail(b"not a png").is_err());
+  }
+}
@@ -0,0 +1,190 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Where the key is kept when no OS keyring is available.
+const KEY_PATH: &str = ".shortcuts/clipboard.key";
+const KEYRING_SERVICE: &str = "shortcuts";
+const KEYRING_USER: &str = "clipboard-history";
+const NONCE_LEN: usize = 12;
+
+/// Where a [`HistoryKey`] was loaded from.
+#[derive(Debug, PartialEq, Clone)]
+pub enum KeyStore {
+  Keyring,
+  File(PathBuf),
+}
+
+/// The key the clipboard history is encrypted with. It is zeroed when
+/// dropped.
+pub struct HistoryKey(Zeroizing<[u8; 32]>);
+
+impl HistoryKey {
+  pub fn generate() -> Self {
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    HistoryKey(Zeroizing::new(key.into()))
+  }
+
+  fn from_slice(bytes: &[u8]) -> Result<Self, String> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| "the clipboard key is corrupt".to_string())?;
+    Ok(HistoryKey(Zeroizing::new(bytes)))
+  }
+
+  /// The key from the key file if there is one, else from the OS keyring,
+  /// creating it in the first of the two that works. The file at `path`,
+  /// relative to the home directory, is only created when the keyring is
+  /// unavailable, e.g. without a Secret Service on Linux.
+  pub fn load_or_create(path: Option<String>) -> Result<(Self, KeyStore), String> {
+    let path = key_path(path);
+    if let Ok(hex) = std::fs::read_to_string(&path) {
+      let bytes = hex::decode(hex.trim()).map_err(|_| "the clipboard key file is corrupt".to_string())?;
+      return Ok((HistoryKey::from_slice(&bytes)?, KeyStore::File(path)));
+    }
+    match keyring_load_or_create() {
+      Ok(key) => Ok((key, KeyStore::Keyring)),
+      Err(err) => {
+        println!("No keyring for the clipboard key, keeping it in {}: {}", path.display(), err);
+        let key = HistoryKey::generate();
+        write_key_file(&path, key.0.as_slice())?;
+        Ok((key, KeyStore::File(path)))
+      },
+    }
+  }
+
+  /// Deletes the key from the keyring and overwrites the key file, so
+  /// whatever was encrypted with it can't be read anymore.
+  pub fn destroy(path: Option<String>) -> Result<(), String> {
+    if let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER) {
+      match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) | Err(keyring::Error::PlatformFailure(_)) | Err(keyring::Error::NoStorageAccess(_)) => {},
+        Err(err) => return Err(err.to_string()),
+      }
+    }
+    shred(&key_path(path))
+  }
+}
+
+fn key_path(path: Option<String>) -> PathBuf {
+  dirs::home_dir().unwrap().join(path.unwrap_or_else(|| KEY_PATH.to_string()))
+}
+
+fn keyring_load_or_create() -> Result<HistoryKey, keyring::Error> {
+  let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+  match entry.get_secret() {
+    Ok(secret) => HistoryKey::from_slice(&Zeroizing::new(secret)).map_err(|err| keyring::Error::Invalid("secret".to_string(), err)),
+    Err(keyring::Error::NoEntry) => {
+      let key = HistoryKey::generate();
+      entry.set_secret(key.0.as_slice())?;
+      Ok(key)
+    },
+    Err(err) => Err(err),
+  }
+}
+
+/// Writes a new key file only the user can read. An existing one is kept.
+pub(crate) fn write_key_file(path: &Path, key: &[u8]) -> Result<(), String> {
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+  }
+  let mut options = std::fs::OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+  let mut file = options.open(path).map_err(|err| err.to_string())?;
+  file.write_all(Zeroizing::new(hex::encode(key)).as_bytes()).map_err(|err| err.to_string())
+}
+
+/// Overwrites a file with zeros before deleting it. Missing files are fine.
+pub fn shred(path: &Path) -> Result<(), String> {
+  let Ok(metadata) = std::fs::metadata(path) else { return Ok(()) };
+  let mut file = std::fs::OpenOptions::new().write(true).open(path).map_err(|err| err.to_string())?;
+  let zeros = vec![0; 64 * 1024];
+  let mut left = metadata.len() as usize;
+  while left > 0 {
+    let len = left.min(zeros.len());
+    file.write_all(&zeros[..len]).map_err(|err| err.to_string())?;
+    left -= len;
+  }
+  file.sync_all().map_err(|err| err.to_string())?;
+  drop(file);
+  std::fs::remove_file(path).map_err(|err| err.to_string())
+}
+
+/// Authenticated encryption of history values with ChaCha20-Poly1305, each
+/// value with a random nonce stored in front of it.
+pub struct Cipher {
+  aead: ChaCha20Poly1305,
+  /// Keys the hashes identifying contents, so equal contents can be found
+  /// without the hashes revealing what was copied.
+  hash_key: Zeroizing<[u8; 32]>,
+}
+
+impl Cipher {
+  pub fn new(key: &HistoryKey) -> Self {
+    let hash_key = Sha256::new().chain_update(b"shortcuts clipboard hash").chain_update(key.0.as_slice()).finalize();
+    Cipher { aead: ChaCha20Poly1305::new(Key::from_slice(key.0.as_slice())), hash_key: Zeroizing::new(hash_key.into()) }
+  }
+
+  pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut sealed = nonce.to_vec();
+    sealed.extend(self.aead.encrypt(&nonce, plaintext).expect("encrypting in memory can't fail"));
+    sealed
+  }
+
+  pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+      return Err("the encrypted value is truncated".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    self.aead.decrypt(Nonce::from_slice(nonce), ciphertext)
+      .map_err(|_| "the clipboard history can't be decrypted with this key".to_string())
+  }
+
+  pub fn decrypt_string(&self, sealed: &[u8]) -> Result<String, String> {
+    String::from_utf8(self.decrypt(sealed)?).map_err(|err| err.to_string())
+  }
+
+  /// The keyed hash of `data`, hex encoded.
+  pub fn hash(&self, data: &[u8]) -> String {
+    hex::encode(Sha256::new().chain_update(self.hash_key.as_slice()).chain_update(data).finalize())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cipher() {
+    let cipher = Cipher::new(&HistoryKey::generate());
+    let sealed = cipher.encrypt(b"secret");
+    assert_ne!(cipher.encrypt(b"secret"), sealed, "every value gets its own nonce");
+    assert!(!sealed.windows(6).any(|window| window == b"secret"));
+    assert_eq!(cipher.decrypt(&sealed).unwrap(), b"secret");
+    assert!(Cipher::new(&HistoryKey::generate()).decrypt(&sealed).is_err());
+    assert!(cipher.decrypt(&sealed[..4]).is_err());
+    assert_ne!(cipher.hash(b"a"), Cipher::new(&HistoryKey::generate()).hash(b"a"));
+  }
+
+  #[test]
+  fn test_key_file() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_clipboard_key_file.key";
+    let path = dirs::home_dir().unwrap().join(TEST_FILE_PATH);
+    let _ = std::fs::remove_file(&path);
+    let key = HistoryKey::generate();
+    write_key_file(&path, key.0.as_slice()).unwrap();
+    let (loaded, store) = HistoryKey::load_or_create(Some(TEST_FILE_PATH.to_string())).unwrap();
+    assert_eq!(store, KeyStore::File(path.clone()));
+    assert_eq!(Cipher::new(&loaded).decrypt(&Cipher::new(&key).encrypt(b"x")).unwrap(), b"x");
+    assert!(write_key_file(&path, key.0.as_slice()).is_err(), "an existing key is never replaced");
+
+    shred(&path).unwrap();
+    assert!(!path.exists());
+    shred(&path).unwrap();
+  }
+}
@@ -0,0 +1,58 @@
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::net::SocketAddr;
+use std::thread;
+
+const SERVICE_TYPE: &str = "_shortcuts-sync._tcp.local.";
+/// The TXT property carrying the public key of the advertising peer.
+const KEY_PROPERTY: &str = "key";
+
+/// Finds clipboard sync peers on the local network over mDNS, and
+/// advertises this one. Discovery only yields addresses; peers still have
+/// to be paired to connect.
+pub struct Discovery {
+  daemon: ServiceDaemon,
+}
+
+impl Discovery {
+  /// Advertises `public_key` listening on `port` and calls `on_found` with
+  /// the key and address of every other peer found.
+  pub fn start(public_key: &str, port: u16, on_found: impl Fn(&str, SocketAddr) + Send + 'static) -> Result<Self, String> {
+    let daemon = ServiceDaemon::new().map_err(|err| err.to_string())?;
+    let instance = &public_key[..16.min(public_key.len())];
+    let service = ServiceInfo::new(
+      SERVICE_TYPE,
+      instance,
+      &format!("{}.local.", instance),
+      "",
+      port,
+      &[(KEY_PROPERTY, public_key)][..],
+    ).map_err(|err| err.to_string())?.enable_addr_auto();
+    daemon.register(service).map_err(|err| err.to_string())?;
+
+    let events = daemon.browse(SERVICE_TYPE).map_err(|err| err.to_string())?;
+    let own_key = public_key.to_string();
+    thread::spawn(move || {
+      // Ends when the daemon shuts down.
+      while let Ok(event) = events.recv() {
+        let ServiceEvent::ServiceResolved(info) = event else { continue };
+        let Some(key) = info.get_property_val_str(KEY_PROPERTY) else { continue };
+        if key == own_key {
+          continue;
+        }
+        // Prefer IPv4, which needs no interface scope to connect to.
+        let mut addresses: Vec<_> = info.get_addresses().iter().copied().collect();
+        addresses.sort_by_key(|address| !address.is_ipv4());
+        if let Some(address) = addresses.first() {
+          on_found(key, SocketAddr::new(*address, info.get_port()));
+        }
+      }
+    });
+    Ok(Discovery { daemon })
+  }
+}
+
+impl Drop for Discovery {
+  fn drop(&mut self) {
+    let _ = self.daemon.shutdown();
+  }
+}
@@ -0,0 +1,390 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+use x11rb::protocol::xproto::{
+  Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, PropMode, Property, SelectionNotifyEvent, Window,
+  WindowClass, SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME, NONE};
+
+use super::backend::{ClipboardBackend, ClipboardContent, ContentType, CONCEALED_MARKERS};
+
+/// How long the owner of the clipboard gets to answer a request.
+const CONVERT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The clipboard of an X11 or Wayland session.
+pub enum LinuxClipboard {
+  X11(Box<X11Clipboard>),
+  Wayland(WaylandClipboard),
+}
+
+impl LinuxClipboard {
+  pub fn new() -> Result<Self, String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+      if let Some(wayland) = WaylandClipboard::new() {
+        return Ok(LinuxClipboard::Wayland(wayland));
+      }
+    }
+    X11Clipboard::new().map(|x11| LinuxClipboard::X11(Box::new(x11)))
+  }
+}
+
+impl ClipboardBackend for LinuxClipboard {
+  fn read(&mut self) -> Result<Option<ClipboardContent>, String> {
+    match self {
+      LinuxClipboard::X11(x11) => x11.read(),
+      LinuxClipboard::Wayland(wayland) => wayland.read(),
+    }
+  }
+
+  fn write(&mut self, content: &ClipboardContent) -> Result<(), String> {
+    match self {
+      LinuxClipboard::X11(_) => X11Clipboard::write(content),
+      LinuxClipboard::Wayland(_) => WaylandClipboard::write(content),
+    }
+  }
+
+  fn has_changed(&mut self) -> bool {
+    match self {
+      LinuxClipboard::X11(x11) => x11.has_changed(),
+      LinuxClipboard::Wayland(wayland) => wayland.has_changed(),
+    }
+  }
+}
+
+fn x11_error(err: impl std::fmt::Display) -> String {
+  format!("X11: {}", err)
+}
+
+x11rb::atom_manager! {
+  Atoms: AtomsCookie {
+    CLIPBOARD,
+    TARGETS,
+    INCR,
+    UTF8_STRING,
+    TEXT_PLAIN: b"text/plain;charset=utf-8",
+    TEXT_HTML: b"text/html",
+    TEXT_RTF: b"text/rtf",
+    APPLICATION_RTF: b"application/rtf",
+    IMAGE_PNG: b"image/png",
+    URI_LIST: b"text/uri-list",
+    SELECTION_DATA: b"SHORTCUTS_SELECTION",
+    KDE_PASSWORD_MANAGER_HINT: b"x-kde-passwordManagerHint",
+    CONCEALED_TYPE: b"org.nspasteboard.ConcealedType",
+    TRANSIENT_TYPE: b"org.nspasteboard.TransientType",
+  }
+}
+
+impl Atoms {
+  /// The targets a representation is exchanged as, preferred first.
+  fn targets(&self, content_type: ContentType) -> Vec<Atom> {
+    match content_type {
+      ContentType::Text => vec![self.UTF8_STRING, self.TEXT_PLAIN],
+      ContentType::Html => vec![self.TEXT_HTML],
+      ContentType::Rtf => vec![self.TEXT_RTF, self.APPLICATION_RTF],
+      ContentType::Image => vec![self.IMAGE_PNG],
+      ContentType::Files => vec![self.URI_LIST],
+    }
+  }
+
+  /// The atoms of the `CONCEALED_MARKERS`.
+  fn concealed_markers(&self) -> [Atom; 3] {
+    [self.KDE_PASSWORD_MANAGER_HINT, self.CONCEALED_TYPE, self.TRANSIENT_TYPE]
+  }
+}
+
+/// An invisible window to receive selection data on.
+fn connect() -> Result<(RustConnection, Window, Atoms), String> {
+  let (connection, screen) = x11rb::connect(None).map_err(x11_error)?;
+  let window = connection.generate_id().map_err(x11_error)?;
+  let root = connection.setup().roots[screen].root;
+  connection.create_window(
+    COPY_DEPTH_FROM_PARENT, window, root, 0, 0, 1, 1, 0,
+    WindowClass::INPUT_OUTPUT, COPY_FROM_PARENT,
+    &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+  ).map_err(x11_error)?;
+  let atoms = Atoms::new(&connection).map_err(x11_error)?.reply().map_err(x11_error)?;
+  Ok((connection, window, atoms))
+}
+
+/// Reads every representation of the `CLIPBOARD` selection by converting it
+/// to each target the owner offers. XFixes reports when a client takes the
+/// selection, so unchanged clipboards aren't read again.
+pub struct X11Clipboard {
+  connection: RustConnection,
+  window: Window,
+  atoms: Atoms,
+  xfixes: bool,
+  changed: bool,
+}
+
+impl X11Clipboard {
+  fn new() -> Result<Self, String> {
+    let (connection, window, atoms) = connect()?;
+    let xfixes = connection.xfixes_query_version(5, 0).ok().and_then(|cookie| cookie.reply().ok()).is_some()
+      && connection.xfixes_select_selection_input(
+        window,
+        atoms.CLIPBOARD,
+        SelectionEventMask::SET_SELECTION_OWNER | SelectionEventMask::SELECTION_WINDOW_DESTROY | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+      ).is_ok();
+    connection.flush().map_err(x11_error)?;
+    Ok(X11Clipboard { connection, window, atoms, xfixes, changed: true })
+  }
+
+  fn note(&mut self, event: &Event) {
+    if let Event::XfixesSelectionNotify(_) = event {
+      self.changed = true;
+    }
+  }
+
+  fn has_changed(&mut self) -> bool {
+    if !self.xfixes {
+      return true;
+    }
+    while let Ok(Some(event)) = self.connection.poll_for_event() {
+      self.note(&event);
+    }
+    self.changed
+  }
+
+  /// Waits for the first event `matches` accepts.
+  fn wait<T>(&mut self, mut matches: impl FnMut(&Event) -> Option<T>) -> Result<T, String> {
+    let deadline = Instant::now() + CONVERT_TIMEOUT;
+    loop {
+      match self.connection.poll_for_event().map_err(x11_error)? {
+        Some(event) => {
+          self.note(&event);
+          if let Some(found) = matches(&event) {
+            return Ok(found);
+          }
+        },
+        None if Instant::now() < deadline => thread::sleep(Duration::from_millis(2)),
+        None => return Err("the clipboard owner did not answer".to_string()),
+      }
+    }
+  }
+
+  /// The selection converted to `target`, or `None` when the owner refused.
+  fn convert(&mut self, target: Atom) -> Result<Option<Vec<u8>>, String> {
+    let (window, property) = (self.window, self.atoms.SELECTION_DATA);
+    self.connection.convert_selection(window, self.atoms.CLIPBOARD, target, property, CURRENT_TIME).map_err(x11_error)?;
+    self.connection.flush().map_err(x11_error)?;
+    let notified = self.wait(|event| match event {
+      Event::SelectionNotify(notify) if notify.requestor == window => Some(notify.property),
+      _ => None,
+    })?;
+    if notified == NONE {
+      return Ok(None);
+    }
+
+    let reply = self.connection.get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX / 4)
+      .map_err(x11_error)?.reply().map_err(x11_error)?;
+    if reply.type_ != self.atoms.INCR {
+      return Ok(Some(reply.value));
+    }
+    // Large values arrive in chunks, each one a new value of the property.
+    // An empty chunk ends the transfer.
+    let mut value = vec![];
+    loop {
+      self.wait(|event| match event {
+        Event::PropertyNotify(notify) if notify.window == window && notify.atom == property && notify.state == Property::NEW_VALUE => Some(()),
+        _ => None,
+      })?;
+      let chunk = self.connection.get_property(true, window, property, AtomEnum::ANY, 0, u32::MAX / 4)
+        .map_err(x11_error)?.reply().map_err(x11_error)?;
+      if chunk.value.is_empty() {
+        return Ok(Some(value));
+      }
+      value.extend(chunk.value);
+    }
+  }
+
+  fn owner(&self) -> Result<Window, String> {
+    Ok(self.connection.get_selection_owner(self.atoms.CLIPBOARD).map_err(x11_error)?.reply().map_err(x11_error)?.owner)
+  }
+
+  /// The `WM_CLASS` class of the owning window, e.g. `firefox`.
+  fn owner_class(&self, owner: Window) -> Option<String> {
+    let property = self.connection.get_property(false, owner, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+      .ok()?.reply().ok()?;
+    // `WM_CLASS` holds the instance and the class name, each NUL terminated.
+    property.value.split(|byte| *byte == 0)
+      .rfind(|part| !part.is_empty())
+      .map(|class| String::from_utf8_lossy(class).to_string())
+  }
+
+  fn read(&mut self) -> Result<Option<ClipboardContent>, String> {
+    self.changed = false;
+    let owner = self.owner()?;
+    if owner == NONE {
+      return Ok(None);
+    }
+    let offered: Vec<Atom> = self.convert(self.atoms.TARGETS)?
+      .map(|value| value.chunks_exact(4).map(|atom| u32::from_ne_bytes(atom.try_into().unwrap())).collect())
+      .unwrap_or_default();
+    let mut content = ClipboardContent {
+      concealed: self.atoms.concealed_markers().iter().any(|marker| offered.contains(marker)),
+      ..Default::default()
+    };
+    for content_type in ContentType::ALL {
+      let target = self.atoms.targets(content_type).into_iter().find(|target| offered.contains(target));
+      if let Some(target) = target {
+        if let Some(data) = self.convert(target)? {
+          content = content.with(content_type, data);
+        }
+      }
+    }
+    content.source = self.owner_class(owner);
+    Ok(Some(content).filter(|content| !content.representations.is_empty()))
+  }
+
+  /// Takes the selection with a window of its own that answers requests for
+  /// every representation until another client takes it.
+  fn write(content: &ClipboardContent) -> Result<(), String> {
+    let representations = content.representations.clone();
+    let (ready, claimed) = mpsc::channel();
+    thread::spawn(move || {
+      if let Err(err) = serve(representations, &ready) {
+        let _ = ready.send(Err(err));
+      }
+    });
+    claimed.recv().map_err(|_| "the selection owner stopped".to_string())?
+  }
+}
+
+fn serve(representations: BTreeMap<ContentType, Vec<u8>>, ready: &mpsc::Sender<Result<(), String>>) -> Result<(), String> {
+  let (connection, window, atoms) = connect()?;
+  let offers: BTreeMap<Atom, Vec<u8>> = representations.iter()
+    .flat_map(|(content_type, data)| atoms.targets(*content_type).into_iter().map(move |target| (target, data.clone())))
+    .collect();
+  connection.set_selection_owner(window, atoms.CLIPBOARD, CURRENT_TIME).map_err(x11_error)?;
+  let owner = connection.get_selection_owner(atoms.CLIPBOARD).map_err(x11_error)?.reply().map_err(x11_error)?.owner;
+  if owner != window {
+    return Err("could not take the clipboard".to_string());
+  }
+  let _ = ready.send(Ok(()));
+
+  // Values too large for one request would need an INCR transfer; they are
+  // refused instead.
+  let max_len = connection.maximum_request_bytes().saturating_sub(32);
+  loop {
+    match connection.wait_for_event().map_err(x11_error)? {
+      Event::SelectionRequest(request) => {
+        let property = if request.property == NONE { request.target } else { request.property };
+        let answered = if request.target == atoms.TARGETS {
+          let targets: Vec<Atom> = std::iter::once(atoms.TARGETS).chain(offers.keys().copied()).collect();
+          connection.change_property32(PropMode::REPLACE, request.requestor, property, AtomEnum::ATOM, &targets).is_ok()
+        } else {
+          match offers.get(&request.target) {
+            Some(data) if data.len() <= max_len => {
+              connection.change_property8(PropMode::REPLACE, request.requestor, property, request.target, data).is_ok()
+            },
+            _ => false,
+          }
+        };
+        let notify = SelectionNotifyEvent {
+          response_type: SELECTION_NOTIFY_EVENT,
+          sequence: 0,
+          time: request.time,
+          requestor: request.requestor,
+          selection: request.selection,
+          target: request.target,
+          property: if answered { property } else { NONE },
+        };
+        connection.send_event(false, request.requestor, EventMask::NO_EVENT, notify).map_err(x11_error)?;
+        connection.flush().map_err(x11_error)?;
+      },
+      Event::SelectionClear(_) => return Ok(()),
+      _ => {},
+    }
+  }
+}
+
+/// The clipboard of a Wayland session, through `wl-paste` and `wl-copy` from
+/// wl-clipboard: compositors don't let unfocused clients use the clipboard
+/// otherwise. `wl-copy` offers a single type, so writes restore the most
+/// specific representation only.
+pub struct WaylandClipboard {
+  changed: Arc<AtomicBool>,
+  watcher: Option<Child>,
+}
+
+impl WaylandClipboard {
+  /// `None` when wl-clipboard isn't installed.
+  fn new() -> Option<Self> {
+    Command::new("wl-paste").arg("--version").stdout(Stdio::null()).status().ok()?;
+    let changed = Arc::new(AtomicBool::new(true));
+    // `wl-paste --watch` runs the command on every change; each line it
+    // prints marks the clipboard as changed.
+    let watcher = Command::new("wl-paste").args(["--watch", "echo"]).stdout(Stdio::piped()).spawn().ok();
+    let mut wayland = WaylandClipboard { changed, watcher };
+    if let Some(stdout) = wayland.watcher.as_mut().and_then(|watcher| watcher.stdout.take()) {
+      let changed = wayland.changed.clone();
+      thread::spawn(move || {
+        for _ in BufReader::new(stdout).lines() {
+          changed.store(true, Ordering::SeqCst);
+        }
+      });
+    }
+    Some(wayland)
+  }
+
+  /// Without a watcher every poll reads the clipboard.
+  fn has_changed(&mut self) -> bool {
+    self.watcher.is_none() || self.changed.swap(false, Ordering::SeqCst)
+  }
+
+  fn read(&mut self) -> Result<Option<ClipboardContent>, String> {
+    let types = Command::new("wl-paste").arg("--list-types").output().map_err(|err| err.to_string())?;
+    // wl-paste fails when the clipboard is empty.
+    if !types.status.success() {
+      return Ok(None);
+    }
+    let offered: Vec<String> = String::from_utf8_lossy(&types.stdout).lines().map(str::to_string).collect();
+    let mut content = ClipboardContent {
+      concealed: offered.iter().any(|mime| CONCEALED_MARKERS.contains(&mime.as_str())),
+      ..Default::default()
+    };
+    for content_type in ContentType::ALL {
+      let mime = offered.iter().find(|mime| mime.as_str() == content_type.mime())
+        .or_else(|| offered.iter().find(|mime| ContentType::from_mime(mime) == Some(content_type)));
+      if let Some(mime) = mime {
+        let output = Command::new("wl-paste").args(["--no-newline", "--type", mime]).output().map_err(|err| err.to_string())?;
+        if output.status.success() {
+          content = content.with(content_type, output.stdout);
+        }
+      }
+    }
+    Ok(Some(content).filter(|content| !content.representations.is_empty()))
+  }
+
+  fn write(content: &ClipboardContent) -> Result<(), String> {
+    let preferred = [ContentType::Files, ContentType::Image, ContentType::Text, ContentType::Html, ContentType::Rtf];
+    let (content_type, data) = preferred.iter()
+      .find_map(|content_type| content.get(*content_type).map(|data| (content_type, data)))
+      .ok_or("nothing to copy")?;
+    let mut child = Command::new("wl-copy").args(["--type", content_type.mime()]).stdin(Stdio::piped()).spawn()
+      .map_err(|err| err.to_string())?;
+    child.stdin.take().unwrap().write_all(data).map_err(|err| err.to_string())?;
+    child.wait().map_err(|err| err.to_string())?;
+    Ok(())
+  }
+}
+
+impl Drop for WaylandClipboard {
+  fn drop(&mut self) {
+    if let Some(watcher) = &mut self.watcher {
+      let _ = watcher.kill();
+      let _ = watcher.wait();
+    }
+  }
+}
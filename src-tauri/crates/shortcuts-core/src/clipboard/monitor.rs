@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::backend::{ClipboardContent, SharedBackend};
+
+/// How often the system clipboard is checked for a new copy.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls a clipboard and reports every content that differs from the previous
+/// one. Blank contents are ignored. Most platforms offer no change
+/// notification an unfocused app can subscribe to, hence the polling; the
+/// backends that do have one skip reads while nothing changed.
+pub struct ClipboardMonitor {
+  running: Arc<AtomicBool>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl ClipboardMonitor {
+  pub fn start(backend: SharedBackend, interval: Duration, mut on_copy: impl FnMut(ClipboardContent) + Send + 'static) -> Self {
+    let running = Arc::new(AtomicBool::new(true));
+    let thread_running = running.clone();
+    let thread = thread::spawn(move || {
+      let mut last: Option<String> = None;
+      let mut last_error: Option<String> = None;
+      while thread_running.load(Ordering::SeqCst) {
+        let read = {
+          let mut backend = backend.lock().unwrap();
+          if backend.has_changed() { backend.read() } else { Ok(None) }
+        };
+        match read {
+          Ok(Some(content)) if last.as_ref() != Some(&content.hash()) => {
+            last = Some(content.hash());
+            if !content.is_blank() {
+              on_copy(content);
+            }
+          },
+          Ok(_) => {},
+          Err(err) => {
+            if last_error.as_ref() != Some(&err) {
+              println!("Failed to read the clipboard: {}", err);
+              last_error = Some(err);
+            }
+          },
+        }
+        thread::sleep(interval);
+      }
+    });
+    ClipboardMonitor { running, thread: Some(thread) }
+  }
+
+  pub fn stop(&mut self) {
+    self.running.store(false, Ordering::SeqCst);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for ClipboardMonitor {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::clipboard::backend::{ContentType, MemoryClipboard};
+  use std::sync::mpsc;
+
+  #[test]
+  fn test_reports_new_copies() {
+    let clipboard = MemoryClipboard::new();
+    clipboard.copy("first", Some("terminal"));
+    let (sender, receiver) = mpsc::channel();
+    let mut monitor = ClipboardMonitor::start(clipboard.shared(), Duration::from_millis(5), move |content| {
+      let _ = sender.send(content);
+    });
+    let next = || receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(next(), ClipboardContent::text("first").with_source(Some("terminal")));
+
+    thread::sleep(Duration::from_millis(30));
+    clipboard.copy("  ", None);
+    thread::sleep(Duration::from_millis(30));
+    clipboard.copy_content(ClipboardContent::text("second").with(ContentType::Html, "<b>second</b>"));
+    assert_eq!(next().types(), vec![ContentType::Text, ContentType::Html]);
+    monitor.stop();
+    assert!(receiver.try_recv().is_err());
+  }
+}
@@ -0,0 +1,23 @@
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait before pasting, so the keys of the shortcut that
+/// triggered it are released and the target app sees the new content.
+const PASTE_DELAY: Duration = Duration::from_millis(100);
+
+#[cfg(target_os = "macos")]
+const PASTE_MODIFIER: Key = Key::Meta;
+#[cfg(not(target_os = "macos"))]
+const PASTE_MODIFIER: Key = Key::Control;
+
+/// Pastes the clipboard into the focused app by pressing the platform's
+/// paste keys, e.g. Ctrl+V.
+pub fn paste() -> Result<(), String> {
+  thread::sleep(PASTE_DELAY);
+  let mut enigo = Enigo::new(&Settings::default()).map_err(|err| err.to_string())?;
+  enigo.key(PASTE_MODIFIER, Direction::Press).map_err(|err| err.to_string())?;
+  let pasted = enigo.key(Key::Unicode('v'), Direction::Click).map_err(|err| err.to_string());
+  enigo.key(PASTE_MODIFIER, Direction::Release).map_err(|err| err.to_string())?;
+  pasted
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::backend::{ClipboardContent, ContentType};
+
+/// Copies collected while the paste stack is on, to paste one after another
+/// in the order they were copied, e.g. to fill in a form.
+#[derive(Debug, Default)]
+pub struct PasteStack {
+  active: bool,
+  queue: VecDeque<ClipboardContent>,
+  /// Hash of the content last popped. Putting it on the clipboard to paste
+  /// it reads back as a copy, which must not be queued again.
+  popped: Option<String>,
+}
+
+/// What the panel shows of the paste stack.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct PasteStackState {
+  pub active: bool,
+  /// Next to paste first.
+  pub items: Vec<PasteStackItem>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct PasteStackItem {
+  pub text: String,
+  pub types: Vec<ContentType>,
+}
+
+pub static PASTE_STACK: Mutex<PasteStack> = Mutex::new(PasteStack::new());
+
+impl PasteStack {
+  pub const fn new() -> Self {
+    PasteStack { active: false, queue: VecDeque::new(), popped: None }
+  }
+
+  pub fn is_active(&self) -> bool {
+    self.active
+  }
+
+  /// Turns the paste stack on or off, returning whether it is on. Either
+  /// way it starts out empty.
+  pub fn toggle(&mut self) -> bool {
+    self.active = !self.active;
+    self.clear();
+    self.active
+  }
+
+  pub fn clear(&mut self) {
+    self.queue.clear();
+    self.popped = None;
+  }
+
+  /// Queues a copy while the stack is on, returning whether it was queued.
+  pub fn push(&mut self, content: &ClipboardContent) -> bool {
+    let popped = self.popped.take();
+    if !self.active || popped == Some(content.hash()) {
+      return false;
+    }
+    self.queue.push_back(content.clone());
+    true
+  }
+
+  /// The earliest queued copy, which is taken off the stack.
+  pub fn pop(&mut self) -> Option<ClipboardContent> {
+    let content = self.queue.pop_front()?;
+    self.popped = Some(content.hash());
+    Some(content)
+  }
+
+  pub fn len(&self) -> usize {
+    self.queue.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.queue.is_empty()
+  }
+
+  pub fn state(&self) -> PasteStackState {
+    PasteStackState {
+      active: self.active,
+      items: self.queue.iter().map(|content| PasteStackItem { text: content.summary(), types: content.types() }).collect(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_paste_stack() {
+    let mut stack = PasteStack::new();
+    assert!(!stack.push(&ClipboardContent::text("ignored")), "the stack is off");
+    assert!(stack.toggle());
+    for text in ["name", "street", "city"] {
+      assert!(stack.push(&ClipboardContent::text(text)));
+    }
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.state().items[0], PasteStackItem { text: "name".to_string(), types: vec![ContentType::Text] });
+
+    let first = stack.pop().unwrap();
+    assert_eq!(first.plain_text(), Some("name"));
+    assert!(!stack.push(&first), "pasting it reads back as a copy");
+    assert!(stack.push(&ClipboardContent::text("name")), "only right after it was popped");
+    assert_eq!(stack.pop().unwrap().plain_text(), Some("street"));
+    assert_eq!(stack.pop().unwrap().plain_text(), Some("city"));
+    assert_eq!(stack.pop().unwrap().plain_text(), Some("name"));
+    assert!(stack.pop().is_none());
+
+    stack.push(&ClipboardContent::text("left over"));
+    assert!(!stack.toggle());
+    assert!(stack.is_empty());
+    assert_eq!(stack.state(), PasteStackState { active: false, items: vec![] });
+  }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::backend::ContentType;
+use crate::shortcut::Code;
+
+/// How long the second step of a register chord is waited for.
+pub const CHORD_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The keys that complete a register chord: a letter naming the register,
+/// or Escape to cancel it. They are only grabbed while a chord is pending.
+pub const CHORD_KEYS: [Code; 27] = [
+  Code::KeyA, Code::KeyB, Code::KeyC, Code::KeyD, Code::KeyE, Code::KeyF, Code::KeyG, Code::KeyH, Code::KeyI,
+  Code::KeyJ, Code::KeyK, Code::KeyL, Code::KeyM, Code::KeyN, Code::KeyO, Code::KeyP, Code::KeyQ, Code::KeyR,
+  Code::KeyS, Code::KeyT, Code::KeyU, Code::KeyV, Code::KeyW, Code::KeyX, Code::KeyY, Code::KeyZ, Code::Escape,
+];
+
+/// A named register, as the panel lists it.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Register {
+  /// `a` to `z`.
+  pub name: char,
+  /// The plain text, file paths or text of the HTML kept.
+  pub text: String,
+  pub types: Vec<ContentType>,
+  /// Unix time in milliseconds it was last set.
+  pub updated_at: u64,
+}
+
+pub fn check_name(name: char) -> Result<(), String> {
+  match name {
+    'a'..='z' => Ok(()),
+    _ => Err(format!("`{}` is not a register; they are named a to z", name)),
+  }
+}
+
+/// The register a chord key names, if it is a letter.
+pub fn register_name(code: Code) -> Option<char> {
+  let index = CHORD_KEYS[..26].iter().position(|key| *key == code)?;
+  Some((b'a' + index as u8) as char)
+}
+
+/// What a register chord does once its letter is pressed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RegisterOp {
+  /// Saves the clipboard into the register.
+  Copy,
+  /// Puts the register on the clipboard and pastes it.
+  Paste,
+}
+
+/// A register chord waiting for its second step.
+#[derive(Debug, Default)]
+pub struct ChordState {
+  pending: Option<(RegisterOp, Instant)>,
+}
+
+pub static CHORD: Mutex<ChordState> = Mutex::new(ChordState { pending: None });
+
+impl ChordState {
+  /// Starts a chord, replacing a pending one.
+  pub fn begin(&mut self, op: RegisterOp, now: Instant) {
+    self.pending = Some((op, now));
+  }
+
+  pub fn is_pending(&self) -> bool {
+    self.pending.is_some()
+  }
+
+  /// Ends the pending chord with the key pressed, returning what to do with
+  /// which register. Escape, any other key and a late key cancel it.
+  pub fn complete(&mut self, code: Code, now: Instant) -> Option<(RegisterOp, char)> {
+    let (op, started) = self.pending.take()?;
+    if now.duration_since(started) >= CHORD_TIMEOUT {
+      return None;
+    }
+    register_name(code).map(|name| (op, name))
+  }
+
+  /// Cancels the pending chord once it timed out, returning whether it did.
+  pub fn expire(&mut self, now: Instant) -> bool {
+    match self.pending {
+      Some((_, started)) if now.duration_since(started) >= CHORD_TIMEOUT => {
+        self.pending = None;
+        true
+      },
+      _ => false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_register_names() {
+    assert_eq!(register_name(Code::KeyA), Some('a'));
+    assert_eq!(register_name(Code::KeyZ), Some('z'));
+    assert_eq!(register_name(Code::Escape), None);
+    assert_eq!(register_name(Code::Digit1), None);
+    assert!(check_name('q').is_ok());
+    assert!(check_name('Q').is_err());
+    assert!(check_name('1').is_err());
+  }
+
+  #[test]
+  fn test_chord() {
+    let start = Instant::now();
+    let mut chord = ChordState::default();
+    assert_eq!(chord.complete(Code::KeyA, start), None, "nothing pending");
+
+    chord.begin(RegisterOp::Copy, start);
+    assert!(chord.is_pending());
+    assert!(!chord.expire(start + Duration::from_secs(1)));
+    assert_eq!(chord.complete(Code::KeyR, start + Duration::from_secs(1)), Some((RegisterOp::Copy, 'r')));
+    assert!(!chord.is_pending());
+
+    chord.begin(RegisterOp::Paste, start);
+    assert_eq!(chord.complete(Code::Escape, start), None);
+    assert!(!chord.is_pending(), "escape cancels");
+
+    chord.begin(RegisterOp::Paste, start);
+    assert_eq!(chord.complete(Code::KeyA, start + CHORD_TIMEOUT), None, "too late");
+    chord.begin(RegisterOp::Paste, start);
+    assert!(chord.expire(start + CHORD_TIMEOUT));
+    assert!(!chord.is_pending());
+  }
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::backend::ContentType;
+
+pub const DEFAULT_CAPACITY: usize = 200;
+
+/// How much of the clipboard history is kept. The limits apply to unpinned
+/// entries only, dropping the least recently copied first; pinned entries
+/// are kept until they are unpinned or removed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RetentionPolicy {
+  /// At least one.
+  pub max_count: usize,
+  pub max_age: Option<Duration>,
+  /// Total size of the representations, before encryption.
+  pub max_bytes: Option<u64>,
+  /// Entries offering a type, e.g. at most 20 with an image.
+  pub type_limits: BTreeMap<ContentType, usize>,
+}
+
+impl Default for RetentionPolicy {
+  fn default() -> Self {
+    RetentionPolicy { max_count: DEFAULT_CAPACITY, max_age: None, max_bytes: None, type_limits: BTreeMap::new() }
+  }
+}
+
+/// Parses per-type limits written as `type:count`, e.g. `image:20`.
+pub fn parse_type_limits(limits: &[String]) -> Result<BTreeMap<ContentType, usize>, String> {
+  limits.iter().map(|limit| {
+    let (content_type, count) = limit.split_once(':').ok_or_else(|| format!("`{}` is not `type:count`", limit))?;
+    let count = count.trim().parse().map_err(|_| format!("`{}` is not a count", count))?;
+    Ok((content_type.trim().parse()?, count))
+  }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_type_limits() {
+    let limits = parse_type_limits(&["image:20".to_string(), " files : 5".to_string()]).unwrap();
+    assert_eq!(limits, BTreeMap::from([(ContentType::Image, 20), (ContentType::Files, 5)]));
+    assert!(parse_type_limits(&["image".to_string()]).is_err());
+    assert!(parse_type_limits(&["video:2".to_string()]).is_err());
+    assert!(parse_type_limits(&["image:-1".to_string()]).is_err());
+  }
+}
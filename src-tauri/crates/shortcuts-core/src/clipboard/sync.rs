@@ -0,0 +1,579 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+use zeroize::Zeroizing;
+
+use super::backend::{ClipboardContent, ContentType};
+use super::crypto::write_key_file;
+
+// Peers pair by trusting each other's ed25519 identity key. A connection
+// starts with both sides sending a hello: `PROTOCOL`, their identity key
+// and a fresh X25519 key. Each then signs the hash of both hellos with its
+// identity, tagged with its role, and checks the other's signature and
+// that its identity is paired. Both directions are then encrypted with
+// ChaCha20-Poly1305 under keys derived from the X25519 secret, with a frame
+// counter as the nonce, so frames can't be replayed or reordered. Frames
+// are a big endian u32 length followed by that many bytes.
+const PROTOCOL: &[u8] = b"shortcuts-clipboard-sync-1";
+const HELLO_LEN: usize = PROTOCOL.len() + 64;
+const INITIATOR: &[u8] = b"initiator";
+const RESPONDER: &[u8] = b"responder";
+
+/// Where the identity key is kept.
+const IDENTITY_PATH: &str = ".shortcuts/clipboard-sync.key";
+pub const DEFAULT_PORT: u16 = 47800;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the listener is checked for connections and unconnected peers
+/// with an address are dialed.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a peer may take to take in a frame before its connection is
+/// dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Frames waiting to be written to one peer. A peer that falls this far
+/// behind is dropped instead of holding up the others.
+const SEND_QUEUE: usize = 8;
+/// Incoming connections authenticated at once; more are closed right away.
+const MAX_HANDSHAKES: usize = 4;
+
+/// What identifies this device to its peers. Pairing two devices means
+/// adding each one's [`public_key`](Self::public_key) to the other's peers.
+pub struct SyncIdentity(SigningKey);
+
+impl SyncIdentity {
+  pub fn generate() -> Self {
+    let mut secret = Zeroizing::new([0; 32]);
+    OsRng.fill_bytes(secret.as_mut_slice());
+    SyncIdentity(SigningKey::from_bytes(&secret))
+  }
+
+  /// The identity in the key file at `path`, relative to the home
+  /// directory, creating it on first use.
+  pub fn load_or_create(path: Option<String>) -> Result<Self, String> {
+    let path = dirs::home_dir().unwrap().join(path.unwrap_or_else(|| IDENTITY_PATH.to_string()));
+    if let Ok(hex) = std::fs::read_to_string(&path) {
+      let bytes = Zeroizing::new(hex::decode(hex.trim()).map_err(|_| "the sync key file is corrupt".to_string())?);
+      let secret: [u8; 32] = bytes.as_slice().try_into().map_err(|_| "the sync key file is corrupt".to_string())?;
+      return Ok(SyncIdentity(SigningKey::from_bytes(&secret)));
+    }
+    let identity = SyncIdentity::generate();
+    write_key_file(&path, identity.0.as_bytes())?;
+    Ok(identity)
+  }
+
+  /// Hex encoded ed25519 public key.
+  pub fn public_key(&self) -> String {
+    hex::encode(self.0.verifying_key().as_bytes())
+  }
+}
+
+/// A paired device, written as `<public key>` or `<public key>@<host>:<port>`.
+/// Peers without an address are only connected to once discovered, or when
+/// they connect themselves.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyncPeer {
+  /// Hex encoded ed25519 public key.
+  pub key: String,
+  pub address: Option<String>,
+}
+
+impl FromStr for SyncPeer {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let (key, address) = match value.trim().split_once('@') {
+      Some((key, address)) => (key, Some(address.trim().to_string())),
+      None => (value.trim(), None),
+    };
+    parse_key(key).map_err(|_| format!("`{}` is not a public key", key))?;
+    if address.as_ref().is_some_and(|address| address.rsplit_once(':').is_none_or(|(_, port)| port.parse::<u16>().is_err())) {
+      return Err(format!("`{}` is not <host>:<port>", value));
+    }
+    Ok(SyncPeer { key: key.to_lowercase(), address })
+  }
+}
+
+fn parse_key(key: &str) -> Result<VerifyingKey, String> {
+  let bytes: [u8; 32] = hex::decode(key.trim()).ok().and_then(|bytes| bytes.try_into().ok())
+    .ok_or_else(|| "invalid key".to_string())?;
+  VerifyingKey::from_bytes(&bytes).map_err(|err| err.to_string())
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyncConfig {
+  /// Port to listen on; 0 picks a free one.
+  pub port: u16,
+  pub peers: Vec<SyncPeer>,
+  /// Copies larger than this, counting every representation, are neither
+  /// sent nor accepted.
+  pub max_bytes: usize,
+}
+
+/// A copy made on a peer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyncClip {
+  pub content: ClipboardContent,
+  /// Unix time in milliseconds it was copied at, by the peer's clock.
+  pub copied_at: u64,
+  /// Public key of the peer it was copied on.
+  pub origin: String,
+}
+
+/// What the panel shows of clipboard sync.
+#[derive(Debug, Default, Serialize, PartialEq, Clone)]
+pub struct SyncStatus {
+  pub enabled: bool,
+  /// This device's key, for pairing it on the others.
+  pub public_key: Option<String>,
+  pub port: Option<u16>,
+  /// Keys of the peers connected now.
+  pub connected: Vec<String>,
+}
+
+/// What a frame carries, representations base64 encoded.
+#[derive(Deserialize, Serialize)]
+struct WireClip {
+  copied_at: u64,
+  source: Option<String>,
+  representations: BTreeMap<ContentType, String>,
+}
+
+fn content_size(content: &ClipboardContent) -> usize {
+  content.representations.values().map(Vec::len).sum()
+}
+
+/// Settles which copy ends up on the clipboard when peers copy at about the
+/// same time: the latest by copy time, ties going to the greater origin
+/// key, so every peer settles on the same one.
+#[derive(Debug, Default)]
+pub struct Resolver {
+  latest: Option<(u64, String)>,
+  /// Hash of the last copy put on the clipboard from a peer, which reads
+  /// back as a local copy that must not be sent back.
+  applied: Option<String>,
+}
+
+impl Resolver {
+  pub const fn new() -> Self {
+    Resolver { latest: None, applied: None }
+  }
+
+  /// A copy made here, returning whether to send it to the peers.
+  pub fn local(&mut self, content: &ClipboardContent, copied_at: u64, origin: &str) -> bool {
+    if self.applied.take() == Some(content.hash()) {
+      return false;
+    }
+    self.latest = Some((copied_at, origin.to_string()));
+    true
+  }
+
+  /// A copy from a peer, returning whether to put it on the clipboard. It is
+  /// not when a later copy was made already.
+  pub fn remote(&mut self, clip: &SyncClip) -> bool {
+    let candidate = (clip.copied_at, clip.origin.clone());
+    if self.latest.as_ref().is_some_and(|latest| *latest >= candidate) {
+      return false;
+    }
+    self.latest = Some(candidate);
+    self.applied = Some(clip.content.hash());
+    true
+  }
+}
+
+/// One direction of an encrypted connection.
+struct FrameCipher {
+  cipher: ChaCha20Poly1305,
+  counter: u64,
+}
+
+impl FrameCipher {
+  fn new(key: &[u8; 32]) -> Self {
+    FrameCipher { cipher: ChaCha20Poly1305::new(Key::from_slice(key)), counter: 0 }
+  }
+
+  fn nonce(&mut self) -> Nonce {
+    let mut nonce = [0; 12];
+    nonce[..8].copy_from_slice(&self.counter.to_le_bytes());
+    self.counter += 1;
+    *Nonce::from_slice(&nonce)
+  }
+
+  fn seal(&mut self, plaintext: &[u8]) -> Vec<u8> {
+    let nonce = self.nonce();
+    self.cipher.encrypt(&nonce, plaintext).expect("encryption does not fail")
+  }
+
+  fn open(&mut self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = self.nonce();
+    self.cipher.decrypt(&nonce, sealed).map_err(|_| "a frame failed to decrypt".to_string())
+  }
+}
+
+fn write_frame(stream: &mut TcpStream, frame: &[u8]) -> Result<(), String> {
+  let len = u32::try_from(frame.len()).map_err(|_| "the frame is too large".to_string())?;
+  stream.write_all(&len.to_be_bytes()).and_then(|_| stream.write_all(frame)).map_err(|err| err.to_string())
+}
+
+fn read_frame(stream: &mut TcpStream, max_len: usize) -> Result<Vec<u8>, String> {
+  let mut len = [0; 4];
+  stream.read_exact(&mut len).map_err(|err| err.to_string())?;
+  let len = u32::from_be_bytes(len) as usize;
+  if len > max_len {
+    return Err(format!("a {} byte frame is over the limit", len));
+  }
+  let mut frame = vec![0; len];
+  stream.read_exact(&mut frame).map_err(|err| err.to_string())?;
+  Ok(frame)
+}
+
+/// Authenticates a fresh connection, returning the peer's public key and
+/// the ciphers to send and receive with. Fails unless `is_paired` accepts
+/// the peer.
+fn handshake(
+  stream: &mut TcpStream,
+  identity: &SigningKey,
+  initiator: bool,
+  is_paired: impl Fn(&str) -> bool,
+) -> Result<(String, FrameCipher, FrameCipher), String> {
+  let ephemeral = EphemeralSecret::random_from_rng(OsRng);
+  let hello = [PROTOCOL, identity.verifying_key().as_bytes(), PublicKey::from(&ephemeral).as_bytes()].concat();
+  write_frame(stream, &hello)?;
+  let peer_hello = read_frame(stream, HELLO_LEN)?;
+  if peer_hello.len() != HELLO_LEN || !peer_hello.starts_with(PROTOCOL) {
+    return Err("the peer speaks another protocol".to_string());
+  }
+  let peer_identity = &peer_hello[PROTOCOL.len()..PROTOCOL.len() + 32];
+  let peer_key = hex::encode(peer_identity);
+  if !is_paired(&peer_key) {
+    return Err(format!("{} is not paired", peer_key));
+  }
+  let peer_identity = parse_key(&peer_key)?;
+  let peer_ephemeral: [u8; 32] = peer_hello[PROTOCOL.len() + 32..].try_into().unwrap();
+
+  let (own_role, peer_role, transcript) = match initiator {
+    true => (INITIATOR, RESPONDER, [hello.as_slice(), &peer_hello].concat()),
+    false => (RESPONDER, INITIATOR, [peer_hello.as_slice(), &hello].concat()),
+  };
+  let transcript_hash = Sha256::digest(&transcript);
+  write_frame(stream, &identity.sign(&[own_role, &transcript_hash].concat()).to_bytes())?;
+  let signature = Signature::from_slice(&read_frame(stream, 64)?).map_err(|err| err.to_string())?;
+  peer_identity.verify(&[peer_role, &transcript_hash].concat(), &signature)
+    .map_err(|_| format!("{} failed to prove its identity", peer_key))?;
+
+  let shared = ephemeral.diffie_hellman(&PublicKey::from(peer_ephemeral));
+  if !shared.was_contributory() {
+    return Err("the peer sent a weak key".to_string());
+  }
+  let hkdf = Hkdf::<Sha256>::new(Some(&transcript_hash), shared.as_bytes());
+  let mut keys = Zeroizing::new([[0; 32]; 2]);
+  for (key, info) in keys.iter_mut().zip([b"initiator to responder", b"responder to initiator"]) {
+    hkdf.expand(info, key).map_err(|err| err.to_string())?;
+  }
+  let (send, receive) = if initiator { (keys[0], keys[1]) } else { (keys[1], keys[0]) };
+  Ok((peer_key, FrameCipher::new(&send), FrameCipher::new(&receive)))
+}
+
+struct Connection {
+  id: u64,
+  initiator: bool,
+  /// Another handle on the stream the reader thread reads from.
+  stream: TcpStream,
+  /// Frames for the writer thread to seal and write.
+  frames: SyncSender<Vec<u8>>,
+}
+
+struct Shared {
+  identity: SigningKey,
+  config: Mutex<SyncConfig>,
+  /// By peer key; a peer connecting again replaces its connection.
+  connections: Mutex<HashMap<String, Connection>>,
+  next_id: AtomicU64,
+  running: AtomicBool,
+  handshakes: AtomicUsize,
+  on_clip: Box<dyn Fn(SyncClip) + Send + Sync>,
+}
+
+impl Shared {
+  fn is_paired(&self, key: &str) -> bool {
+    self.config.lock().unwrap().peers.iter().any(|peer| peer.key == key)
+  }
+
+  /// Frames carry base64 in JSON, which takes a third more than the copy.
+  fn max_frame(&self) -> usize {
+    self.config.lock().unwrap().max_bytes / 3 * 4 + 64 * 1024
+  }
+
+  fn connect(self: &Arc<Self>, address: &str) -> Result<String, String> {
+    let address = address.to_socket_addrs().map_err(|err| err.to_string())?.next()
+      .ok_or_else(|| format!("{} has no address", address))?;
+    let stream = TcpStream::connect_timeout(&address, HANDSHAKE_TIMEOUT).map_err(|err| err.to_string())?;
+    self.add(stream, true)
+  }
+
+  /// Authenticates a connection and reads from it in the background,
+  /// returning the peer's key.
+  fn add(self: &Arc<Self>, mut stream: TcpStream, initiator: bool) -> Result<String, String> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|err| err.to_string())?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT)).map_err(|err| err.to_string())?;
+    let (peer, mut send, mut receive) = handshake(&mut stream, &self.identity, initiator, |key| self.is_paired(key))?;
+    stream.set_read_timeout(None).map_err(|err| err.to_string())?;
+    let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+    let handle = stream.try_clone().map_err(|err| err.to_string())?;
+    let mut writer = stream.try_clone().map_err(|err| err.to_string())?;
+    let (frames, queued) = mpsc::sync_channel::<Vec<u8>>(SEND_QUEUE);
+    {
+      // When both dialed each other, both keep the connection dialed by the
+      // greater key. Otherwise the newer connection replaces the older.
+      let own = hex::encode(self.identity.verifying_key().as_bytes());
+      let dialer = |initiator: bool| if initiator { own.clone() } else { peer.clone() };
+      let mut connections = self.connections.lock().unwrap();
+      if connections.get(&peer).is_some_and(|kept| kept.initiator != initiator && dialer(kept.initiator) > dialer(initiator)) {
+        let _ = stream.shutdown(Shutdown::Both);
+        return Ok(peer);
+      }
+      if let Some(replaced) = connections.insert(peer.clone(), Connection { id, initiator, stream: handle, frames }) {
+        let _ = replaced.stream.shutdown(Shutdown::Both);
+      }
+    }
+
+    // Ends once the connection is dropped from the map, or fails to write,
+    // which the reader then notices.
+    thread::spawn(move || {
+      for frame in queued {
+        if write_frame(&mut writer, &send.seal(&frame)).is_err() {
+          let _ = writer.shutdown(Shutdown::Both);
+          return;
+        }
+      }
+    });
+
+    let shared = self.clone();
+    let origin = peer.clone();
+    thread::spawn(move || {
+      let result: Result<(), String> = (|| loop {
+        let frame = receive.open(&read_frame(&mut stream, shared.max_frame())?)?;
+        let wire: WireClip = serde_json::from_slice(&frame).map_err(|err| err.to_string())?;
+        let mut content = ClipboardContent::default().with_source(wire.source.as_deref());
+        for (content_type, data) in wire.representations {
+          content = content.with(content_type, STANDARD.decode(data).map_err(|err| err.to_string())?);
+        }
+        // Copies over the size limit are ignored.
+        if content_size(&content) > shared.config.lock().unwrap().max_bytes {
+          continue;
+        }
+        (shared.on_clip)(SyncClip { content, copied_at: wire.copied_at, origin: origin.clone() });
+      })();
+      let mut connections = shared.connections.lock().unwrap();
+      if connections.get(&origin).is_some_and(|connection| connection.id == id) {
+        connections.remove(&origin);
+        if shared.running.load(Ordering::SeqCst) {
+          println!("Lost the clipboard sync connection to {}: {}", origin, result.unwrap_err());
+        }
+      }
+    });
+    Ok(peer)
+  }
+}
+
+/// Exchanges copies with the paired peers over encrypted TCP connections.
+/// It listens for them, and dials the ones with an address until they are
+/// connected.
+pub struct ClipboardSync {
+  shared: Arc<Shared>,
+  local_addr: SocketAddr,
+}
+
+impl ClipboardSync {
+  /// Starts listening on every interface, calling `on_clip` for every copy
+  /// a peer sends.
+  pub fn start(identity: &SyncIdentity, config: SyncConfig, on_clip: impl Fn(SyncClip) + Send + Sync + 'static) -> Result<Self, String> {
+    let listener = TcpListener::bind(("0.0.0.0", config.port)).map_err(|err| err.to_string())?;
+    listener.set_nonblocking(true).map_err(|err| err.to_string())?;
+    let local_addr = listener.local_addr().map_err(|err| err.to_string())?;
+    let shared = Arc::new(Shared {
+      identity: identity.0.clone(),
+      config: Mutex::new(config),
+      connections: Mutex::new(HashMap::new()),
+      next_id: AtomicU64::new(0),
+      running: AtomicBool::new(true),
+      handshakes: AtomicUsize::new(0),
+      on_clip: Box::new(on_clip),
+    });
+
+    let accepting = shared.clone();
+    thread::spawn(move || {
+      while accepting.running.load(Ordering::SeqCst) {
+        match listener.accept() {
+          Ok((stream, _)) => {
+            if accepting.handshakes.fetch_add(1, Ordering::SeqCst) >= MAX_HANDSHAKES {
+              accepting.handshakes.fetch_sub(1, Ordering::SeqCst);
+              continue;
+            }
+            let adding = accepting.clone();
+            thread::spawn(move || {
+              // Unpaired or failed handshakes are just closed.
+              let _ = stream.set_nonblocking(false).map_err(|err| err.to_string()).and_then(|_| adding.add(stream, false));
+              adding.handshakes.fetch_sub(1, Ordering::SeqCst);
+            });
+          },
+          Err(err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+          Err(err) => {
+            println!("Failed to accept a clipboard sync connection: {}", err);
+            thread::sleep(POLL_INTERVAL);
+          },
+        }
+      }
+    });
+
+    let dialing = shared.clone();
+    thread::spawn(move || {
+      while dialing.running.load(Ordering::SeqCst) {
+        dialing_round(&dialing);
+        let dialed = Instant::now();
+        while dialed.elapsed() < RECONNECT_INTERVAL && dialing.running.load(Ordering::SeqCst) {
+          thread::sleep(POLL_INTERVAL);
+        }
+      }
+    });
+    Ok(ClipboardSync { shared, local_addr })
+  }
+
+  pub fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+
+  /// Dials a peer now, returning its key once connected.
+  pub fn connect(&self, address: &str) -> Result<String, String> {
+    self.shared.connect(address)
+  }
+
+  /// Sets where a paired peer is found, e.g. once it was discovered.
+  pub fn set_address(&self, key: &str, address: &str) {
+    let mut config = self.shared.config.lock().unwrap();
+    if let Some(peer) = config.peers.iter_mut().find(|peer| peer.key == key) {
+      peer.address = Some(address.to_string());
+    }
+  }
+
+  /// Public keys of the connected peers, sorted.
+  pub fn connected(&self) -> Vec<String> {
+    let mut keys: Vec<String> = self.shared.connections.lock().unwrap().keys().cloned().collect();
+    keys.sort();
+    keys
+  }
+
+  /// Queues a copy made here for every connected peer, returning for how
+  /// many. Copies over the size limit are not sent, and peers too far behind
+  /// to take it are dropped.
+  pub fn send(&self, content: &ClipboardContent, copied_at: u64) -> Result<usize, String> {
+    if content_size(content) > self.shared.config.lock().unwrap().max_bytes {
+      return Ok(0);
+    }
+    let wire = WireClip {
+      copied_at,
+      source: content.source.clone(),
+      representations: content.representations.iter().map(|(content_type, data)| (*content_type, STANDARD.encode(data))).collect(),
+    };
+    let frame = serde_json::to_vec(&wire).map_err(|err| err.to_string())?;
+    let connections = self.shared.connections.lock().unwrap();
+    let mut sent = 0;
+    for connection in connections.values() {
+      match connection.frames.try_send(frame.clone()) {
+        Ok(()) => sent += 1,
+        // The reader notices the closed connection and removes it.
+        Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+          let _ = connection.stream.shutdown(Shutdown::Both);
+        },
+      }
+    }
+    Ok(sent)
+  }
+
+  /// Closes the listener and every connection.
+  pub fn stop(&self) {
+    self.shared.running.store(false, Ordering::SeqCst);
+    for (_, connection) in self.shared.connections.lock().unwrap().drain() {
+      let _ = connection.stream.shutdown(Shutdown::Both);
+    }
+  }
+}
+
+impl Drop for ClipboardSync {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+/// Dials every peer with an address that isn't connected.
+fn dialing_round(shared: &Arc<Shared>) {
+  let peers = shared.config.lock().unwrap().peers.clone();
+  for peer in peers {
+    let Some(address) = peer.address else { continue };
+    if !shared.running.load(Ordering::SeqCst) || shared.connections.lock().unwrap().contains_key(&peer.key) {
+      continue;
+    }
+    // Offline peers are retried next round.
+    let _ = shared.connect(&address);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn clip(text: &str, copied_at: u64, origin: &str) -> SyncClip {
+    SyncClip { content: ClipboardContent::text(text), copied_at, origin: origin.to_string() }
+  }
+
+  #[test]
+  fn test_parse_peer() {
+    let key = SyncIdentity::generate().public_key();
+    assert_eq!(key.parse::<SyncPeer>().unwrap(), SyncPeer { key: key.clone(), address: None });
+    assert_eq!(
+      format!("{}@laptop.local:47800", key.to_uppercase()).parse::<SyncPeer>().unwrap(),
+      SyncPeer { key: key.clone(), address: Some("laptop.local:47800".to_string()) },
+    );
+    assert!(format!("{}@laptop.local", key).parse::<SyncPeer>().is_err());
+    assert!("abcd@10.0.0.2:47800".parse::<SyncPeer>().is_err());
+  }
+
+  #[test]
+  fn test_latest_copy_wins() {
+    let mut resolver = Resolver::default();
+    assert!(resolver.local(&ClipboardContent::text("mine"), 100, "a"));
+    assert!(!resolver.remote(&clip("older", 90, "b")));
+    assert!(!resolver.remote(&clip("tie", 100, "0")), "ties go to the greater key");
+    assert!(resolver.remote(&clip("tie", 100, "b")));
+    assert!(!resolver.local(&ClipboardContent::text("tie"), 101, "a"), "the echo of the applied copy");
+    assert!(resolver.local(&ClipboardContent::text("tie"), 102, "a"), "copying it again");
+    assert!(resolver.remote(&clip("newer", 200, "b")));
+  }
+
+  #[test]
+  fn test_frame_cipher() {
+    let key = [7; 32];
+    let (mut sender, mut receiver) = (FrameCipher::new(&key), FrameCipher::new(&key));
+    let first = sender.seal(b"first");
+    let second = sender.seal(b"second");
+    assert_eq!(receiver.open(&first).unwrap(), b"first");
+    assert!(receiver.open(&first).is_err(), "replayed");
+    let mut receiver = FrameCipher::new(&key);
+    assert!(receiver.open(&second).is_err(), "out of order");
+  }
+}
@@ -0,0 +1,235 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+/// Rewrites the clipboard text, e.g. lowercasing it. The Clipboard extension
+/// offers each registered transform as its own action; other extensions add
+/// theirs with `inventory::submit!`:
+///
+/// ```ignore
+/// shortcuts_sdk::inventory::submit! {
+///   shortcuts_sdk::Transform { id: "reverse", name: "Paste reversed", description: "...", apply: reverse }
+/// }
+/// ```
+pub struct Transform {
+  /// Stable identifier; the action running it is `transform_<id>`.
+  pub id: &'static str,
+  pub name: &'static str,
+  pub description: &'static str,
+  /// A pure function of the text, failing when it doesn't apply, e.g.
+  /// pretty-printing something that isn't JSON.
+  pub apply: fn(&str) -> Result<String, String>,
+}
+
+inventory::collect!(Transform);
+
+const ACTION_PREFIX: &str = "transform_";
+
+impl Transform {
+  pub fn action_id(&self) -> String {
+    format!("{}{}", ACTION_PREFIX, self.id)
+  }
+}
+
+/// Every registered transform, ordered by id. Of several with the same id
+/// only one is kept.
+pub fn transforms() -> Vec<&'static Transform> {
+  let mut transforms: Vec<&'static Transform> = inventory::iter::<Transform>.into_iter().collect();
+  transforms.sort_by_key(|transform| transform.id);
+  transforms.dedup_by_key(|transform| transform.id);
+  transforms
+}
+
+/// The transform run by the action `action`.
+pub fn for_action(action: &str) -> Option<&'static Transform> {
+  let id = action.strip_prefix(ACTION_PREFIX)?;
+  transforms().into_iter().find(|transform| transform.id == id)
+}
+
+inventory::submit! {
+  Transform {
+    id: "plain_text",
+    name: "Paste as plain text",
+    description: "Drop the formatting of the clipboard and keep its text",
+    apply: |text| Ok(text.to_string()),
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "json_pretty",
+    name: "Paste as pretty-printed JSON",
+    description: "Indent the JSON on the clipboard",
+    apply: json_pretty,
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "json_compact",
+    name: "Paste as compact JSON",
+    description: "Remove the whitespace from the JSON on the clipboard",
+    apply: json_compact,
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "lowercase",
+    name: "Paste lowercased",
+    description: "Lowercase the clipboard text",
+    apply: |text| Ok(text.to_lowercase()),
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "uppercase",
+    name: "Paste uppercased",
+    description: "Uppercase the clipboard text",
+    apply: |text| Ok(text.to_uppercase()),
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "trim",
+    name: "Paste trimmed",
+    description: "Remove the whitespace around the clipboard text",
+    apply: |text| Ok(text.trim().to_string()),
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "url_encode",
+    name: "Paste URL-encoded",
+    description: "Percent-encode the clipboard text for use in a URL",
+    apply: |text| Ok(url_encode(text)),
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "url_decode",
+    name: "Paste URL-decoded",
+    description: "Decode the percent-encoded clipboard text",
+    apply: url_decode,
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "base64_encode",
+    name: "Paste base64-encoded",
+    description: "Encode the clipboard text as base64",
+    apply: |text| Ok(STANDARD.encode(text)),
+  }
+}
+
+inventory::submit! {
+  Transform {
+    id: "base64_decode",
+    name: "Paste base64-decoded",
+    description: "Decode the base64 on the clipboard",
+    apply: base64_decode,
+  }
+}
+
+fn json_pretty(text: &str) -> Result<String, String> {
+  let value: serde_json::Value = serde_json::from_str(text).map_err(|err| format!("not JSON: {}", err))?;
+  serde_json::to_string_pretty(&value).map_err(|err| err.to_string())
+}
+
+fn json_compact(text: &str) -> Result<String, String> {
+  let value: serde_json::Value = serde_json::from_str(text).map_err(|err| format!("not JSON: {}", err))?;
+  Ok(value.to_string())
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986.
+fn url_encode(text: &str) -> String {
+  text.bytes().map(|byte| match byte {
+    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+    _ => format!("%{:02X}", byte),
+  }).collect()
+}
+
+/// Decodes `%XX` escapes, and `+` as a space the way query strings do.
+fn url_decode(text: &str) -> Result<String, String> {
+  let bytes = text.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'%' => {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok())
+          .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+          .ok_or_else(|| format!("invalid escape at {}", i))?;
+        decoded.push(hex);
+        i += 3;
+      },
+      b'+' => {
+        decoded.push(b' ');
+        i += 1;
+      },
+      byte => {
+        decoded.push(byte);
+        i += 1;
+      },
+    }
+  }
+  String::from_utf8(decoded).map_err(|_| "the decoded text is not UTF-8".to_string())
+}
+
+fn base64_decode(text: &str) -> Result<String, String> {
+  let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+  let decoded = STANDARD.decode(compact).map_err(|err| format!("not base64: {}", err))?;
+  String::from_utf8(decoded).map_err(|_| "the decoded text is not UTF-8".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn apply(id: &str, text: &str) -> Result<String, String> {
+    (for_action(&format!("transform_{}", id)).unwrap().apply)(text)
+  }
+
+  #[test]
+  fn test_registry() {
+    let ids: Vec<&str> = transforms().iter().map(|transform| transform.id).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(ids.contains(&"plain_text") && ids.contains(&"base64_decode"));
+    assert_eq!(for_action("transform_lowercase").unwrap().action_id(), "transform_lowercase");
+    assert!(for_action("lowercase").is_none());
+    assert!(for_action("transform_rot13").is_none());
+  }
+
+  #[test]
+  fn test_transforms() {
+    assert_eq!(apply("plain_text", "text").unwrap(), "text");
+    assert_eq!(apply("json_pretty", r#"{"a":[1]}"#).unwrap(), "{\n  \"a\": [\n    1\n  ]\n}");
+    assert_eq!(apply("json_compact", "{ \"a\" : 1 }").unwrap(), r#"{"a":1}"#);
+    assert!(apply("json_pretty", "{a:1}").is_err());
+    assert_eq!(apply("lowercase", "ÄBC").unwrap(), "äbc");
+    assert_eq!(apply("uppercase", "äbc").unwrap(), "ÄBC");
+    assert_eq!(apply("trim", "  a b \n").unwrap(), "a b");
+  }
+
+  #[test]
+  fn test_url_encoding() {
+    assert_eq!(url_encode("a b&c=ä~"), "a%20b%26c%3D%C3%A4~");
+    assert_eq!(url_decode("a%20b%26c%3D%C3%A4~").unwrap(), "a b&c=ä~");
+    assert_eq!(url_decode("a+b").unwrap(), "a b");
+    assert!(url_decode("100%").is_err());
+    assert!(url_decode("%zz").is_err());
+    assert!(url_decode("%ff").is_err());
+  }
+
+  #[test]
+  fn test_base64() {
+    assert_eq!(apply("base64_encode", "hello").unwrap(), "aGVsbG8=");
+    assert_eq!(base64_decode("aGVs\nbG8=").unwrap(), "hello");
+    assert!(base64_decode("not base64!").is_err());
+    assert!(base64_decode("/w==").is_err());
+  }
+}
@@ -0,0 +1,502 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::shortcut::Shortcut;
+
+// Shortcut presses are handed to a small pool of worker threads so a slow
+// handler never blocks the global shortcut callback. Each action's policy
+// decides what happens when it is pressed again while still running.
+
+pub const DEFAULT_WORKERS: usize = 4;
+/// Invocations waiting for a free worker before new ones are dropped.
+pub const QUEUE_CAPACITY: usize = 64;
+/// Invocations of one `Queue` action waiting for the running one.
+pub const MAX_QUEUED_PER_ACTION: usize = 16;
+
+/// What to do when an action is triggered while it is still running.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Concurrency {
+  /// Ignore the new trigger.
+  #[default]
+  Drop,
+  /// Run it after the running invocation finished.
+  Queue,
+  /// Cancel the running invocation and start a new one.
+  Restart,
+  /// Run it alongside the running invocation.
+  Parallel,
+}
+
+/// Limits how often an action starts, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RateLimit {
+  /// Start only once triggers stopped for the window; the last one wins.
+  #[serde(rename = "debounce_ms")]
+  Debounce(u64),
+  /// Start at most once per window; triggers in between are dropped.
+  #[serde(rename = "throttle_ms")]
+  Throttle(u64),
+}
+
+impl RateLimit {
+  pub fn window(&self) -> Duration {
+    match self {
+      RateLimit::Debounce(ms) | RateLimit::Throttle(ms) => Duration::from_millis(*ms),
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DispatchPolicy {
+  #[serde(default)]
+  pub concurrency: Concurrency,
+  #[serde(default)]
+  pub rate_limit: Option<RateLimit>,
+}
+
+/// Set when a `Restart` action is triggered again. Long-running handlers
+/// should check it and return early; nothing is interrupted forcibly.
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::SeqCst);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::SeqCst)
+  }
+
+  fn same(&self, other: &CancellationToken) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
+}
+
+/// What started an invocation.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+  Shortcut(Shortcut),
+  /// The "repeat last action" action.
+  Repeat,
+  /// Re-run from the action history.
+  Rerun,
+}
+
+/// Why an invocation failed.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Failure {
+  /// The action reported an error, e.g. an extension process returned one.
+  Error(String),
+  /// The handler panicked.
+  Panic(String),
+}
+
+pub type Job = Box<dyn FnOnce(CancellationToken) -> Result<(), Failure> + Send>;
+
+pub struct Invocation {
+  pub extension: String,
+  pub action: String,
+  pub trigger: Trigger,
+  pub policy: DispatchPolicy,
+  pub job: Job,
+}
+
+/// The outcome of one invocation, reported after it ran.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Completion {
+  pub extension: String,
+  pub action: String,
+  pub trigger: Trigger,
+  pub started_at: SystemTime,
+  pub duration: Duration,
+  /// Whether a restart cancelled the invocation while it ran.
+  pub cancelled: bool,
+  pub result: Result<(), Failure>,
+}
+
+type Key = (String, String);
+type Reporter = Arc<dyn Fn(Completion) + Send + Sync>;
+
+struct Task {
+  key: Key,
+  trigger: Trigger,
+  token: CancellationToken,
+  job: Job,
+}
+
+/// The latest trigger of a debounced action, admitted at `deadline` unless
+/// another one replaces it first.
+struct Debounced {
+  deadline: Instant,
+  trigger: Trigger,
+  concurrency: Concurrency,
+  job: Job,
+}
+
+#[derive(Default)]
+struct ActionState {
+  running: Vec<CancellationToken>,
+  queued: VecDeque<(Trigger, Job)>,
+  last_started: Option<Instant>,
+  debounced: Option<Debounced>,
+}
+
+#[derive(Default)]
+struct Inner {
+  actions: Mutex<HashMap<Key, ActionState>>,
+  reporter: Mutex<Option<Reporter>>,
+  /// Wakes the timer thread when a debounce deadline is set.
+  timer: Condvar,
+  stopped: AtomicBool,
+}
+
+/// Runs invocations on a bounded pool of worker threads, plus one timer
+/// thread for debounced actions. They exit once the dispatcher is dropped.
+pub struct Dispatcher {
+  sender: SyncSender<Task>,
+  inner: Arc<Inner>,
+}
+
+impl Default for Dispatcher {
+  fn default() -> Self {
+    Dispatcher::new(DEFAULT_WORKERS)
+  }
+}
+
+impl Dispatcher {
+  pub fn new(workers: usize) -> Self {
+    let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+    let receiver = Arc::new(Mutex::new(receiver));
+    let inner = Arc::new(Inner::default());
+    for _ in 0..workers.max(1) {
+      let receiver = receiver.clone();
+      let inner = inner.clone();
+      thread::spawn(move || work(&inner, &receiver));
+    }
+    let timer = (inner.clone(), sender.clone());
+    thread::spawn(move || time(&timer.0, &timer.1));
+    Dispatcher { sender, inner }
+  }
+
+  /// Called on a worker thread after every invocation.
+  pub fn on_complete(&self, reporter: impl Fn(Completion) + Send + Sync + 'static) {
+    *self.inner.reporter.lock().unwrap() = Some(Arc::new(reporter));
+  }
+
+  pub fn dispatch(&self, invocation: Invocation) {
+    let key = (invocation.extension, invocation.action);
+    let trigger = invocation.trigger;
+    let concurrency = invocation.policy.concurrency;
+    match invocation.policy.rate_limit {
+      Some(limit @ RateLimit::Throttle(_)) => {
+        let actions = self.inner.actions.lock().unwrap();
+        let throttled = actions.get(&key)
+          .and_then(|state| state.last_started)
+          .is_some_and(|started| started.elapsed() < limit.window());
+        if throttled {
+          return;
+        }
+      },
+      Some(limit @ RateLimit::Debounce(_)) => {
+        let mut actions = self.inner.actions.lock().unwrap();
+        let deadline = Instant::now() + limit.window();
+        actions.entry(key).or_default().debounced = Some(Debounced { deadline, trigger, concurrency, job: invocation.job });
+        self.inner.timer.notify_one();
+        return;
+      },
+      None => {},
+    }
+    admit(&self.inner, &self.sender, key, trigger, concurrency, invocation.job);
+  }
+
+  /// Whether an invocation of the action is running.
+  pub fn is_running(&self, extension: &str, action: &str) -> bool {
+    let key = (extension.to_string(), action.to_string());
+    self.inner.actions.lock().unwrap().get(&key).is_some_and(|state| !state.running.is_empty())
+  }
+}
+
+impl Drop for Dispatcher {
+  fn drop(&mut self) {
+    let _actions = self.inner.actions.lock().unwrap();
+    self.inner.stopped.store(true, Ordering::SeqCst);
+    self.inner.timer.notify_one();
+  }
+}
+
+/// Admits debounced invocations once their deadline passes, sleeping until
+/// the earliest one otherwise.
+fn time(inner: &Inner, sender: &SyncSender<Task>) {
+  let mut actions = inner.actions.lock().unwrap();
+  while !inner.stopped.load(Ordering::SeqCst) {
+    let now = Instant::now();
+    let due: Vec<(Key, Debounced)> = actions.iter_mut()
+      .filter(|(_, state)| state.debounced.as_ref().is_some_and(|debounced| debounced.deadline <= now))
+      .filter_map(|(key, state)| Some((key.clone(), state.debounced.take()?)))
+      .collect();
+    if !due.is_empty() {
+      drop(actions);
+      for (key, debounced) in due {
+        admit(inner, sender, key, debounced.trigger, debounced.concurrency, debounced.job);
+      }
+      actions = inner.actions.lock().unwrap();
+      continue;
+    }
+
+    let next = actions.values().filter_map(|state| state.debounced.as_ref().map(|debounced| debounced.deadline)).min();
+    actions = match next {
+      Some(deadline) => inner.timer.wait_timeout(actions, deadline - now).unwrap().0,
+      None => inner.timer.wait(actions).unwrap(),
+    };
+  }
+}
+
+fn admit(inner: &Inner, sender: &SyncSender<Task>, key: Key, trigger: Trigger, concurrency: Concurrency, job: Job) {
+  let mut actions = inner.actions.lock().unwrap();
+  let state = actions.entry(key.clone()).or_default();
+  if !state.running.is_empty() {
+    match concurrency {
+      Concurrency::Drop => return,
+      Concurrency::Queue => {
+        if state.queued.len() < MAX_QUEUED_PER_ACTION {
+          state.queued.push_back((trigger, job));
+        }
+        return;
+      },
+      Concurrency::Restart => state.running.iter().for_each(|token| token.cancel()),
+      Concurrency::Parallel => {},
+    }
+  }
+
+  let token = CancellationToken::default();
+  state.running.push(token.clone());
+  state.last_started = Some(Instant::now());
+  drop(actions);
+
+  if let Err(err) = sender.try_send(Task { key, trigger, token, job }) {
+    let task = match err {
+      TrySendError::Full(task) | TrySendError::Disconnected(task) => task,
+    };
+    if let Some(state) = inner.actions.lock().unwrap().get_mut(&task.key) {
+      state.running.retain(|running| !running.same(&task.token));
+    }
+  }
+}
+
+/// Marks an invocation as done and hands back the next queued one.
+fn finish(inner: &Inner, key: &Key, token: &CancellationToken) -> Option<Task> {
+  let mut actions = inner.actions.lock().unwrap();
+  let state = actions.get_mut(key)?;
+  state.running.retain(|running| !running.same(token));
+  if !state.running.is_empty() {
+    return None;
+  }
+  let (trigger, job) = state.queued.pop_front()?;
+  let token = CancellationToken::default();
+  state.running.push(token.clone());
+  state.last_started = Some(Instant::now());
+  Some(Task { key: key.clone(), trigger, token, job })
+}
+
+fn work(inner: &Inner, receiver: &Mutex<Receiver<Task>>) {
+  loop {
+    let task = match receiver.lock().unwrap().recv() {
+      Ok(task) => task,
+      Err(_) => return,
+    };
+    let mut next = Some(task);
+    while let Some(task) = next.take() {
+      let started_at = SystemTime::now();
+      let started = Instant::now();
+      let result = (task.job)(task.token.clone());
+      let completion = Completion {
+        extension: task.key.0.clone(),
+        action: task.key.1.clone(),
+        trigger: task.trigger,
+        started_at,
+        duration: started.elapsed(),
+        cancelled: task.token.is_cancelled(),
+        result,
+      };
+      next = finish(inner, &task.key, &task.token);
+      let reporter = inner.reporter.lock().unwrap().clone();
+      if let Some(reporter) = reporter {
+        reporter(completion);
+      }
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::AtomicUsize;
+
+  fn invocation(policy: DispatchPolicy, job: impl FnOnce(CancellationToken) -> Result<(), Failure> + Send + 'static) -> Invocation {
+    Invocation { extension: "test".to_string(), action: "run".to_string(), trigger: Trigger::Rerun, policy, job: Box::new(job) }
+  }
+
+  fn policy(concurrency: Concurrency) -> DispatchPolicy {
+    DispatchPolicy { concurrency, rate_limit: None }
+  }
+
+  /// A job that blocks until released and counts its runs.
+  fn blocking(runs: &Arc<AtomicUsize>, release: &Arc<Mutex<Receiver<()>>>) -> impl FnOnce(CancellationToken) -> Result<(), Failure> + Send + 'static {
+    let runs = runs.clone();
+    let release = release.clone();
+    move |_| {
+      runs.fetch_add(1, Ordering::SeqCst);
+      let _ = release.lock().unwrap().recv_timeout(Duration::from_secs(5));
+      Ok(())
+    }
+  }
+
+  fn completions(dispatcher: &Dispatcher) -> Receiver<Completion> {
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    dispatcher.on_complete(move |completion| {
+      let _ = sender.lock().unwrap().send(completion);
+    });
+    receiver
+  }
+
+  fn wait_until(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+      if condition() {
+        return true;
+      }
+      thread::sleep(Duration::from_millis(5));
+    }
+    false
+  }
+
+  #[test]
+  fn test_drop_if_running() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    let runs = Arc::new(AtomicUsize::new(0));
+    let (release, receiver) = mpsc::channel();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    dispatcher.dispatch(invocation(policy(Concurrency::Drop), blocking(&runs, &receiver)));
+    assert!(wait_until(|| runs.load(Ordering::SeqCst) == 1));
+    dispatcher.dispatch(invocation(policy(Concurrency::Drop), blocking(&runs, &receiver)));
+    release.send(()).unwrap();
+
+    let completion = done.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((completion.extension.as_str(), completion.action.as_str(), completion.result), ("test", "run", Ok(())));
+    assert!(done.recv_timeout(Duration::from_millis(100)).is_err());
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert!(!dispatcher.is_running("test", "run"));
+  }
+
+  #[test]
+  fn test_queue() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    let order = Arc::new(Mutex::new(vec![]));
+    for index in 0..3 {
+      let order = order.clone();
+      dispatcher.dispatch(invocation(policy(Concurrency::Queue), move |_| {
+        thread::sleep(Duration::from_millis(20));
+        order.lock().unwrap().push(index);
+        Ok(())
+      }));
+    }
+    for _ in 0..3 {
+      done.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+  }
+
+  #[test]
+  fn test_restart_cancels_running() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let observed = cancelled.clone();
+    dispatcher.dispatch(invocation(policy(Concurrency::Restart), move |token| {
+      if wait_until(|| token.is_cancelled()) {
+        observed.store(true, Ordering::SeqCst);
+      }
+      Ok(())
+    }));
+    assert!(wait_until(|| dispatcher.is_running("test", "run")));
+    dispatcher.dispatch(invocation(policy(Concurrency::Restart), |_| Ok(())));
+
+    let first = done.recv_timeout(Duration::from_secs(5)).unwrap();
+    let second = done.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(cancelled.load(Ordering::SeqCst));
+    assert_eq!([first.cancelled, second.cancelled].iter().filter(|cancelled| **cancelled).count(), 1);
+  }
+
+  #[test]
+  fn test_parallel() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    let runs = Arc::new(AtomicUsize::new(0));
+    let (release, receiver) = mpsc::channel();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    dispatcher.dispatch(invocation(policy(Concurrency::Parallel), blocking(&runs, &receiver)));
+    dispatcher.dispatch(invocation(policy(Concurrency::Parallel), blocking(&runs, &receiver)));
+    assert!(wait_until(|| runs.load(Ordering::SeqCst) == 2));
+    release.send(()).unwrap();
+    release.send(()).unwrap();
+    done.recv_timeout(Duration::from_secs(5)).unwrap();
+    done.recv_timeout(Duration::from_secs(5)).unwrap();
+  }
+
+  #[test]
+  fn test_throttle() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    let throttled = DispatchPolicy { concurrency: Concurrency::Parallel, rate_limit: Some(RateLimit::Throttle(10_000)) };
+    for _ in 0..5 {
+      dispatcher.dispatch(invocation(throttled, |_| Ok(())));
+    }
+    done.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(done.recv_timeout(Duration::from_millis(100)).is_err());
+  }
+
+  #[test]
+  fn test_debounce_runs_last() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    let debounced = DispatchPolicy { concurrency: Concurrency::Drop, rate_limit: Some(RateLimit::Debounce(50)) };
+    for index in 0..5 {
+      dispatcher.dispatch(invocation(debounced, move |_| Err(Failure::Error(index.to_string()))));
+    }
+    assert_eq!(done.recv_timeout(Duration::from_secs(5)).unwrap().result, Err(Failure::Error("4".to_string())));
+    assert!(done.recv_timeout(Duration::from_millis(150)).is_err());
+  }
+
+  #[test]
+  fn test_debounce_per_action() {
+    let dispatcher = Dispatcher::new(2);
+    let done = completions(&dispatcher);
+    for (action, ms) in [("slow", 200), ("fast", 20)] {
+      let policy = DispatchPolicy { concurrency: Concurrency::Drop, rate_limit: Some(RateLimit::Debounce(ms)) };
+      dispatcher.dispatch(Invocation { action: action.to_string(), ..invocation(policy, |_| Ok(())) });
+    }
+    assert_eq!(done.recv_timeout(Duration::from_secs(5)).unwrap().action, "fast");
+    assert_eq!(done.recv_timeout(Duration::from_secs(5)).unwrap().action, "slow");
+  }
+
+  #[test]
+  fn test_deserialize_policy() {
+    let policy: DispatchPolicy = serde_json::from_str(r#"{"concurrency":"queue","rate_limit":{"throttle_ms":500}}"#).unwrap();
+    assert_eq!(policy, DispatchPolicy { concurrency: Concurrency::Queue, rate_limit: Some(RateLimit::Throttle(500)) });
+    assert_eq!(serde_json::from_str::<DispatchPolicy>("{}").unwrap(), DispatchPolicy::default());
+  }
+}
@@ -0,0 +1,117 @@
+use serde_json::json;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::LazyLock;
+use crate::shortcut::Shortcut;
+
+use super::dispatcher::{CancellationToken, DispatchPolicy};
+use super::locale::Translation;
+use super::metadata::{Icon, Metadata};
+use super::permissions::Capability;
+use super::settings::{SettingSchema, Settings};
+
+pub struct Extension {
+  /// Stable identifier used for config, permissions and invocation. Unlike
+  /// `name` it never changes when the extension is renamed or translated.
+  pub id: String,
+  pub name: String,
+  pub description: String,
+  pub metadata: Metadata,
+  pub shortcuts: Vec<ShortcutHandler>,
+  pub settings: Vec<SettingSchema>,
+  pub capabilities: Vec<Capability>,
+  /// Fluent sources translating the name and description of the extension
+  /// and its actions; see `locale`.
+  pub translations: Vec<Translation>,
+}
+
+/// A compiled-in extension. `#[extension]` submits one per extension so the
+/// manager can find them without a hand-maintained list.
+pub struct BuiltinExtension(pub &'static LazyLock<Extension>);
+
+inventory::collect!(BuiltinExtension);
+
+/// Passed to a handler when its shortcut fires.
+#[derive(Debug, Default, Clone)]
+pub struct InvocationContext {
+  pub extension: String,
+  pub action: String,
+  pub settings: Settings,
+  /// Set when a `Restart` action is triggered again while this runs.
+  pub cancellation: CancellationToken,
+}
+
+impl InvocationContext {
+  pub fn is_cancelled(&self) -> bool {
+    self.cancellation.is_cancelled()
+  }
+}
+
+/// Implemented by `#[actions]` for the struct carrying `#[extension]`.
+pub trait ExtensionActions {
+  fn actions() -> Vec<ShortcutHandler>;
+}
+
+pub struct ShortcutHandler {
+  pub id: String,
+  pub name: String,
+  pub description: String,
+  pub handle: fn(&InvocationContext),
+  pub default_shortcut: Option<Shortcut>,
+  pub icon: Option<Icon>,
+  /// Extra terms the command palette matches besides name and description.
+  pub keywords: Vec<String>,
+  /// How repeated triggers are dispatched while the action runs.
+  pub policy: DispatchPolicy,
+}
+
+impl ShortcutHandler {
+  pub fn new(id: String, name: String, description: String, handle: fn(&InvocationContext)) -> Self {
+    ShortcutHandler {
+      id,
+      name,
+      description,
+      handle,
+      default_shortcut: None,
+      icon: None,
+      keywords: vec![],
+      policy: DispatchPolicy::default(),
+    }
+  }
+  
+  /// Runs the handler. A panic is caught and returned as the error so one
+  /// broken extension can't take down the app.
+  pub fn handle(&self, context: &InvocationContext) -> Result<(), String> {
+    call_handler(self.handle, context)
+  }
+
+  pub fn name(&self) -> String {
+    self.name.to_string()
+  }
+
+  pub fn description(&self) -> String {
+    self.description.to_string()
+  }
+
+  pub fn to_string(&self) -> String {
+    json!({
+      "id": self.id,
+      "name": self.name,
+      "description": self.description,
+      "icon": self.icon,
+      "keywords": self.keywords,
+    }).to_string()
+  }
+}
+
+/// Calls a handler function, returning a panic as the error.
+pub fn call_handler(handle: fn(&InvocationContext), context: &InvocationContext) -> Result<(), String> {
+  panic::catch_unwind(AssertUnwindSafe(|| handle(context))).map_err(|payload| panic_message(&*payload))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+  match payload.downcast_ref::<&str>() {
+    Some(message) => message.to_string(),
+    None => payload.downcast_ref::<String>().cloned().unwrap_or_else(|| "unknown panic".to_string()),
+  }
+}
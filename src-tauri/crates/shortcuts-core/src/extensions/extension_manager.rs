@@ -0,0 +1,1579 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+use std::sync::{Arc, LazyLock, Mutex};
+use crate::modules::cache_manager::{self, CacheExtension, CacheManager};
+
+use super::dispatcher::{Completion, DispatchPolicy, Dispatcher, Failure, Invocation, Trigger};
+use super::extension::{self as builtin, BuiltinExtension, Extension, InvocationContext, ShortcutHandler};
+use super::external::{self, LoadError, ProcessManifest, ProcessSupervisor, MANIFEST_FILE};
+use super::history::{ActionHistory, HistoryEntry};
+use super::host::HostApi;
+use super::locale::{self, LocaleConfig, Localizer};
+use super::metadata::{Icon, Metadata};
+use super::package::{self, InstalledExtension, Package, TrustedKey};
+use super::permissions::{Capability, Permissions};
+use super::registry::RegistryIndex;
+use super::settings::{self, SettingSchema, Settings};
+use super::usage::{ExportFormat, UsageReport, UsageStats};
+use super::watcher::ExtensionWatcher;
+use crate::shortcut::Shortcut;
+use std::collections::HashMap;
+
+/// What the manager knows about an extension, whether it is compiled in or
+/// announced by an external process.
+struct ExtensionView {
+  id: String,
+  name: String,
+  description: String,
+  metadata: Metadata,
+  actions: Vec<ActionView>,
+  settings: Vec<SettingSchema>,
+  capabilities: Vec<Capability>,
+}
+
+struct ActionView {
+  id: String,
+  name: String,
+  description: String,
+  default_shortcut: Option<Shortcut>,
+  icon: Option<Icon>,
+  keywords: Vec<String>,
+  policy: DispatchPolicy,
+}
+
+/// Consecutive panics after which an extension is disabled.
+pub const MAX_CRASHES: u32 = 3;
+const MAX_FAILURES: usize = 50;
+
+/// A handler that panicked.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct InvocationFailure {
+  pub extension: String,
+  pub action: String,
+  pub message: String,
+  /// Whether this failure disabled the extension.
+  pub disabled: bool,
+}
+
+type FailureListener = Box<dyn Fn(&InvocationFailure) + Send>;
+type HistoryListener = Box<dyn Fn(&HistoryEntry) + Send>;
+
+pub struct ExtensionManager<'a> {
+  extensions: Vec<&'a Extension>,
+  external: Vec<ProcessSupervisor>,
+  extensions_dir: PathBuf,
+  dev_dirs: Vec<PathBuf>,
+  watcher: Option<ExtensionWatcher>,
+  load_errors: Vec<LoadError>,
+  cache_manager: CacheManager,
+  host: Arc<HostApi>,
+  localizer: Localizer,
+  failures: Vec<InvocationFailure>,
+  failure_listener: Option<FailureListener>,
+  dispatcher: Dispatcher,
+  history: ActionHistory,
+  history_listener: Option<HistoryListener>,
+  usage: UsageStats,
+
+  handler_mapper: HashMap<String, (&'a Extension, &'a ShortcutHandler)>,
+  shortcut_mapper: HashMap<Shortcut, (&'a Extension, &'a ShortcutHandler)>,
+}
+
+impl<'a> ExtensionManager<'a> {
+  pub fn new() -> Self {
+    ExtensionManager {
+      extensions: vec![],
+      external: vec![],
+      extensions_dir: external::default_extensions_dir(),
+      dev_dirs: vec![],
+      watcher: None,
+      load_errors: vec![],
+      cache_manager: CacheManager::new(None),
+      host: Arc::new(HostApi::new(Arc::new(Permissions::new()))),
+      localizer: Localizer::default(),
+      failures: vec![],
+      failure_listener: None,
+      dispatcher: Dispatcher::default(),
+      history: ActionHistory::new(None),
+      history_listener: None,
+      usage: UsageStats::new(None),
+      handler_mapper: HashMap::new(),
+      shortcut_mapper: HashMap::new(),
+    }
+  }
+
+  /// Registers every extension compiled into this build, ordered by ID so
+  /// the list is stable across builds.
+  pub fn register_builtin(&mut self) {
+    let mut builtin: Vec<&'static Extension> = inventory::iter::<BuiltinExtension>
+      .into_iter()
+      .map(|builtin| &**builtin.0)
+      .collect();
+    builtin.sort_by(|a, b| a.id.cmp(&b.id));
+    builtin.into_iter().for_each(|extension| self.register(extension));
+  }
+
+  pub fn register(&mut self, extension: &'a Extension) {
+    self.host.permissions().reserve_builtin(&extension.id);
+    self.extensions.push(extension);
+    self.localizer.add(&extension.translations);
+  }
+
+  pub fn register_external(&mut self, supervisor: ProcessSupervisor) {
+    self.localizer.add(supervisor.translations());
+    self.external.push(supervisor);
+  }
+
+  pub fn locale(&self) -> String {
+    self.localizer.locale().to_string()
+  }
+
+  /// Switches the locale names and descriptions are resolved in.
+  pub fn set_locale(&mut self, locale: &str) -> Result<(), String> {
+    self.localizer.set_locale(locale::parse_locale(locale)?);
+    Ok(())
+  }
+
+  fn rebuild_localizer(&mut self) {
+    self.localizer.clear();
+    self.extensions.iter().for_each(|extension| self.localizer.add(&extension.translations));
+    self.external.iter().for_each(|supervisor| self.localizer.add(supervisor.translations()));
+  }
+
+  pub fn load_external(&mut self, dir: &Path) {
+    self.extensions_dir = dir.to_path_buf();
+    let (manifests, errors) = external::scan_manifests(dir);
+    errors.iter().for_each(|error| println!("Skipping extension at {:?}: {}", error.path, error.error));
+    self.load_errors.extend(errors);
+    manifests.into_iter().for_each(|manifest| {
+      self.register_external(ProcessSupervisor::new(manifest, self.host.clone()));
+    });
+  }
+
+  /// Side-loads an extension from outside the extensions directory for
+  /// development. It is watched like installed extensions, even when its
+  /// manifest doesn't load yet.
+  pub fn load_dev_extension(&mut self, dir: &Path) -> Result<(), String> {
+    let dir = dir.canonicalize().map_err(|err| format!("{}: {}", dir.display(), err))?;
+    if let Some(watcher) = self.watcher.as_mut() {
+      watcher.watch(&dir);
+    }
+    self.dev_dirs.push(dir.clone());
+    match ProcessManifest::read(&dir) {
+      Ok(manifest) => {
+        self.register_external(ProcessSupervisor::new(manifest, self.host.clone()));
+        Ok(())
+      },
+      Err(err) => {
+        let error = err.to_string();
+        self.load_errors.push(LoadError { path: dir.clone(), error: error.clone() });
+        Err(format!("{}: {}", dir.display(), error))
+      },
+    }
+  }
+
+  /// Starts watching the extensions directory and side-loaded extensions
+  /// for changes picked up by `reload_changed`.
+  pub fn watch(&mut self) {
+    self.watcher = Some(ExtensionWatcher::new(&self.extensions_dir, &self.dev_dirs));
+  }
+
+  /// Reloads every watched extension whose files changed since the last
+  /// call and returns their directories.
+  pub fn reload_changed(&mut self) -> Vec<PathBuf> {
+    let changed = match self.watcher.as_mut() {
+      Some(watcher) => watcher.poll(),
+      None => return vec![],
+    };
+    changed.iter().for_each(|dir| self.reload(dir));
+    changed
+  }
+
+  /// Replaces the extension process in `dir` with one built from its current
+  /// files. The old process is unloaded first and the new one only runs if
+  /// the old one did or the extension is new. Bindings, settings and grants
+  /// are cached by extension ID, so they carry over.
+  pub fn reload(&mut self, dir: &Path) {
+    let previous = self.external.iter().position(|supervisor| supervisor.dir() == dir)
+      .map(|position| self.external.remove(position));
+    let start = previous.as_ref().is_none_or(|supervisor| supervisor.is_started());
+    if let Some(supervisor) = previous {
+      supervisor.unload();
+    }
+
+    self.load_errors.retain(|error| error.path != dir);
+    if dir.join(MANIFEST_FILE).is_file() {
+      match ProcessManifest::read(dir) {
+        Ok(manifest) => {
+          println!("Reloading extension {}", manifest.name);
+          let supervisor = ProcessSupervisor::new(manifest, self.host.clone());
+          if start && self.has_enabled(&supervisor) {
+            supervisor.start();
+          }
+          self.external.push(supervisor);
+        },
+        Err(err) => self.load_errors.push(LoadError { path: dir.to_path_buf(), error: err.to_string() }),
+      }
+    }
+    self.rebuild_localizer();
+    self.build_shortcut_mapper();
+  }
+
+  /// Extensions that failed to load or whose process failed to start.
+  pub fn load_errors(&self) -> Vec<LoadError> {
+    let mut errors = self.load_errors.clone();
+    errors.extend(self.external.iter().filter_map(|supervisor| {
+      supervisor.last_error().map(|error| LoadError { path: supervisor.dir().to_path_buf(), error })
+    }));
+    errors
+  }
+
+  fn refresh_watcher(&mut self) {
+    if let Some(watcher) = self.watcher.as_mut() {
+      watcher.refresh();
+    }
+  }
+
+  /// Installs a package into the extensions directory and starts its
+  /// process if one of its extensions is enabled, replacing a running older
+  /// version.
+  pub fn install(&mut self, path: &Path, trusted: &[TrustedKey], allow_unsigned: bool) -> Result<InstalledExtension, String> {
+    let installed = package::install(path, &self.extensions_dir, trusted, allow_unsigned)?;
+    self.external.retain(|supervisor| supervisor.name() != installed.name);
+    self.rebuild_localizer();
+    if let Ok(manifest) = ProcessManifest::read(&self.extensions_dir.join(&installed.name)) {
+      let supervisor = ProcessSupervisor::new(manifest, self.host.clone());
+      if self.has_enabled(&supervisor) {
+        supervisor.start();
+      }
+      self.register_external(supervisor);
+    }
+    self.load_errors.retain(|error| !error.path.ends_with(&installed.name));
+    self.refresh_watcher();
+    Ok(installed)
+  }
+
+  /// Where packages from the registry are downloaded before installing.
+  pub fn downloads_dir(&self) -> PathBuf {
+    self.extensions_dir.join(".downloads")
+  }
+
+  pub fn uninstall(&mut self, name: &str) -> Result<(), String> {
+    package::uninstall(name, &self.extensions_dir)?;
+    self.external.retain(|supervisor| supervisor.name() != name);
+    self.rebuild_localizer();
+    self.refresh_watcher();
+    Ok(())
+  }
+
+  pub fn list_installed(&self) -> Vec<InstalledExtension> {
+    package::list_installed(&self.extensions_dir)
+  }
+
+  pub fn host(&self) -> Arc<HostApi> {
+    self.host.clone()
+  }
+
+  /// Starts the processes that provide at least one enabled extension.
+  pub fn start_external(&self) {
+    self.external.iter()
+      .filter(|supervisor| self.has_enabled(supervisor))
+      .for_each(|supervisor| supervisor.start());
+  }
+
+  fn has_enabled(&self, supervisor: &ProcessSupervisor) -> bool {
+    supervisor.extension_ids().iter().any(|id| self.is_enabled(id))
+  }
+
+  fn views(&self) -> Vec<ExtensionView> {
+    let mut views: Vec<ExtensionView> = self.extensions.iter().map(|ext| ExtensionView {
+      id: ext.id.clone(),
+      name: ext.name.clone(),
+      description: ext.description.clone(),
+      metadata: ext.metadata.clone(),
+      actions: ext.shortcuts.iter().map(|shortcut| ActionView {
+        id: shortcut.id.clone(),
+        name: shortcut.name.clone(),
+        description: shortcut.description.clone(),
+        default_shortcut: shortcut.default_shortcut,
+        icon: shortcut.icon.clone(),
+        keywords: shortcut.keywords.clone(),
+        policy: shortcut.policy,
+      }).collect(),
+      settings: ext.settings.clone(),
+      capabilities: ext.capabilities.clone(),
+    }).collect();
+
+    self.external.iter().for_each(|supervisor| {
+      let announced = supervisor.extensions();
+      // Processes of disabled extensions are never started, so list what
+      // their manifest declares to let them be enabled.
+      if announced.is_empty() {
+        views.extend(supervisor.extension_ids().into_iter().map(|id| ExtensionView {
+          name: id.clone(),
+          id,
+          description: String::new(),
+          metadata: Metadata::default(),
+          actions: vec![],
+          settings: vec![],
+          capabilities: vec![],
+        }));
+      }
+      announced.into_iter().for_each(|ext| {
+        views.push(ExtensionView {
+          id: ext.id().to_string(),
+          actions: ext.shortcuts.iter().map(|action| ActionView {
+            id: action.id().to_string(),
+            name: action.name.clone(),
+            description: action.description.clone(),
+            default_shortcut: action.default_shortcut.as_deref().and_then(|s| Shortcut::from_str(s).ok()),
+            icon: action.icon.clone(),
+            keywords: action.keywords.clone(),
+            policy: action.policy,
+          }).collect(),
+          metadata: ext.metadata,
+          name: ext.name,
+          description: ext.description,
+          settings: ext.settings,
+          capabilities: ext.capabilities,
+        });
+      });
+    });
+    views
+  }
+
+  fn view(&self, id: &str) -> Option<ExtensionView> {
+    self.views().into_iter().find(|view| view.id == id)
+  }
+
+  /// The shortcut an action is bound to: the user's binding if there is one,
+  /// otherwise the action's default.
+  fn binding(cache: Option<&CacheExtension>, action: &ActionView) -> Option<Shortcut> {
+    cache.and_then(|cache| cache.shortcuts.iter().find(|s| s.name == action.id))
+      .map(|value| value.shortcut)
+      .or(action.default_shortcut)
+  }
+
+  /// A translated string for `<extension>[-<action>]-<field>`, or `fallback`.
+  fn localized(&self, key: &[&str], fallback: &str) -> String {
+    self.localizer.message(&key.join("-")).unwrap_or_else(|| fallback.to_string())
+  }
+
+  fn extension_json(&self, map: &HashMap<String, CacheExtension>, view: &ExtensionView) -> Value {
+    let cache = map.get(&view.id);
+    let settings = Settings::resolve(&view.settings, &cache.map(|cache| cache.settings.clone()).unwrap_or_default());
+    let mut value = json!({
+      "id": view.id,
+      "name": self.localized(&[&view.id, "name"], &view.name),
+      "description": self.localized(&[&view.id, "description"], &view.description),
+      "enabled": cache.map(|cache| cache.enabled).unwrap_or(false),
+      "shortcuts": view.actions.iter().map(|action| {
+        let binding = Self::binding(cache, action);
+        json!({
+          "id": action.id,
+          "name": self.localized(&[&view.id, &action.id, "name"], &action.name),
+          "description": self.localized(&[&view.id, &action.id, "description"], &action.description),
+          "shortcut": binding.map(|shortcut| shortcut.to_string()),
+          "icon": action.icon,
+          "keywords": action.keywords,
+          "unused": binding.is_some() && self.usage.is_unused(&view.id, &action.id, SystemTime::now()),
+        })
+      }).collect::<Vec<_>>(),
+      "settings": view.settings.iter().map(|setting| {
+        let mut value = serde_json::to_value(setting).unwrap();
+        value["value"] = settings.get(&setting.key).cloned().unwrap_or(Value::Null);
+        value
+      }).collect::<Vec<_>>(),
+      "capabilities": view.capabilities,
+      "granted": cache.map(|cache| cache.granted.clone()).unwrap_or_default(),
+    });
+    if let Value::Object(metadata) = serde_json::to_value(&view.metadata).unwrap() {
+      value.as_object_mut().unwrap().extend(metadata);
+    }
+    value
+  }
+
+  pub fn to_string(&self) -> String {
+    let map: HashMap<String, cache_manager::CacheExtension> = self.cache_manager.to_map();
+    let extensions: Vec<_> = self.views().iter().map(|view| self.extension_json(&map, view)).collect();
+    serde_json::to_string(&extensions).unwrap()
+  }
+
+  pub fn init(&mut self) {
+    self.cache_manager.init();
+    self.history.load();
+    self.usage.load();
+    self.migrate_cache();
+    self.sync_permissions();
+    self.build_handler_mapper();
+    self.build_shortcut_mapper();
+  }
+
+  /// Older configs keyed extensions and shortcuts by display name. Rewrites
+  /// those entries to the stable IDs of the registered extensions.
+  fn migrate_cache(&mut self) {
+    let extensions = self.extensions.clone();
+    let mut migrated = false;
+    for cache in self.cache_manager.extensions.iter_mut() {
+      let ext = match extensions.iter().find(|ext| ext.id != cache.name && ext.name == cache.name) {
+        Some(ext) => ext,
+        None => continue,
+      };
+      cache.name = ext.id.clone();
+      for cache_shortcut in cache.shortcuts.iter_mut() {
+        if let Some(shortcut) = ext.shortcuts.iter().find(|s| s.name == cache_shortcut.name) {
+          cache_shortcut.name = shortcut.id.clone();
+        }
+      }
+      migrated = true;
+    }
+    if migrated {
+      self.cache_manager.write_to_cache();
+    }
+  }
+
+  pub fn build_handler_mapper(&mut self) {
+    self.handler_mapper.clear();
+    self.extensions.clone().iter().for_each(|ext| {
+      ext.shortcuts.iter().for_each(|shortcut| {
+        self.handler_mapper.insert(self.build_key(ext.id.clone(), shortcut.id.clone()), (*ext, shortcut));
+      });
+    });
+  }
+
+  pub fn build_shortcut_mapper(&mut self) {
+    self.shortcut_mapper.clear();
+    let map = self.cache_manager.to_map();
+    self.views().iter().for_each(|view| {
+      let cache = match map.get(&view.id) {
+        Some(cache) if cache.enabled => cache,
+        _ => return,
+      };
+
+      view.actions.iter().for_each(|action| {
+        let key = self.build_key(view.id.clone(), action.id.clone());
+        if let (Some(shortcut), Some(shortcut_instance)) = (Self::binding(Some(cache), action), self.handler_mapper.get(&key)) {
+          self.shortcut_mapper.insert(shortcut, *shortcut_instance);
+        }
+      });
+    });
+  }
+
+  fn build_key(&self, extension_id: String, shortcut_id: String) -> String {
+    format!("{}-{}", extension_id, shortcut_id)
+  }
+
+  pub fn is_enabled(&self, id: &str) -> bool {
+    self.cache_manager.extensions.iter().any(|ext| ext.name == id && ext.enabled)
+  }
+
+  /// Persists the enabled flag of an extension. External extension processes
+  /// are started when one of their extensions is enabled and killed once all
+  /// of them are disabled.
+  pub fn set_enabled(&mut self, id: &str, enabled: bool) {
+    let entry = self.cache_entry(id);
+    entry.enabled = enabled;
+    if enabled {
+      entry.crashes = 0;
+    }
+    self.cache_manager.write_to_cache();
+    self.build_shortcut_mapper();
+    self.sync_permissions();
+
+    for supervisor in self.external.iter().filter(|supervisor| supervisor.may_provide(id)) {
+      if enabled {
+        supervisor.start();
+      } else if !self.has_enabled(supervisor) {
+        supervisor.stop();
+      }
+    }
+  }
+
+  fn cache_entry(&mut self, id: &str) -> &mut CacheExtension {
+    if !self.cache_manager.extensions.iter().any(|ext| ext.name == id) {
+      self.cache_manager.add(CacheExtension {
+        name: id.to_string(),
+        shortcuts: vec![],
+        enabled: false,
+        settings: HashMap::new(),
+        granted: vec![],
+        crashes: 0,
+      });
+    }
+    self.cache_manager.extensions.iter_mut().find(|ext| ext.name == id).unwrap()
+  }
+
+  /// Records the capabilities the user granted to an extension. Only
+  /// capabilities the extension declares are kept.
+  pub fn grant(&mut self, id: &str, granted: Vec<Capability>) -> Result<(), String> {
+    let declared = self.view(id).ok_or_else(|| format!("unknown extension `{}`", id))?.capabilities;
+    let granted: Vec<Capability> = granted.into_iter().filter(|capability| declared.contains(capability)).collect();
+    self.cache_entry(id).granted = granted;
+    self.cache_manager.write_to_cache();
+    self.sync_permissions();
+    Ok(())
+  }
+
+  /// Mirrors the persisted grants of enabled extensions into the runtime
+  /// permissions checked by the host API.
+  fn sync_permissions(&self) {
+    let permissions = self.host.permissions();
+    self.cache_manager.extensions.iter().for_each(|ext| {
+      if ext.enabled {
+        permissions.set(&ext.name, ext.granted.clone());
+      } else {
+        permissions.revoke(&ext.name);
+      }
+    });
+  }
+
+  pub fn settings_for(&self, id: &str) -> Settings {
+    let schema = self.view(id).map(|view| view.settings).unwrap_or_default();
+    let stored = self.cache_manager.to_map().get(id).map(|ext| ext.settings.clone()).unwrap_or_default();
+    Settings::resolve(&schema, &stored)
+  }
+
+  /// Validates `values` against the extension's schema and persists them next
+  /// to its bindings. Keys that are not part of `values` keep their value.
+  pub fn set_settings(&mut self, id: &str, values: Map<String, Value>) -> Result<(), String> {
+    let schema = self.view(id).ok_or_else(|| format!("unknown extension `{}`", id))?.settings;
+    settings::validate(&schema, &values)?;
+    self.cache_entry(id).settings.extend(values);
+    self.cache_manager.write_to_cache();
+    Ok(())
+  }
+
+  fn find_external_action(&self, shortcut: &Shortcut) -> Option<(&ProcessSupervisor, String, String, DispatchPolicy)> {
+    let map = self.cache_manager.to_map();
+    for supervisor in &self.external {
+      let provided: Vec<String> = supervisor.extensions().iter().map(|ext| ext.id().to_string()).collect();
+      for view in self.views().into_iter().filter(|view| provided.contains(&view.id)) {
+        let cache = match map.get(&view.id) {
+          Some(cache) if cache.enabled => cache,
+          _ => continue,
+        };
+        for action in &view.actions {
+          if Self::binding(Some(cache), action).as_ref() == Some(shortcut) {
+            return Some((supervisor, view.id.clone(), action.id.clone(), action.policy));
+          }
+        }
+      }
+    }
+    None
+  }
+
+  /// Recent handler panics, oldest first.
+  pub fn failures(&self) -> &[InvocationFailure] {
+    &self.failures
+  }
+
+  /// Called whenever a handler panics, e.g. to notify the user.
+  pub fn set_failure_listener(&mut self, listener: impl Fn(&InvocationFailure) + Send + 'static) {
+    self.failure_listener = Some(Box::new(listener));
+  }
+
+  /// Records a handler panic and disables the extension once it panicked
+  /// `MAX_CRASHES` times in a row.
+  fn record_crash(&mut self, extension: &str, action: &str, message: String) {
+    println!("{}-{} panicked: {}", extension, action, message);
+    let entry = self.cache_entry(extension);
+    entry.crashes += 1;
+    let disabled = entry.enabled && entry.crashes >= MAX_CRASHES;
+    self.cache_manager.write_to_cache();
+    if disabled {
+      println!("Disabling {} after {} crashes", extension, MAX_CRASHES);
+      self.set_enabled(extension, false);
+    }
+
+    let failure = InvocationFailure {
+      extension: extension.to_string(),
+      action: action.to_string(),
+      message,
+      disabled,
+    };
+    if self.failures.len() == MAX_FAILURES {
+      self.failures.remove(0);
+    }
+    self.failures.push(failure.clone());
+    if let Some(listener) = &self.failure_listener {
+      listener(&failure);
+    }
+  }
+
+  /// Reports finished invocations back to the manager, which lives behind
+  /// a mutex the dispatcher's workers can't borrow.
+  pub fn on_complete(&self, reporter: impl Fn(Completion) + Send + Sync + 'static) {
+    self.dispatcher.on_complete(reporter);
+  }
+
+  /// Records the outcome of an invocation in the history and the usage
+  /// stats. A panic counts as a crash, a successful run resets the count.
+  pub fn complete(&mut self, completion: Completion) {
+    let entry = self.history.record(&completion);
+    self.usage.record(&completion.extension, &completion.action, completion.started_at);
+    match completion.result {
+      Ok(()) => {
+        if let Some(cache) = self.cache_manager.extensions.iter_mut().find(|ext| ext.name == completion.extension) {
+          if cache.crashes > 0 {
+            cache.crashes = 0;
+            self.cache_manager.write_to_cache();
+          }
+        }
+      },
+      Err(Failure::Panic(message)) => self.record_crash(&completion.extension, &completion.action, message),
+      Err(Failure::Error(_)) => {},
+    }
+    if let Some(listener) = &self.history_listener {
+      listener(&entry);
+    }
+  }
+
+  /// Called whenever an invocation was added to the history.
+  pub fn set_history_listener(&mut self, listener: impl Fn(&HistoryEntry) + Send + 'static) {
+    self.history_listener = Some(Box::new(listener));
+  }
+
+  /// Recent invocations, newest first.
+  pub fn history(&self) -> Vec<HistoryEntry> {
+    self.history.entries()
+  }
+
+  /// Daily invocation counts per action.
+  pub fn usage_stats(&self) -> UsageReport {
+    self.usage.report()
+  }
+
+  pub fn export_usage_stats(&self, format: ExportFormat) -> String {
+    self.usage.export(format)
+  }
+
+  /// Runs the action of a history entry again.
+  pub fn rerun(&mut self, id: u64) -> Result<(), String> {
+    let entry = self.history.get(id).cloned().ok_or_else(|| format!("no history entry {}", id))?;
+    self.invoke(&entry.extension, &entry.action, Trigger::Rerun)
+  }
+
+  /// Runs the most recent action again, skipping the ones of `except`, the
+  /// extension providing the repeat action itself.
+  pub fn repeat_last(&mut self, except: &str) -> Result<(), String> {
+    let entry = self.history.last_except(except).cloned().ok_or("no action ran yet")?;
+    self.invoke(&entry.extension, &entry.action, Trigger::Repeat)
+  }
+
+  /// Dispatches an action of an enabled extension by ID.
+  pub fn invoke(&mut self, extension: &str, action: &str, trigger: Trigger) -> Result<(), String> {
+    if !self.is_enabled(extension) {
+      return Err(format!("extension `{}` is disabled", extension));
+    }
+    if let Some((extension, handler)) = self.handler_mapper.get(&self.build_key(extension.to_string(), action.to_string())).copied() {
+      self.dispatch_builtin(extension, handler, trigger);
+      return Ok(());
+    }
+
+    let view = self.view(extension).ok_or_else(|| format!("unknown extension `{}`", extension))?;
+    let policy = view.actions.iter().find(|view_action| view_action.id == action)
+      .ok_or_else(|| format!("unknown action `{}` of `{}`", action, extension))?
+      .policy;
+    let supervisor = self.external.iter().find(|supervisor| supervisor.provides(extension))
+      .ok_or_else(|| format!("no process provides `{}`", extension))?;
+    self.dispatch_external(supervisor, extension.to_string(), action.to_string(), policy, trigger);
+    Ok(())
+  }
+
+  /// Enqueues the action bound to `shortcut` on the dispatcher, subject to
+  /// the action's concurrency policy and rate limit.
+  pub fn listen(&mut self, shortcut: &Shortcut) {
+    if let Some((extension, handler)) = self.shortcut_mapper.get(shortcut).copied() {
+      self.dispatch_builtin(extension, handler, Trigger::Shortcut(*shortcut));
+      return;
+    }
+
+    if let Some((supervisor, extension, action, policy)) = self.find_external_action(shortcut) {
+      self.dispatch_external(supervisor, extension, action, policy, Trigger::Shortcut(*shortcut));
+    }
+  }
+
+  fn dispatch_builtin(&self, extension: &Extension, handler: &ShortcutHandler, trigger: Trigger) {
+    let handle = handler.handle;
+    let extension_id = extension.id.clone();
+    let action_id = handler.id.clone();
+    let settings = self.settings_for(&extension.id);
+    self.dispatcher.dispatch(Invocation {
+      extension: extension.id.clone(),
+      action: handler.id.clone(),
+      trigger,
+      policy: handler.policy,
+      job: Box::new(move |cancellation| {
+        builtin::call_handler(handle, &InvocationContext { extension: extension_id, action: action_id, settings, cancellation })
+          .map_err(Failure::Panic)
+      }),
+    });
+  }
+
+  fn dispatch_external(&self, supervisor: &ProcessSupervisor, extension: String, action: String, policy: DispatchPolicy, trigger: Trigger) {
+    let process = supervisor.handle();
+    let settings = self.settings_for(&extension);
+    let (extension_id, action_id) = (extension.clone(), action.clone());
+    self.dispatcher.dispatch(Invocation {
+      extension,
+      action,
+      trigger,
+      policy,
+      job: Box::new(move |_| {
+        process.invoke(&extension_id, &action_id, &settings).map(|_| ()).map_err(|err| {
+          println!("{}-{} failed: {}", extension_id, action_id, err);
+          Failure::Error(err.to_string())
+        })
+      }),
+    });
+  }
+
+
+}
+
+/// Installs `version` of an extension from the index, or its latest
+/// version. The package is downloaded and its checksum verified without
+/// holding `manager`, which is only locked to install it. The package must
+/// be named like its index entry.
+pub fn install_from_index(
+  manager: &Mutex<ExtensionManager>,
+  index: &RegistryIndex,
+  name: &str,
+  version: Option<&str>,
+  trusted: &[TrustedKey],
+  allow_unsigned: bool,
+) -> Result<InstalledExtension, String> {
+  package::validate_name(name)?;
+  let downloads = manager.lock().unwrap().downloads_dir().join(name);
+  let installed = index.download(name, version, &downloads).and_then(|path| {
+    let package = Package::read(&path)?;
+    if package.manifest.name != name {
+      return Err(format!("the registry index lists `{}` for a package named `{}`", name, package.manifest.name));
+    }
+    manager.lock().unwrap().install(&path, trusted, allow_unsigned)
+  });
+  let _ = std::fs::remove_dir_all(&downloads);
+  installed
+}
+
+/// Updates every installed extension that has a newer version in the index.
+pub fn update_all(
+  manager: &Mutex<ExtensionManager>,
+  index: &RegistryIndex,
+  trusted: &[TrustedKey],
+  allow_unsigned: bool,
+) -> Result<Vec<InstalledExtension>, String> {
+  let installed = manager.lock().unwrap().list_installed();
+  index.available(&installed).iter()
+    .filter(|available| available.update_available)
+    .map(|available| install_from_index(manager, index, &available.name, None, trusted, allow_unsigned))
+    .collect()
+}
+
+pub static EXTENSION_MANAGER: LazyLock<Mutex<ExtensionManager>> = LazyLock::new(|| {
+  let mut manager: ExtensionManager<'_> = ExtensionManager::new();
+  manager.on_complete(|completion| EXTENSION_MANAGER.lock().unwrap().complete(completion));
+  manager.register_builtin();
+  manager.load_external(&external::default_extensions_dir());
+  if let Err(err) = manager.set_locale(&LocaleConfig::load().locale) {
+    println!("{}", err);
+  }
+  manager.init();
+  Mutex::new(manager)
+});
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::extensions::extension::{Extension, ShortcutHandler};
+  use crate::extensions::history::Outcome;
+  use crate::extensions::locale::Translation;
+  use crate::extensions::settings::SettingKind;
+  use crate::shortcut::{Code, Modifiers};
+
+  const FIXTURE_ID: &str = "fixture";
+
+  static FIXTURE: LazyLock<Extension> = LazyLock::new(|| Extension {
+    id: FIXTURE_ID.to_string(),
+    name: "Fixture".to_string(),
+    description: "Built-in extension used by the tests".to_string(),
+    metadata: Metadata::default(),
+    shortcuts: vec![
+      ShortcutHandler {
+        id: "history_viewer".to_string(),
+        name: "HistoryViewer".to_string(),
+        description: "View paste history board".to_string(),
+        handle: |_| (),
+        default_shortcut: Some(Shortcut::new(Some(Modifiers::META | Modifiers::ALT), Code::KeyV)),
+        icon: None,
+        keywords: vec![],
+        policy: DispatchPolicy::default(),
+      },
+    ],
+    settings: vec![
+      SettingSchema {
+        key: "history_size".to_string(),
+        label: "Clipboard history size".to_string(),
+        description: "".to_string(),
+        kind: SettingKind::Number { min: Some(1.0), max: Some(10000.0) },
+        default: json!(200),
+      },
+    ],
+    capabilities: vec![Capability::ClipboardRead, Capability::ClipboardWrite],
+    translations: vec![
+      Translation {
+        locale: "de".to_string(),
+        source: "fixture-name = Testerweiterung\nfixture-history_viewer-name = Verlauf anzeigen".to_string(),
+      },
+    ],
+  });
+
+  inventory::submit! {
+    BuiltinExtension(&FIXTURE)
+  }
+
+  static CRASHING: LazyLock<Extension> = LazyLock::new(|| Extension {
+    id: "crashing".to_string(),
+    name: "Crashing".to_string(),
+    description: "Extension whose action always panics".to_string(),
+    metadata: Metadata::default(),
+    shortcuts: vec![
+      ShortcutHandler {
+        default_shortcut: Some(Shortcut::new(Some(Modifiers::ALT), Code::KeyC)),
+        ..ShortcutHandler::new("crash".to_string(), "Crash".to_string(), "".to_string(), |_| panic!("boom"))
+      },
+    ],
+    settings: vec![],
+    capabilities: vec![],
+    translations: vec![],
+  });
+
+  #[test]
+  fn test_extension_manager_new() {
+    let manager: ExtensionManager = ExtensionManager::new();
+    assert!(manager.extensions.is_empty());
+  }
+
+  #[test]
+  fn test_register_builtin() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.register_builtin();
+    assert!(manager.extensions.iter().any(|extension| extension.id == FIXTURE_ID));
+  }
+
+  #[test]
+  fn test_register_extension() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    let extension = Extension {
+      id: "test_extension".to_string(),
+      name: "Test Extension".to_string(),
+      description: "A test extension".to_string(),
+      metadata: Metadata::default(),
+      shortcuts: vec![],
+      settings: vec![],
+      capabilities: vec![],
+      translations: vec![],
+    };
+    manager.register(&extension);
+    assert_eq!(manager.extensions.len(), 1);
+    assert_eq!(manager.extensions[0].name, "Test Extension");
+  }
+
+  #[test]
+  fn test_to_string_when_meta() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    let extension = Extension {
+      id: "test_extension".to_string(),
+      name: "Test Extension".to_string(),
+      description: "A test extension".to_string(),
+      metadata: Metadata::default(),
+      shortcuts: vec![
+        ShortcutHandler{
+          id: "copy".to_string(),
+          name: "Copy".to_string(),
+          description: "Copy the selected text to the clipboard".to_string(),
+          handle: |_| -> () {
+            println!("Copy the selected text to the clipboard");
+          },
+          default_shortcut: Some(Shortcut::new(Some(Modifiers::META), Code::KeyV)),
+          icon: None,
+          keywords: vec![],
+          policy: DispatchPolicy::default(),
+        },
+      ],
+      settings: vec![],
+      capabilities: vec![],
+      translations: vec![],
+    };
+    manager.register(&extension);
+    let json_str = manager.to_string();
+    let expected_json = serde_json::json!([{
+      "id": "test_extension",
+      "name": "Test Extension",
+      "description": "A test extension",
+      "enabled": false,
+      "shortcuts": [{
+        "id": "copy",
+        "name": "Copy",
+        "description": "Copy the selected text to the clipboard",
+        "shortcut": "super+KeyV",
+        "icon": null,
+        "keywords": [],
+        "unused": false
+      }],
+      "settings": [],
+      "capabilities": [],
+      "granted": [],
+      "version": null,
+      "author": null,
+      "homepage": null,
+      "license": null,
+      "category": null,
+      "keywords": [],
+      "icon": null
+    }]);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&json_str).unwrap(), expected_json);
+  }
+
+  #[test]
+  fn test_to_string_when_alt() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    let extension = Extension {
+      id: "test_extension".to_string(),
+      name: "Test Extension".to_string(),
+      description: "A test extension".to_string(),
+      metadata: Metadata::default(),
+      shortcuts: vec![
+        ShortcutHandler{
+          id: "copy".to_string(),
+          name: "Copy".to_string(),
+          description: "Copy the selected text to the clipboard".to_string(),
+          handle: |_| -> () {
+            println!("Copy the selected text to the clipboard");
+          },
+          default_shortcut: Some(Shortcut::new(Some(Modifiers::ALT), Code::KeyV)),
+          icon: None,
+          keywords: vec![],
+          policy: DispatchPolicy::default(),
+        },
+      ],
+      settings: vec![],
+      capabilities: vec![],
+      translations: vec![],
+    };
+    manager.register(&extension);
+    let json_str = manager.to_string();
+    let expected_json = serde_json::json!([{
+      "id": "test_extension",
+      "name": "Test Extension",
+      "description": "A test extension",
+      "enabled": false,
+      "shortcuts": [{
+        "id": "copy",
+        "name": "Copy",
+        "description": "Copy the selected text to the clipboard",
+        "shortcut": "alt+KeyV",
+        "icon": null,
+        "keywords": [],
+        "unused": false
+      }],
+      "settings": [],
+      "capabilities": [],
+      "granted": [],
+      "version": null,
+      "author": null,
+      "homepage": null,
+      "license": null,
+      "category": null,
+      "keywords": [],
+      "icon": null
+    }]);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&json_str).unwrap(), expected_json);
+  }
+
+  #[test]
+  fn test_to_string_when_ctrl() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    let extension = Extension {
+      id: "test_extension".to_string(),
+      name: "Test Extension".to_string(),
+      description: "A test extension".to_string(),
+      metadata: Metadata::default(),
+      shortcuts: vec![
+        ShortcutHandler{
+          id: "copy".to_string(),
+          name: "Copy".to_string(),
+          description: "Copy the selected text to the clipboard".to_string(),
+          handle: |_| -> () {
+            println!("Copy the selected text to the clipboard");
+          },
+          default_shortcut: Some(Shortcut::new(Some(Modifiers::CONTROL), Code::KeyV)),
+          icon: None,
+          keywords: vec![],
+          policy: DispatchPolicy::default(),
+        },
+      ],
+      settings: vec![],
+      capabilities: vec![],
+      translations: vec![],
+    };
+    manager.register(&extension);
+    let json_str = manager.to_string();
+    let expected_json = serde_json::json!([{
+      "id": "test_extension",
+      "name": "Test Extension",
+      "description": "A test extension",
+      "enabled": false,
+      "shortcuts": [{
+        "id": "copy",
+        "name": "Copy",
+        "description": "Copy the selected text to the clipboard",
+        "shortcut": "control+KeyV",
+        "icon": null,
+        "keywords": [],
+        "unused": false
+      }],
+      "settings": [],
+      "capabilities": [],
+      "granted": [],
+      "version": null,
+      "author": null,
+      "homepage": null,
+      "license": null,
+      "category": null,
+      "keywords": [],
+      "icon": null
+    }]);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&json_str).unwrap(), expected_json);
+  }
+
+  #[test]
+  fn test_to_string_when_shift() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    let extension = Extension {
+      id: "test_extension".to_string(),
+      name: "Test Extension".to_string(),
+      description: "A test extension".to_string(),
+      metadata: Metadata::default(),
+      shortcuts: vec![
+        ShortcutHandler{
+          id: "copy".to_string(),
+          name: "Copy".to_string(),
+          description: "Copy the selected text to the clipboard".to_string(),
+          handle: |_| -> () {
+            println!("Copy the selected text to the clipboard");
+          },
+          default_shortcut: Some(Shortcut::new(Some(Modifiers::SHIFT), Code::KeyV)),
+          icon: None,
+          keywords: vec![],
+          policy: DispatchPolicy::default(),
+        },
+      ],
+      settings: vec![],
+      capabilities: vec![],
+      translations: vec![],
+    };
+    manager.register(&extension);
+    let json_str = manager.to_string();
+    let expected_json = serde_json::json!([{
+      "id": "test_extension",
+      "name": "Test Extension",
+      "description": "A test extension",
+      "enabled": false,
+      "shortcuts": [{
+        "id": "copy",
+        "name": "Copy",
+        "description": "Copy the selected text to the clipboard",
+        "shortcut": "shift+KeyV",
+        "icon": null,
+        "keywords": [],
+        "unused": false
+      }],
+      "settings": [],
+      "capabilities": [],
+      "granted": [],
+      "version": null,
+      "author": null,
+      "homepage": null,
+      "license": null,
+      "category": null,
+      "keywords": [],
+      "icon": null
+    }]);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&json_str).unwrap(), expected_json);
+  }
+
+  #[test]
+  fn test_external_extensions() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    let supervisor = ProcessSupervisor::new(ProcessManifest {
+      name: "fixture".to_string(),
+      command: "sh".to_string(),
+      args: vec!["rpc_extension.sh".to_string()],
+      extensions: vec!["Echo".to_string()],
+      dir: std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"),
+      translations: vec![],
+    }, manager.host());
+    manager.register_external(supervisor);
+    manager.external[0].start();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !manager.external[0].provides("Echo") && std::time::Instant::now() < deadline {
+      std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let expected_json = serde_json::json!([{
+      "id": "Echo",
+      "name": "Echo",
+      "description": "Echo extension",
+      "enabled": false,
+      "shortcuts": [{
+        "id": "Say",
+        "name": "Say",
+        "description": "Say hello",
+        "shortcut": "alt+KeyE",
+        "icon": null,
+        "keywords": ["greet"],
+        "unused": false
+      }],
+      "settings": [{
+        "key": "greeting",
+        "label": "Greeting",
+        "description": "",
+        "type": "string",
+        "default": "hello",
+        "value": "hello"
+      }],
+      "capabilities": ["shell:exec"],
+      "granted": [],
+      "version": "1.0.0",
+      "author": "Shortcuts",
+      "homepage": null,
+      "license": null,
+      "category": null,
+      "keywords": ["echo"],
+      "icon": "symbol:speaker"
+    }]);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&manager.to_string()).unwrap(), expected_json);
+
+    let shortcut = Shortcut::new(Some(Modifiers::ALT), Code::KeyE);
+    assert!(manager.find_external_action(&shortcut).is_none());
+
+    manager.cache_manager.add(CacheExtension {
+      name: "Echo".to_string(),
+      shortcuts: vec![],
+      enabled: true,
+      settings: HashMap::new(),
+      granted: vec![],
+      crashes: 0,
+    });
+    let (_, extension, action, _) = manager.find_external_action(&shortcut).unwrap();
+    assert_eq!((extension.as_str(), action.as_str()), ("Echo", "Say"));
+  }
+
+  #[test]
+  fn test_start_external_skips_disabled() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_start_external_skips_disabled.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    let supervisor = ProcessSupervisor::new(ProcessManifest {
+      name: "fixture".to_string(),
+      command: "sh".to_string(),
+      args: vec!["rpc_extension.sh".to_string()],
+      extensions: vec!["Echo".to_string()],
+      dir: std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"),
+      translations: vec![],
+    }, manager.host());
+    manager.register_external(supervisor);
+    manager.start_external();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(!manager.external[0].is_started());
+    assert!(!manager.external[0].is_running());
+    assert_eq!(manager.view("Echo").unwrap().name, "Echo");
+
+    manager.set_enabled("Echo", true);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !manager.external[0].provides("Echo") && std::time::Instant::now() < deadline {
+      std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(manager.external[0].provides("Echo"));
+
+    manager.set_enabled("Echo", false);
+    assert!(!manager.external[0].is_running());
+  }
+
+  #[test]
+  fn test_set_settings() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_set_settings.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&FIXTURE);
+
+    assert_eq!(manager.settings_for(FIXTURE_ID).get_f64("history_size"), Some(200.0));
+
+    let mut values = Map::new();
+    values.insert("history_size".to_string(), json!(50));
+    assert!(manager.set_settings(FIXTURE_ID, values).is_ok());
+    assert_eq!(manager.settings_for(FIXTURE_ID).get_f64("history_size"), Some(50.0));
+
+    let mut values = Map::new();
+    values.insert("history_size".to_string(), json!(0));
+    assert!(manager.set_settings(FIXTURE_ID, values).is_err());
+    assert!(manager.set_settings("Unknown", Map::new()).is_err());
+
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&FIXTURE);
+    manager.init();
+    assert_eq!(manager.settings_for(FIXTURE_ID).get_f64("history_size"), Some(50.0));
+  }
+
+  #[test]
+  fn test_grant() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_grant.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&FIXTURE);
+    let permissions = manager.host().permissions();
+
+    assert!(manager.grant(FIXTURE_ID, vec![Capability::ClipboardRead, Capability::ShellExec]).is_ok());
+    assert!(!permissions.is_granted(FIXTURE_ID, &Capability::ClipboardRead));
+
+    manager.set_enabled(FIXTURE_ID, true);
+    assert!(permissions.is_granted(FIXTURE_ID, &Capability::ClipboardRead));
+    assert!(!permissions.is_granted(FIXTURE_ID, &Capability::ShellExec));
+
+    manager.set_enabled(FIXTURE_ID, false);
+    assert!(!permissions.is_granted(FIXTURE_ID, &Capability::ClipboardRead));
+    assert!(manager.grant("Unknown", vec![]).is_err());
+
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&FIXTURE);
+    manager.init();
+    assert_eq!(manager.cache_manager.to_map()[FIXTURE_ID].granted, vec![Capability::ClipboardRead]);
+  }
+
+  #[test]
+  fn test_default_shortcuts_are_bound() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_default_shortcuts_are_bound.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&FIXTURE);
+    manager.init();
+    assert!(manager.shortcut_mapper.is_empty());
+
+    manager.set_enabled(FIXTURE_ID, true);
+    manager.build_shortcut_mapper();
+    let (extension, handler) = manager.shortcut_mapper[&Shortcut::new(Some(Modifiers::META | Modifiers::ALT), Code::KeyV)];
+    assert_eq!((extension.id.as_str(), handler.id.as_str()), (FIXTURE_ID, "history_viewer"));
+  }
+
+  #[test]
+  fn test_panicking_handler_is_disabled() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_panicking_handler_is_disabled.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.history = ActionHistory::new(Some(".shortcuts/__test__/test_panicking_handler_history.json".to_string()));
+    manager.usage = UsageStats::new(Some(".shortcuts/__test__/test_panicking_handler_usage.json".to_string()));
+    manager.register(&CRASHING);
+    manager.init();
+    manager.set_enabled("crashing", true);
+
+    let notified = Arc::new(Mutex::new(vec![]));
+    let listener_notified = notified.clone();
+    manager.set_failure_listener(move |failure| listener_notified.lock().unwrap().push(failure.clone()));
+
+    let manager = Arc::new(Mutex::new(manager));
+    let reporter = Arc::downgrade(&manager);
+    manager.lock().unwrap().on_complete(move |completion| {
+      if let Some(manager) = reporter.upgrade() {
+        manager.lock().unwrap().complete(completion);
+      }
+    });
+
+    let shortcut = Shortcut::new(Some(Modifiers::ALT), Code::KeyC);
+    for crashes in 1..=MAX_CRASHES as usize {
+      manager.lock().unwrap().listen(&shortcut);
+      let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+      while manager.lock().unwrap().failures().len() < crashes && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+      }
+    }
+    let mut manager = manager.lock().unwrap();
+    assert!(!manager.is_enabled("crashing"));
+    assert_eq!(manager.cache_manager.to_map()["crashing"].crashes, MAX_CRASHES);
+    let failures = manager.failures().to_vec();
+    assert_eq!(failures.len(), MAX_CRASHES as usize);
+    assert_eq!((failures[0].action.as_str(), failures[0].message.as_str()), ("crash", "boom"));
+    assert!(!failures[0].disabled);
+    assert!(failures.last().unwrap().disabled);
+    assert_eq!(*notified.lock().unwrap(), failures);
+
+    manager.listen(&shortcut);
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    assert_eq!(manager.failures().len(), MAX_CRASHES as usize);
+    drop(manager);
+
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&CRASHING);
+    manager.init();
+    assert!(!manager.is_enabled("crashing"));
+    manager.set_enabled("crashing", true);
+    assert_eq!(manager.cache_manager.to_map()["crashing"].crashes, 0);
+  }
+
+  #[test]
+  fn test_history_rerun_and_repeat() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_history_rerun_and_repeat.json";
+    const HISTORY_PATH: &str = ".shortcuts/__test__/test_history_rerun_and_repeat_history.json";
+    const USAGE_PATH: &str = ".shortcuts/__test__/test_history_rerun_and_repeat_usage.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(HISTORY_PATH));
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(USAGE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.history = ActionHistory::new(Some(HISTORY_PATH.to_string()));
+    manager.usage = UsageStats::new(Some(USAGE_PATH.to_string()));
+    manager.register(&FIXTURE);
+    manager.init();
+    assert!(manager.invoke(FIXTURE_ID, "history_viewer", Trigger::Rerun).is_err());
+    assert!(manager.repeat_last("none").is_err());
+    manager.set_enabled(FIXTURE_ID, true);
+
+    let manager = Arc::new(Mutex::new(manager));
+    let reporter = Arc::downgrade(&manager);
+    manager.lock().unwrap().on_complete(move |completion| {
+      if let Some(manager) = reporter.upgrade() {
+        manager.lock().unwrap().complete(completion);
+      }
+    });
+    let wait_for_history = |len: usize| {
+      let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+      while manager.lock().unwrap().history().len() < len && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+      }
+      manager.lock().unwrap().history()
+    };
+
+    let shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::KeyV);
+    manager.lock().unwrap().listen(&shortcut);
+    let history = wait_for_history(1);
+    assert_eq!((history[0].extension.as_str(), history[0].action.as_str()), (FIXTURE_ID, "history_viewer"));
+    assert_eq!(history[0].trigger, Trigger::Shortcut(shortcut));
+    assert_eq!(history[0].outcome, Outcome::Succeeded);
+
+    manager.lock().unwrap().repeat_last("none").unwrap();
+    assert_eq!(wait_for_history(2)[0].trigger, Trigger::Repeat);
+    let id = history[0].id;
+    manager.lock().unwrap().rerun(id).unwrap();
+    assert_eq!(wait_for_history(3)[0].trigger, Trigger::Rerun);
+    assert!(manager.lock().unwrap().rerun(999).is_err());
+    assert!(manager.lock().unwrap().repeat_last(FIXTURE_ID).is_err());
+    let usage = manager.lock().unwrap().usage_stats();
+    assert_eq!((usage.actions[0].action.as_str(), usage.actions[0].total), ("history_viewer", 3));
+  }
+
+  #[test]
+  fn test_migrate_cache_from_names() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_migrate_cache_from_names.json";
+    let path = dirs::home_dir().unwrap().join(TEST_FILE_PATH);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, r#"[{"name":"Fixture","enabled":true,"shortcuts":[{"name":"HistoryViewer","shortcut":"alt+KeyH"}]}]"#).unwrap();
+
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.register(&FIXTURE);
+    manager.init();
+
+    let cache = &manager.cache_manager.to_map()[FIXTURE_ID];
+    assert!(cache.enabled);
+    assert_eq!(cache.shortcuts[0].name, "history_viewer");
+    assert!(manager.shortcut_mapper.contains_key(&Shortcut::new(Some(Modifiers::ALT), Code::KeyH)));
+    assert!(std::fs::read_to_string(&path).unwrap().contains(r#""name":"fixture""#));
+  }
+
+  #[test]
+  fn test_localized_names() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.register(&FIXTURE);
+    let names = |manager: &ExtensionManager| {
+      let extensions: Value = serde_json::from_str(&manager.to_string()).unwrap();
+      (extensions[0]["name"].clone(), extensions[0]["shortcuts"][0]["name"].clone(), extensions[0]["description"].clone())
+    };
+    assert_eq!(names(&manager), (json!("Fixture"), json!("HistoryViewer"), json!("Built-in extension used by the tests")));
+
+    manager.set_locale("de-CH").unwrap();
+    assert_eq!(manager.locale(), "de-CH");
+    assert_eq!(names(&manager), (json!("Testerweiterung"), json!("Verlauf anzeigen"), json!("Built-in extension used by the tests")));
+
+    assert!(manager.set_locale("not a locale").is_err());
+    assert_eq!(manager.locale(), "de-CH");
+  }
+
+  #[test]
+  fn test_install_and_uninstall() {
+    let dir = std::env::temp_dir().join("shortcuts_test_install_and_uninstall");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("extensions")).unwrap();
+
+    let fixture = std::fs::read(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpc_extension.sh")).unwrap();
+    let mut entries = std::collections::BTreeMap::new();
+    entries.insert("manifest.json".to_string(), br#"{"name":"echo","version":"1.0.0","command":"sh","args":["main.sh"],"extensions":["Echo"]}"#.to_vec());
+    entries.insert("main.sh".to_string(), fixture);
+    package::write_package(&dir.join("echo.scext"), &entries, None).unwrap();
+
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_install_and_uninstall.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.load_external(&dir.join("extensions"));
+    assert!(manager.install(&dir.join("echo.scext"), &[], false).is_err());
+    assert!(manager.external.is_empty());
+
+    let installed = manager.install(&dir.join("echo.scext"), &[], true).unwrap();
+    assert_eq!(installed.version, "1.0.0");
+    assert_eq!(manager.list_installed(), vec![installed]);
+    assert_eq!(manager.external.len(), 1);
+    assert!(!manager.external[0].is_started());
+
+    manager.set_enabled("Echo", true);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !manager.external[0].provides("Echo") && std::time::Instant::now() < deadline {
+      std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(manager.external[0].provides("Echo"));
+
+    manager.uninstall("echo").unwrap();
+    assert!(manager.external.is_empty());
+    assert!(manager.list_installed().is_empty());
+  }
+
+  #[test]
+  fn test_reload_changed() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_reload_changed.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let dir = std::env::temp_dir().join("shortcuts_test_reload_changed");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("extensions/echo")).unwrap();
+    std::fs::copy(
+      std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpc_extension.sh"),
+      dir.join("extensions/echo/main.sh"),
+    ).unwrap();
+    std::fs::write(dir.join("extensions/echo/manifest.json"), r#"{"name":"echo","command":"sh","args":["main.sh"],"extensions":["Echo"]}"#).unwrap();
+
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    manager.load_external(&dir.join("extensions"));
+    manager.cache_entry("Echo").enabled = true;
+    manager.start_external();
+    manager.watch();
+    let wait_for_echo = |manager: &ExtensionManager| {
+      let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+      while !manager.external.iter().any(|supervisor| supervisor.provides("Echo")) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+      }
+      manager.external.iter().any(|supervisor| supervisor.provides("Echo"))
+    };
+    assert!(wait_for_echo(&manager));
+    assert!(manager.reload_changed().is_empty());
+
+    let mut values = Map::new();
+    values.insert("greeting".to_string(), json!("bonjour"));
+    manager.set_settings("Echo", values).unwrap();
+
+    std::fs::write(dir.join("extensions/echo/manifest.json"), r#"{"name":"echo","command":"sh","args":["main.sh","--dev"],"extensions":["Echo"]}"#).unwrap();
+    assert_eq!(manager.reload_changed(), vec![dir.join("extensions/echo")]);
+    assert_eq!(manager.external.len(), 1);
+    assert!(wait_for_echo(&manager));
+    assert_eq!(manager.settings_for("Echo").get_str("greeting"), Some("bonjour"));
+
+    std::fs::write(dir.join("extensions/echo/manifest.json"), "{").unwrap();
+    assert_eq!(manager.reload_changed(), vec![dir.join("extensions/echo")]);
+    assert!(manager.external.is_empty());
+    assert_eq!(manager.load_errors().len(), 1);
+    assert_eq!(manager.load_errors()[0].path, dir.join("extensions/echo"));
+
+    std::fs::write(dir.join("extensions/echo/manifest.json"), r#"{"name":"echo","command":"sh","args":["main.sh"],"extensions":["Echo"]}"#).unwrap();
+    manager.reload_changed();
+    assert!(manager.load_errors().is_empty());
+    assert!(wait_for_echo(&manager));
+  }
+
+  #[test]
+  fn test_load_dev_extension() {
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.load_external(&std::env::temp_dir().join("shortcuts_test_load_dev_extension"));
+    assert!(manager.load_dev_extension(Path::new("/nonexistent/extension")).is_err());
+
+    let dir = std::env::temp_dir().join("shortcuts_test_load_dev_extension_src");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("manifest.json"), "{").unwrap();
+    manager.watch();
+    assert!(manager.load_dev_extension(&dir).is_err());
+    assert_eq!(manager.load_errors()[0].path, dir.canonicalize().unwrap());
+
+    std::fs::write(dir.join("manifest.json"), r#"{"name":"dev","command":"sh","args":["main.sh"],"extensions":["Echo"]}"#).unwrap();
+    assert_eq!(manager.reload_changed(), vec![dir.canonicalize().unwrap()]);
+    assert!(manager.load_errors().is_empty());
+    assert_eq!(manager.external[0].name(), "dev");
+
+    std::fs::write(dir.join("main.sh"), "").unwrap();
+    assert_eq!(manager.reload_changed(), vec![dir.canonicalize().unwrap()]);
+    assert_eq!(manager.external[0].name(), "dev");
+  }
+
+  #[test]
+  fn test_update_from_index() {
+    use sha2::{Digest, Sha256};
+
+    let dir = std::env::temp_dir().join("shortcuts_test_update_from_index");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("registry")).unwrap();
+
+    let mut checksums = vec![];
+    for version in ["1.0.0", "1.1.0"] {
+      let mut entries = std::collections::BTreeMap::new();
+      let manifest = format!(r#"{{"name":"echo","version":"{}","command":"sh","args":["main.sh"],"extensions":["Echo"]}}"#, version);
+      entries.insert("manifest.json".to_string(), manifest.into_bytes());
+      let path = dir.join("registry").join(format!("echo-{}.scext", version));
+      package::write_package(&path, &entries, None).unwrap();
+      checksums.push(hex::encode(Sha256::digest(std::fs::read(path).unwrap())));
+    }
+    std::fs::write(dir.join("registry/index.json"), format!(r#"{{"extensions":[{{"name":"echo","versions":[
+      {{"version":"1.0.0","package":"echo-1.0.0.scext","sha256":"{0}"}},
+      {{"version":"1.1.0","package":"echo-1.1.0.scext","sha256":"{1}"}}]}},
+      {{"name":"impostor","versions":[{{"version":"1.0.0","package":"echo-1.0.0.scext","sha256":"{0}"}}]}}]}}"#,
+      checksums[0], checksums[1])).unwrap();
+
+    let index = RegistryIndex::load(dir.join("registry").to_str().unwrap()).unwrap();
+    let mut manager: ExtensionManager = ExtensionManager::new();
+    manager.load_external(&dir.join("extensions"));
+    let manager = Mutex::new(manager);
+
+    assert!(install_from_index(&manager, &index, "echo", Some("1.0.0"), &[], false).is_err());
+    assert_eq!(install_from_index(&manager, &index, "echo", Some("1.0.0"), &[], true).unwrap().version, "1.0.0");
+    assert!(index.available(&manager.lock().unwrap().list_installed())[0].update_available);
+
+    let updated = update_all(&manager, &index, &[], true).unwrap();
+    assert_eq!(updated.len(), 1);
+    assert_eq!(manager.lock().unwrap().list_installed()[0].version, "1.1.0");
+    assert!(update_all(&manager, &index, &[], true).unwrap().is_empty());
+    assert!(install_from_index(&manager, &index, "missing", None, &[], true).is_err());
+    assert!(install_from_index(&manager, &index, "impostor", None, &[], true).is_err(), "the package is named echo");
+    assert!(install_from_index(&manager, &index, "../../registry", None, &[], true).is_err());
+    assert!(dir.join("registry/index.json").exists(), "a traversal name never reaches the filesystem");
+    assert!(!manager.lock().unwrap().downloads_dir().join("echo").exists());
+  }
+}
@@ -0,0 +1,717 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::rpc::{
+  self, AnnounceParams, AnnouncedExtension, Caller, HostServices, InvokeParams, RpcError, RpcMessage, RpcRequest,
+  RpcResponse, HOST_METHOD_PREFIX, INTERNAL_ERROR, METHOD_ANNOUNCE, METHOD_INVOKE, METHOD_LOAD, METHOD_NOT_FOUND,
+  METHOD_UNLOAD,
+};
+use super::locale::{self, Translation};
+use super::settings::Settings;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const INVOKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a process gets to exit after `extension/unload` before it is killed.
+pub const UNLOAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Describes how to launch an out-of-process extension. Lives in
+/// `<extensions dir>/<name>/manifest.json`.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct ProcessManifest {
+  pub name: String,
+  pub command: String,
+  #[serde(default)]
+  pub args: Vec<String>,
+  /// IDs of the extensions the process may announce. Without them it may
+  /// only announce one named like the manifest.
+  #[serde(default)]
+  pub extensions: Vec<String>,
+  #[serde(skip)]
+  pub dir: PathBuf,
+  /// Read from `locales/*.ftl` next to the manifest.
+  #[serde(skip)]
+  pub translations: Vec<Translation>,
+}
+
+impl ProcessManifest {
+  pub fn read(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    let mut manifest: ProcessManifest = serde_json::from_str(&contents)?;
+    manifest.dir = dir.to_path_buf();
+    manifest.translations = locale::read_translations(dir);
+    Ok(manifest)
+  }
+
+  pub fn may_provide(&self, extension: &str) -> bool {
+    match self.extensions.is_empty() {
+      true => extension == self.name,
+      false => self.extensions.iter().any(|id| id == extension),
+    }
+  }
+
+  /// IDs of the extensions the process may announce.
+  pub fn extension_ids(&self) -> Vec<String> {
+    match self.extensions.is_empty() {
+      true => vec![self.name.clone()],
+      false => self.extensions.clone(),
+    }
+  }
+
+  /// Identifies the process to the host. Manifest names may repeat, e.g.
+  /// for a development copy of an installed extension, directories don't.
+  pub fn owner(&self) -> String {
+    self.dir.display().to_string()
+  }
+
+  fn program(&self) -> PathBuf {
+    let command = PathBuf::from(&self.command);
+    if command.is_relative() && command.components().count() > 1 {
+      return self.dir.join(command);
+    }
+    command
+  }
+}
+
+pub fn default_extensions_dir() -> PathBuf {
+  dirs::home_dir().unwrap().join(".shortcuts/extensions")
+}
+
+/// An extension that could not be loaded, shown in the UI until it is fixed.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct LoadError {
+  pub path: PathBuf,
+  pub error: String,
+}
+
+pub fn load_manifests(dir: &Path) -> Vec<ProcessManifest> {
+  let (manifests, errors) = scan_manifests(dir);
+  errors.iter().for_each(|error| println!("Skipping extension at {:?}: {}", error.path, error.error));
+  manifests
+}
+
+/// Reads every manifest below `dir`, returning the ones that failed
+/// separately instead of logging them.
+pub fn scan_manifests(dir: &Path) -> (Vec<ProcessManifest>, Vec<LoadError>) {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(_) => return (vec![], vec![]),
+  };
+
+  let mut manifests = vec![];
+  let mut errors = vec![];
+  for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+    if !path.join(MANIFEST_FILE).is_file() {
+      continue;
+    }
+    match ProcessManifest::read(&path) {
+      Ok(manifest) => manifests.push(manifest),
+      Err(err) => errors.push(LoadError { path, error: err.to_string() }),
+    }
+  }
+  manifests.sort_by(|a, b| a.name.cmp(&b.name));
+  errors.sort_by(|a, b| a.path.cmp(&b.path));
+  (manifests, errors)
+}
+
+/// Exponential restart delay: `initial * 2^attempt`, capped at `max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+  pub initial: Duration,
+  pub max: Duration,
+  attempt: u32,
+}
+
+impl Backoff {
+  pub fn new(initial: Duration, max: Duration) -> Self {
+    Backoff { initial, max, attempt: 0 }
+  }
+
+  pub fn next_delay(&mut self) -> Duration {
+    let delay = self.initial.saturating_mul(2u32.saturating_pow(self.attempt));
+    self.attempt = self.attempt.saturating_add(1);
+    delay.min(self.max)
+  }
+
+  pub fn reset(&mut self) {
+    self.attempt = 0;
+  }
+}
+
+impl Default for Backoff {
+  fn default() -> Self {
+    Backoff::new(Duration::from_millis(500), Duration::from_secs(30))
+  }
+}
+
+#[derive(Default)]
+struct SupervisorState {
+  running: bool,
+  generation: u64,
+  child: Option<Child>,
+  restarts: u32,
+  extensions: Vec<AnnouncedExtension>,
+  last_error: Option<String>,
+}
+
+struct Shared {
+  state: Mutex<SupervisorState>,
+  /// Kept apart from `state` so a write to a stalled child never blocks
+  /// readers of the supervisor state.
+  stdin: Mutex<Option<ChildStdin>>,
+  pending: Mutex<HashMap<u64, Sender<RpcResponse>>>,
+  next_id: AtomicU64,
+}
+
+/// Keeps one extension process alive: spawns it, restarts it with backoff
+/// when it exits unexpectedly and kills it on `stop`.
+pub struct ProcessSupervisor {
+  manifest: ProcessManifest,
+  host: Arc<dyn HostServices>,
+  backoff: Backoff,
+  shared: Arc<Shared>,
+}
+
+impl ProcessSupervisor {
+  pub fn new(manifest: ProcessManifest, host: Arc<dyn HostServices>) -> Self {
+    ProcessSupervisor {
+      manifest,
+      host,
+      backoff: Backoff::default(),
+      shared: Arc::new(Shared {
+        state: Mutex::new(SupervisorState::default()),
+        stdin: Mutex::new(None),
+        pending: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+      }),
+    }
+  }
+
+  pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+    self.backoff = backoff;
+    self
+  }
+
+  pub fn name(&self) -> &str {
+    &self.manifest.name
+  }
+
+  pub fn dir(&self) -> &Path {
+    &self.manifest.dir
+  }
+
+  pub fn translations(&self) -> &[Translation] {
+    &self.manifest.translations
+  }
+
+  pub fn start(&self) {
+    let generation = {
+      let mut state = self.shared.state.lock().unwrap();
+      if state.running {
+        return;
+      }
+      state.running = true;
+      state.generation += 1;
+      state.generation
+    };
+
+    let manifest = self.manifest.clone();
+    let host = self.host.clone();
+    let backoff = self.backoff;
+    let shared = self.shared.clone();
+    thread::spawn(move || supervise(manifest, host, backoff, shared, generation));
+  }
+
+  pub fn stop(&self) {
+    let mut state = self.shared.state.lock().unwrap();
+    state.running = false;
+    if let Some(mut child) = state.child.take() {
+      let _ = child.kill();
+      let _ = child.wait();
+    }
+    *self.shared.stdin.lock().unwrap() = None;
+    drop(state);
+    fail_pending(&self.shared);
+  }
+
+  /// Sends `extension/unload` and waits up to `UNLOAD_TIMEOUT` for the
+  /// process to exit on its own before stopping it.
+  pub fn unload(&self) {
+    self.shared.state.lock().unwrap().running = false;
+    let notification = RpcMessage::Request(RpcRequest::notification(METHOD_UNLOAD, json!({})));
+    if send(&self.shared, &notification).is_ok() {
+      let deadline = Instant::now() + UNLOAD_TIMEOUT;
+      while self.is_running() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+      }
+    }
+    self.stop();
+  }
+
+  /// Whether the process is supervised, even while it waits to be restarted.
+  pub fn is_started(&self) -> bool {
+    self.shared.state.lock().unwrap().running
+  }
+
+  pub fn is_running(&self) -> bool {
+    self.shared.state.lock().unwrap().child.is_some()
+  }
+
+  pub fn restarts(&self) -> u32 {
+    self.shared.state.lock().unwrap().restarts
+  }
+
+  /// The extensions most recently announced by the process. Kept after the
+  /// process stops so disabled extensions stay visible and can be re-enabled.
+  pub fn extensions(&self) -> Vec<AnnouncedExtension> {
+    self.shared.state.lock().unwrap().extensions.clone()
+  }
+
+  /// Why the process last failed to start or announce itself, cleared by
+  /// the next valid announcement.
+  pub fn last_error(&self) -> Option<String> {
+    self.shared.state.lock().unwrap().last_error.clone()
+  }
+
+  pub fn extension_ids(&self) -> Vec<String> {
+    self.manifest.extension_ids()
+  }
+
+  pub fn may_provide(&self, extension: &str) -> bool {
+    self.manifest.may_provide(extension)
+  }
+
+  pub fn provides(&self, extension: &str) -> bool {
+    self.extensions().iter().any(|ext| ext.id() == extension)
+  }
+
+  pub fn invoke(&self, extension: &str, action: &str, settings: &Settings) -> Result<Value, RpcError> {
+    self.handle().invoke(extension, action, settings)
+  }
+
+  pub fn handle(&self) -> ProcessHandle {
+    ProcessHandle { name: self.manifest.name.clone(), shared: self.shared.clone() }
+  }
+}
+
+impl Drop for ProcessSupervisor {
+  fn drop(&mut self) {
+    self.stop();
+    self.host.release(&self.manifest.owner());
+  }
+}
+
+/// Sends requests to a supervised process from any thread. Unlike the
+/// supervisor, dropping a handle leaves the process running.
+#[derive(Clone)]
+pub struct ProcessHandle {
+  name: String,
+  shared: Arc<Shared>,
+}
+
+impl ProcessHandle {
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn invoke(&self, extension: &str, action: &str, settings: &Settings) -> Result<Value, RpcError> {
+    let params = serde_json::to_value(InvokeParams {
+      extension: extension.to_string(),
+      action: action.to_string(),
+      settings: settings.to_json(),
+    }).unwrap();
+    self.request(METHOD_INVOKE, params)
+  }
+
+  fn request(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+    let id = self.shared.next_id.fetch_add(1, Ordering::SeqCst);
+    let (sender, receiver) = mpsc::channel();
+    self.shared.pending.lock().unwrap().insert(id, sender);
+
+    if let Err(err) = send(&self.shared, &RpcMessage::Request(RpcRequest::new(id, method, params))) {
+      self.shared.pending.lock().unwrap().remove(&id);
+      return Err(err);
+    }
+
+    match receiver.recv_timeout(INVOKE_TIMEOUT) {
+      Ok(response) => response.into_result(),
+      Err(RecvTimeoutError::Timeout) => {
+        self.shared.pending.lock().unwrap().remove(&id);
+        Err(RpcError::new(INTERNAL_ERROR, &format!("extension process `{}` did not respond", self.name())))
+      },
+      Err(RecvTimeoutError::Disconnected) => {
+        Err(RpcError::new(INTERNAL_ERROR, &format!("extension process `{}` exited", self.name())))
+      },
+    }
+  }
+}
+
+fn spawn(manifest: &ProcessManifest) -> std::io::Result<(Child, ChildStdin, ChildStdout)> {
+  let mut child = Command::new(manifest.program())
+    .args(&manifest.args)
+    .current_dir(&manifest.dir)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .spawn()?;
+  let stdin = child.stdin.take().unwrap();
+  let stdout = child.stdout.take().unwrap();
+  Ok((child, stdin, stdout))
+}
+
+fn is_current(shared: &Shared, generation: u64) -> bool {
+  let state = shared.state.lock().unwrap();
+  state.running && state.generation == generation
+}
+
+fn supervise(manifest: ProcessManifest, host: Arc<dyn HostServices>, mut backoff: Backoff, shared: Arc<Shared>, generation: u64) {
+  loop {
+    let started = Instant::now();
+    match spawn(&manifest) {
+      Ok((mut child, stdin, stdout)) => {
+        {
+          let mut state = shared.state.lock().unwrap();
+          if !state.running || state.generation != generation {
+            let _ = child.kill();
+            let _ = child.wait();
+            return;
+          }
+          state.child = Some(child);
+          *shared.stdin.lock().unwrap() = Some(stdin);
+        }
+
+        serve(&manifest, &*host, &shared, stdout);
+
+        let mut state = shared.state.lock().unwrap();
+        if state.generation != generation {
+          return;
+        }
+        if let Some(mut child) = state.child.take() {
+          let _ = child.kill();
+          let _ = child.wait();
+        }
+        *shared.stdin.lock().unwrap() = None;
+        drop(state);
+        fail_pending(&shared);
+      },
+      Err(err) => {
+        println!("Failed to start extension process {}: {}", manifest.name, err);
+        shared.state.lock().unwrap().last_error = Some(format!("failed to start `{}`: {}", manifest.command, err));
+      },
+    }
+
+    {
+      let mut state = shared.state.lock().unwrap();
+      if !state.running || state.generation != generation {
+        return;
+      }
+      state.restarts += 1;
+    }
+
+    if started.elapsed() > backoff.max {
+      backoff.reset();
+    }
+    let delay = backoff.next_delay();
+    println!("Extension process {} exited, restarting in {:?}", manifest.name, delay);
+    thread::sleep(delay);
+
+    if !is_current(&shared, generation) {
+      return;
+    }
+  }
+}
+
+fn serve(manifest: &ProcessManifest, host: &dyn HostServices, shared: &Shared, stdout: ChildStdout) {
+  for line in BufReader::new(stdout).lines() {
+    let line = match line {
+      Ok(line) => line,
+      Err(_) => break,
+    };
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    match rpc::decode(&line) {
+      Ok(RpcMessage::Response(response)) => {
+        if let Some(sender) = shared.pending.lock().unwrap().remove(&response.id) {
+          let _ = sender.send(response);
+        }
+      },
+      Ok(RpcMessage::Request(request)) => handle_request(manifest, host, shared, request),
+      Err(err) => println!("Invalid message from extension process {}: {}", manifest.name, err),
+    }
+  }
+}
+
+fn handle_request(manifest: &ProcessManifest, host: &dyn HostServices, shared: &Shared, request: RpcRequest) {
+  let id = match request.id {
+    Some(id) => id,
+    None => {
+      if request.method == METHOD_ANNOUNCE {
+        match serde_json::from_value::<AnnounceParams>(request.params).map_err(|err| err.to_string()).and_then(|params| {
+          claim(manifest, host, &params)?;
+          Ok(params)
+        }) {
+          Ok(params) => {
+            {
+              let mut state = shared.state.lock().unwrap();
+              state.extensions = params.extensions;
+              state.last_error = None;
+            }
+            let _ = send(shared, &RpcMessage::Request(RpcRequest::notification(METHOD_LOAD, json!({}))));
+          },
+          Err(err) => {
+            println!("Invalid announcement from extension process {}: {}", manifest.name, err);
+            shared.state.lock().unwrap().last_error = Some(format!("invalid announcement: {}", err));
+          },
+        }
+      }
+      return;
+    }
+  };
+
+  let result = if request.method.starts_with(HOST_METHOD_PREFIX) {
+    let caller = Caller { process: manifest.owner() };
+    host.call(&caller, &request.method, request.params)
+  } else {
+    Err(RpcError::new(METHOD_NOT_FOUND, &format!("unknown method `{}`", request.method)))
+  };
+  let response = match result {
+    Ok(value) => RpcResponse::ok(id, value),
+    Err(err) => RpcResponse::err(id, err),
+  };
+  if let Err(err) = send(shared, &RpcMessage::Response(response)) {
+    println!("Failed to reply to extension process {}: {}", manifest.name, err);
+  }
+}
+
+/// Ties the announced extensions to the process, so it can only act with
+/// the grants of extensions its manifest lists and no one else provides.
+fn claim(manifest: &ProcessManifest, host: &dyn HostServices, params: &AnnounceParams) -> Result<(), String> {
+  let ids: Vec<String> = params.extensions.iter().map(|ext| ext.id().to_string()).collect();
+  if let Some(id) = ids.iter().find(|id| !manifest.may_provide(id)) {
+    return Err(format!("`{}` is not listed in the manifest", id));
+  }
+  host.claim(&manifest.owner(), &ids)
+}
+
+fn send(shared: &Shared, message: &RpcMessage) -> Result<(), RpcError> {
+  let mut stdin = shared.stdin.lock().unwrap();
+  let stdin = stdin.as_mut()
+    .ok_or_else(|| RpcError::new(INTERNAL_ERROR, "extension process is not running"))?;
+  stdin.write_all(rpc::encode(message).as_bytes())
+    .and_then(|_| stdin.flush())
+    .map_err(|err| RpcError::new(INTERNAL_ERROR, &err.to_string()))
+}
+
+fn fail_pending(shared: &Shared) {
+  shared.pending.lock().unwrap().clear();
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use crate::extensions::host::HostApi;
+  use crate::extensions::permissions::Permissions;
+
+  fn fixture_manifest() -> ProcessManifest {
+    ProcessManifest {
+      name: "fixture".to_string(),
+      command: "sh".to_string(),
+      args: vec!["rpc_extension.sh".to_string()],
+      extensions: vec!["Echo".to_string()],
+      dir: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"),
+      translations: vec![],
+    }
+  }
+
+  fn fixture_supervisor() -> ProcessSupervisor {
+    ProcessSupervisor::new(fixture_manifest(), Arc::new(HostApi::new(Arc::new(Permissions::new()))))
+      .with_backoff(Backoff::new(Duration::from_millis(10), Duration::from_millis(50)))
+  }
+
+  fn wait_until(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+      if condition() {
+        return true;
+      }
+      thread::sleep(Duration::from_millis(10));
+    }
+    false
+  }
+
+  #[test]
+  fn test_backoff() {
+    let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(500));
+    assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+    assert_eq!(backoff.next_delay(), Duration::from_millis(200));
+    assert_eq!(backoff.next_delay(), Duration::from_millis(400));
+    assert_eq!(backoff.next_delay(), Duration::from_millis(500));
+    backoff.reset();
+    assert_eq!(backoff.next_delay(), Duration::from_millis(100));
+  }
+
+  #[test]
+  fn test_load_manifests() {
+    let dir = std::env::temp_dir().join("shortcuts_test_load_manifests");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("echo")).unwrap();
+    fs::create_dir_all(dir.join("broken")).unwrap();
+    fs::create_dir_all(dir.join("empty")).unwrap();
+    fs::write(dir.join("echo").join(MANIFEST_FILE), r#"{"name":"echo","command":"./echo.py"}"#).unwrap();
+    fs::write(dir.join("broken").join(MANIFEST_FILE), "{").unwrap();
+
+    let manifests = load_manifests(&dir);
+    assert_eq!(manifests.len(), 1);
+    let (_, errors) = scan_manifests(&dir);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, dir.join("broken"));
+    assert_eq!(manifests[0].name, "echo");
+    assert_eq!(manifests[0].program(), dir.join("echo").join("echo.py"));
+  }
+
+  #[test]
+  fn test_announce_and_invoke() {
+    let supervisor = fixture_supervisor();
+    supervisor.start();
+    assert!(wait_until(|| supervisor.provides("Echo")));
+
+    let extensions = supervisor.extensions();
+    assert_eq!(extensions[0].shortcuts[0].name, "Say");
+    assert_eq!(extensions[0].shortcuts[0].default_shortcut, Some("alt+KeyE".to_string()));
+    assert_eq!(extensions[0].settings[0].key, "greeting");
+    assert_eq!(supervisor.invoke("Echo", "Say", &Settings::default()), Ok(json!({ "said": "" })));
+
+    let mut stored = HashMap::new();
+    stored.insert("greeting".to_string(), json!("bonjour"));
+    let settings = Settings::resolve(&extensions[0].settings, &stored);
+    assert_eq!(supervisor.invoke("Echo", "Say", &settings), Ok(json!({ "said": "bonjour" })));
+  }
+
+  #[test]
+  fn test_child_calls_host() {
+    let supervisor = fixture_supervisor();
+    supervisor.start();
+    assert!(wait_until(|| supervisor.provides("Echo")));
+
+    let result = supervisor.invoke("Echo", "Version", &Settings::default()).unwrap();
+    assert_eq!(result["host"]["version"], json!(env!("CARGO_PKG_VERSION")));
+  }
+
+  #[test]
+  fn test_restart_after_crash() {
+    let supervisor = fixture_supervisor();
+    supervisor.start();
+    assert!(wait_until(|| supervisor.is_running()));
+
+    assert!(supervisor.invoke("Echo", "Crash", &Settings::default()).is_err());
+    assert!(wait_until(|| supervisor.restarts() == 1 && supervisor.is_running()));
+    assert!(wait_until(|| supervisor.invoke("Echo", "Say", &Settings::default()).is_ok()));
+  }
+
+  #[test]
+  fn test_state_readable_while_writing() {
+    let supervisor = fixture_supervisor();
+    supervisor.start();
+    assert!(wait_until(|| supervisor.provides("Echo")));
+
+    let _writer = supervisor.shared.stdin.lock().unwrap();
+    assert!(supervisor.is_running());
+    assert_eq!(supervisor.extensions()[0].shortcuts[0].name, "Say");
+  }
+
+  #[test]
+  fn test_unload_lets_process_exit() {
+    let supervisor = fixture_supervisor();
+    supervisor.start();
+    assert!(wait_until(|| supervisor.provides("Echo") && supervisor.is_running()));
+
+    let started = Instant::now();
+    supervisor.unload();
+    assert!(started.elapsed() < UNLOAD_TIMEOUT);
+    assert!(!supervisor.is_running());
+    assert!(!supervisor.is_started());
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(supervisor.restarts(), 0);
+  }
+
+  #[test]
+  fn test_last_error() {
+    let mut manifest = fixture_manifest();
+    manifest.command = "./missing-extension-binary".to_string();
+    let supervisor = ProcessSupervisor::new(manifest, Arc::new(HostApi::new(Arc::new(Permissions::new()))))
+      .with_backoff(Backoff::new(Duration::from_millis(10), Duration::from_millis(50)));
+    supervisor.start();
+    assert!(wait_until(|| supervisor.last_error().is_some()));
+    assert!(supervisor.last_error().unwrap().contains("missing-extension-binary"));
+  }
+
+  #[test]
+  fn test_announcement_must_be_owned() {
+    let mut manifest = fixture_manifest();
+    manifest.extensions = vec!["Other".to_string()];
+    let unlisted = ProcessSupervisor::new(manifest, Arc::new(HostApi::new(Arc::new(Permissions::new()))));
+    unlisted.start();
+    assert!(wait_until(|| unlisted.last_error().is_some()));
+    assert!(unlisted.last_error().unwrap().contains("not listed in the manifest"));
+    assert!(!unlisted.provides("Echo"));
+
+    let host = Arc::new(HostApi::new(Arc::new(Permissions::new())));
+    host.claim("owner", &["Echo".to_string()]).unwrap();
+    let impostor = ProcessSupervisor::new(fixture_manifest(), host.clone());
+    impostor.start();
+    assert!(wait_until(|| impostor.last_error().is_some()));
+    assert!(!impostor.provides("Echo"));
+    assert_eq!(host.permissions().claimed_by("owner"), vec!["Echo".to_string()]);
+  }
+
+  #[test]
+  fn test_same_name_in_other_dir_cannot_claim() {
+    let dir = std::env::temp_dir().join("shortcuts_test_same_name_in_other_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(fixture_manifest().dir.join("rpc_extension.sh"), dir.join("rpc_extension.sh")).unwrap();
+
+    let host = Arc::new(HostApi::new(Arc::new(Permissions::new())));
+    let installed = ProcessSupervisor::new(fixture_manifest(), host.clone());
+    installed.start();
+    assert!(wait_until(|| installed.provides("Echo")));
+
+    let mut manifest = fixture_manifest();
+    manifest.dir = dir.clone();
+    let copy = ProcessSupervisor::new(manifest, host.clone());
+    copy.start();
+    assert!(wait_until(|| copy.last_error().is_some()));
+    assert!(!copy.provides("Echo"));
+
+    drop(copy);
+    assert_eq!(host.permissions().claimed_by(&fixture_manifest().owner()), vec!["Echo".to_string()]);
+    assert!(host.permissions().claimed_by(&dir.display().to_string()).is_empty());
+  }
+
+  #[test]
+  fn test_stop_kills_process() {
+    let supervisor = fixture_supervisor();
+    supervisor.start();
+    assert!(wait_until(|| supervisor.is_running()));
+
+    supervisor.stop();
+    assert!(!supervisor.is_running());
+    assert!(supervisor.invoke("Echo", "Say", &Settings::default()).is_err());
+    thread::sleep(Duration::from_millis(100));
+    assert!(!supervisor.is_running());
+    assert_eq!(supervisor.restarts(), 0);
+    assert!(supervisor.provides("Echo"));
+  }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::UNIX_EPOCH;
+
+use super::dispatcher::{Completion, Failure, Trigger};
+use crate::modules::config::Config;
+
+const HISTORY_PATH: &str = ".shortcuts/history.json";
+/// Entries kept; the oldest are dropped first.
+pub const MAX_ENTRIES: usize = 200;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+  Succeeded,
+  Failed,
+  Panicked,
+  /// Superseded by a restart before it finished.
+  Cancelled,
+}
+
+/// One finished action invocation.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct HistoryEntry {
+  pub id: u64,
+  pub extension: String,
+  pub action: String,
+  pub trigger: Trigger,
+  /// Unix time in milliseconds.
+  pub started_at: u64,
+  pub duration_ms: u64,
+  pub outcome: Outcome,
+  #[serde(default)]
+  pub error: Option<String>,
+}
+
+impl HistoryEntry {
+  pub fn new(id: u64, completion: &Completion) -> Self {
+    let (outcome, error) = match &completion.result {
+      Ok(()) if completion.cancelled => (Outcome::Cancelled, None),
+      Ok(()) => (Outcome::Succeeded, None),
+      Err(Failure::Error(message)) => (Outcome::Failed, Some(message.clone())),
+      Err(Failure::Panic(message)) => (Outcome::Panicked, Some(message.clone())),
+    };
+    HistoryEntry {
+      id,
+      extension: completion.extension.clone(),
+      action: completion.action.clone(),
+      trigger: completion.trigger,
+      started_at: completion.started_at.duration_since(UNIX_EPOCH).map(|since| since.as_millis() as u64).unwrap_or_default(),
+      duration_ms: completion.duration.as_millis() as u64,
+      outcome,
+      error,
+    }
+  }
+}
+
+/// The last `MAX_ENTRIES` invocations, persisted in `.shortcuts/history.json`.
+#[derive(Debug)]
+pub struct ActionHistory {
+  entries: VecDeque<HistoryEntry>,
+  config: Config,
+}
+
+impl ActionHistory {
+  pub fn new(path: Option<String>) -> Self {
+    let path = path.unwrap_or_else(|| HISTORY_PATH.to_string());
+    ActionHistory { entries: VecDeque::new(), config: Config::new(path) }
+  }
+
+  pub fn load(&mut self) {
+    self.entries = self.config.read_config().ok()
+      .and_then(|contents| serde_json::from_str(&contents).ok())
+      .unwrap_or_default();
+  }
+
+  fn save(&self) {
+    if let Err(err) = self.config.write_config(&serde_json::to_string(&self.entries).unwrap()) {
+      println!("Failed to save action history: {}", err);
+    }
+  }
+
+  pub fn record(&mut self, completion: &Completion) -> HistoryEntry {
+    let id = self.entries.back().map(|entry| entry.id + 1).unwrap_or(1);
+    let entry = HistoryEntry::new(id, completion);
+    self.entries.push_back(entry.clone());
+    while self.entries.len() > MAX_ENTRIES {
+      self.entries.pop_front();
+    }
+    self.save();
+    entry
+  }
+
+  /// Newest first.
+  pub fn entries(&self) -> Vec<HistoryEntry> {
+    self.entries.iter().rev().cloned().collect()
+  }
+
+  pub fn get(&self, id: u64) -> Option<&HistoryEntry> {
+    self.entries.iter().find(|entry| entry.id == id)
+  }
+
+  /// The newest entry that isn't from `extension`, so the action repeating
+  /// the last one doesn't find itself.
+  pub fn last_except(&self, extension: &str) -> Option<&HistoryEntry> {
+    self.entries.iter().rev().find(|entry| entry.extension != extension)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn completion(extension: &str, result: Result<(), Failure>) -> Completion {
+    Completion {
+      extension: extension.to_string(),
+      action: "run".to_string(),
+      trigger: Trigger::Rerun,
+      started_at: UNIX_EPOCH + Duration::from_secs(1),
+      duration: Duration::from_millis(12),
+      cancelled: false,
+      result,
+    }
+  }
+
+  #[test]
+  fn test_entry_from_completion() {
+    let entry = HistoryEntry::new(7, &completion("echo", Err(Failure::Panic("boom".to_string()))));
+    assert_eq!((entry.id, entry.started_at, entry.duration_ms), (7, 1000, 12));
+    assert_eq!((entry.outcome, entry.error), (Outcome::Panicked, Some("boom".to_string())));
+
+    let mut cancelled = completion("echo", Ok(()));
+    cancelled.cancelled = true;
+    assert_eq!(HistoryEntry::new(1, &cancelled).outcome, Outcome::Cancelled);
+    assert_eq!(HistoryEntry::new(1, &completion("echo", Err(Failure::Error("no".to_string())))).outcome, Outcome::Failed);
+  }
+
+  #[test]
+  fn test_record_and_load() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_history_record_and_load.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut history = ActionHistory::new(Some(TEST_FILE_PATH.to_string()));
+    history.load();
+    for _ in 0..MAX_ENTRIES + 5 {
+      history.record(&completion("echo", Ok(())));
+    }
+    history.record(&completion("history", Ok(())));
+
+    let mut history = ActionHistory::new(Some(TEST_FILE_PATH.to_string()));
+    history.load();
+    let entries = history.entries();
+    assert_eq!(entries.len(), MAX_ENTRIES);
+    assert_eq!(entries[0].id, MAX_ENTRIES as u64 + 6);
+    assert_eq!(history.last_except("history").unwrap().id, MAX_ENTRIES as u64 + 5);
+    assert!(history.get(1).is_none());
+    assert_eq!(history.get(10).unwrap().extension, "echo");
+  }
+}
@@ -0,0 +1,215 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+
+use super::permissions::{self, Capability, Permissions};
+use super::rpc::{Caller, HostServices, RpcError, INTERNAL_ERROR, INVALID_PARAMS, METHOD_NOT_FOUND, PERMISSION_DENIED};
+
+pub type HostFn = Box<dyn Fn(&Caller, Value) -> Result<Value, RpcError> + Send + Sync>;
+
+/// What a host method needs to be granted before it runs.
+pub enum Requirement {
+  Nothing,
+  Capability(Capability),
+  /// A filesystem capability covering the `path` parameter. The handler
+  /// gets `path` with symlinks resolved, as it was checked.
+  Path,
+}
+
+struct HostMethod {
+  requirement: Requirement,
+  handler: HostFn,
+}
+
+/// The host API reachable from extension processes. Every method declares a
+/// requirement that is checked against the caller's grants before it runs.
+pub struct HostApi {
+  permissions: Arc<Permissions>,
+  methods: RwLock<HashMap<String, HostMethod>>,
+}
+
+impl HostApi {
+  pub fn new(permissions: Arc<Permissions>) -> Self {
+    let api = HostApi {
+      permissions,
+      methods: RwLock::new(HashMap::new()),
+    };
+
+    api.register("host/log", Requirement::Nothing, |caller, params| {
+      println!("[{}] {}", caller.process, str_param(&params, "message")?);
+      Ok(Value::Null)
+    });
+    api.register("host/version", Requirement::Nothing, |_, _| {
+      Ok(json!({ "version": env!("CARGO_PKG_VERSION") }))
+    });
+    api.register("host/shell.exec", Requirement::Capability(Capability::ShellExec), |_, params| {
+      let args: Vec<String> = params.get("args").cloned()
+        .map(serde_json::from_value).transpose()
+        .map_err(|err| RpcError::new(INVALID_PARAMS, &err.to_string()))?
+        .unwrap_or_default();
+      let output = Command::new(str_param(&params, "command")?).args(args).output()
+        .map_err(|err| RpcError::new(INTERNAL_ERROR, &err.to_string()))?;
+      Ok(json!({
+        "status": output.status.code(),
+        "stdout": String::from_utf8_lossy(&output.stdout),
+        "stderr": String::from_utf8_lossy(&output.stderr),
+      }))
+    });
+    api.register("host/fs.read", Requirement::Path, |_, params| {
+      let contents = fs::read_to_string(str_param(&params, "path")?)
+        .map_err(|err| RpcError::new(INTERNAL_ERROR, &err.to_string()))?;
+      Ok(json!({ "contents": contents }))
+    });
+    api.register("host/fs.write", Requirement::Path, |_, params| {
+      fs::write(str_param(&params, "path")?, str_param(&params, "contents")?)
+        .map_err(|err| RpcError::new(INTERNAL_ERROR, &err.to_string()))?;
+      Ok(Value::Null)
+    });
+    api
+  }
+
+  /// Adds or replaces a host method. Used for services that only exist once
+  /// the app is running, such as showing the panel.
+  pub fn register(
+    &self,
+    method: &str,
+    requirement: Requirement,
+    handler: impl Fn(&Caller, Value) -> Result<Value, RpcError> + Send + Sync + 'static,
+  ) {
+    self.methods.write().unwrap().insert(method.to_string(), HostMethod {
+      requirement,
+      handler: Box::new(handler),
+    });
+  }
+
+  pub fn permissions(&self) -> Arc<Permissions> {
+    self.permissions.clone()
+  }
+}
+
+impl HostServices for HostApi {
+  fn call(&self, caller: &Caller, method: &str, mut params: Value) -> Result<Value, RpcError> {
+    let methods = self.methods.read().unwrap();
+    let host_method = methods.get(method)
+      .ok_or_else(|| RpcError::new(METHOD_NOT_FOUND, &format!("unknown host method `{}`", method)))?;
+
+    let capability = match &host_method.requirement {
+      Requirement::Nothing => None,
+      Requirement::Capability(capability) => Some(capability.clone()),
+      Requirement::Path => {
+        let path = PathBuf::from(str_param(&params, "path")?);
+        if !path.is_absolute() {
+          return Err(RpcError::new(INVALID_PARAMS, "`path` must be absolute"));
+        }
+        let path = permissions::resolve(&path).unwrap_or(path);
+        params["path"] = json!(path);
+        Some(Capability::Filesystem(path))
+      },
+    };
+    if let Some(capability) = capability {
+      self.permissions.check(&caller.process, &capability)
+        .map_err(|denied| RpcError::new(PERMISSION_DENIED, &format!("{} ({} from {})", denied, method, caller.process)))?;
+    }
+
+    (host_method.handler)(caller, params)
+  }
+
+  fn claim(&self, process: &str, extensions: &[String]) -> Result<(), String> {
+    self.permissions.claim(process, extensions)
+  }
+
+  fn release(&self, process: &str) {
+    self.permissions.release(process);
+  }
+}
+
+fn str_param<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+  params.get(key).and_then(Value::as_str)
+    .ok_or_else(|| RpcError::new(INVALID_PARAMS, &format!("expected a `{}` string", key)))
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn caller() -> Caller {
+    Caller { process: "fixture".to_string() }
+  }
+
+  fn api(permissions: Arc<Permissions>) -> HostApi {
+    let api = HostApi::new(permissions);
+    api.claim("fixture", &["Echo".to_string()]).unwrap();
+    api
+  }
+
+  #[test]
+  fn test_unrestricted_methods() {
+    let api = api(Arc::new(Permissions::new()));
+    assert_eq!(api.call(&caller(), "host/log", json!({ "message": "hello" })), Ok(Value::Null));
+    assert_eq!(api.call(&caller(), "host/log", json!({})).unwrap_err().code, INVALID_PARAMS);
+    assert_eq!(api.call(&caller(), "host/unknown", Value::Null).unwrap_err().code, METHOD_NOT_FOUND);
+  }
+
+  #[test]
+  fn test_shell_exec_requires_capability() {
+    let permissions = Arc::new(Permissions::new());
+    let api = api(permissions.clone());
+    let params = json!({ "command": "echo", "args": ["hi"] });
+    assert_eq!(api.call(&caller(), "host/shell.exec", params.clone()).unwrap_err().code, PERMISSION_DENIED);
+
+    permissions.set("Echo", vec![Capability::ShellExec]);
+    let result = api.call(&caller(), "host/shell.exec", params.clone()).unwrap();
+    assert_eq!(result["stdout"], json!("hi\n"));
+
+    let impostor = Caller { process: "impostor".to_string() };
+    assert!(api.claim("impostor", &["Echo".to_string()]).is_err(), "Echo belongs to the fixture");
+    assert_eq!(api.call(&impostor, "host/shell.exec", params).unwrap_err().code, PERMISSION_DENIED);
+  }
+
+  #[test]
+  fn test_fs_requires_covering_path() {
+    let dir = std::env::temp_dir().join("shortcuts_test_fs_requires_covering_path");
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("note.txt");
+    let permissions = Arc::new(Permissions::new());
+    let api = api(permissions.clone());
+    let write = json!({ "path": file, "contents": "hello" });
+    assert_eq!(api.call(&caller(), "host/fs.write", write.clone()).unwrap_err().code, PERMISSION_DENIED);
+
+    permissions.set("Echo", vec![Capability::Filesystem(dir.clone())]);
+    assert!(api.call(&caller(), "host/fs.write", write).is_ok());
+    assert_eq!(api.call(&caller(), "host/fs.read", json!({ "path": file })).unwrap()["contents"], json!("hello"));
+    assert_eq!(api.call(&caller(), "host/fs.read", json!({ "path": "/etc/hosts" })).unwrap_err().code, PERMISSION_DENIED);
+    assert_eq!(api.call(&caller(), "host/fs.read", json!({ "path": "note.txt" })).unwrap_err().code, INVALID_PARAMS);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_fs_handler_gets_checked_path() {
+    let dir = std::env::temp_dir().join("shortcuts_test_fs_handler_gets_checked_path");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("granted")).unwrap();
+    std::os::unix::fs::symlink(dir.join("granted"), dir.join("link")).unwrap();
+    let permissions = Arc::new(Permissions::new());
+    let api = api(permissions.clone());
+    api.register("host/fs.path", Requirement::Path, |_, params| Ok(params["path"].clone()));
+    permissions.set("Echo", vec![Capability::Filesystem(dir.join("granted"))]);
+
+    let checked = dir.join("granted").canonicalize().unwrap().join("note.txt");
+    assert_eq!(api.call(&caller(), "host/fs.path", json!({ "path": dir.join("link/note.txt") })), Ok(json!(checked)));
+  }
+
+  #[test]
+  fn test_register() {
+    let permissions = Arc::new(Permissions::new());
+    let api = api(permissions.clone());
+    api.register("host/ui.show", Requirement::Capability(Capability::ShowUi), |_, _| Ok(json!(true)));
+    assert_eq!(api.call(&caller(), "host/ui.show", Value::Null).unwrap_err().code, PERMISSION_DENIED);
+    permissions.set("Echo", vec![Capability::ShowUi]);
+    assert_eq!(api.call(&caller(), "host/ui.show", Value::Null), Ok(json!(true)));
+  }
+}
@@ -0,0 +1,222 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::FluentResource;
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use unic_langid::LanguageIdentifier;
+
+use crate::modules::config::Config;
+
+// Extensions translate their strings with Fluent files named after the
+// locale (`locales/de.ftl`, `locales/pt-BR.ftl`). Messages are keyed by
+// stable IDs so renaming an extension never breaks its translations:
+//
+//   clipboard-name = Zwischenablage
+//   clipboard-description = ...
+//   clipboard-history_viewer-name = Verlauf
+//   clipboard-history_viewer-description = ...
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+pub const LOCALES_DIR: &str = "locales";
+const LOCALE_CONFIG_PATH: &str = ".shortcuts/locale.json";
+
+/// The Fluent source for one locale.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Translation {
+  pub locale: String,
+  pub source: String,
+}
+
+impl Translation {
+  /// Checks the locale tag and the Fluent syntax.
+  pub fn validate(&self) -> Result<(), String> {
+    parse_locale(&self.locale)?;
+    FluentResource::try_new(self.source.clone())
+      .map(|_| ())
+      .map_err(|(_, errors)| format!("invalid Fluent source for {}: {:?}", self.locale, errors))
+  }
+}
+
+pub fn parse_locale(locale: &str) -> Result<LanguageIdentifier, String> {
+  locale.parse().map_err(|err| format!("invalid locale `{}`: {}", locale, err))
+}
+
+/// Reads `<dir>/locales/*.ftl`, skipping files whose name isn't a locale.
+pub fn read_translations(dir: &Path) -> Vec<Translation> {
+  let entries = match fs::read_dir(dir.join(LOCALES_DIR)) {
+    Ok(entries) => entries,
+    Err(_) => return vec![],
+  };
+  let mut translations: Vec<Translation> = entries
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| path.extension().is_some_and(|extension| extension == "ftl"))
+    .filter_map(|path| {
+      let locale = path.file_stem()?.to_string_lossy().to_string();
+      parse_locale(&locale).ok()?;
+      Some(Translation { locale, source: fs::read_to_string(&path).ok()? })
+    })
+    .collect();
+  translations.sort_by(|a, b| a.locale.cmp(&b.locale));
+  translations
+}
+
+/// Resolves extension strings in the current locale. All translations are
+/// merged into one bundle per locale since message IDs are namespaced by
+/// extension ID.
+pub struct Localizer {
+  locale: LanguageIdentifier,
+  bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Default for Localizer {
+  fn default() -> Self {
+    Localizer::new(DEFAULT_LOCALE.parse().unwrap())
+  }
+}
+
+impl Localizer {
+  pub fn new(locale: LanguageIdentifier) -> Self {
+    Localizer { locale, bundles: vec![] }
+  }
+
+  pub fn locale(&self) -> &LanguageIdentifier {
+    &self.locale
+  }
+
+  pub fn set_locale(&mut self, locale: LanguageIdentifier) {
+    self.locale = locale;
+  }
+
+  pub fn clear(&mut self) {
+    self.bundles.clear();
+  }
+
+  /// Adds translations, logging and skipping the ones that don't parse.
+  pub fn add(&mut self, translations: &[Translation]) {
+    for translation in translations {
+      let locale = match parse_locale(&translation.locale) {
+        Ok(locale) => locale,
+        Err(err) => {
+          println!("{}", err);
+          continue;
+        },
+      };
+      let resource = match FluentResource::try_new(translation.source.clone()) {
+        Ok(resource) => resource,
+        Err((resource, errors)) => {
+          println!("Fluent errors in {} translation: {:?}", translation.locale, errors);
+          resource
+        },
+      };
+
+      let position = match self.bundles.iter().position(|bundle| bundle.locales[0] == locale) {
+        Some(position) => position,
+        None => {
+          let mut bundle = FluentBundle::new_concurrent(vec![locale]);
+          bundle.set_use_isolating(false);
+          self.bundles.push(bundle);
+          self.bundles.len() - 1
+        },
+      };
+      self.bundles[position].add_resource_overriding(resource);
+    }
+  }
+
+  /// The message in the best available locale: the current one, then a
+  /// matching language, then English. `None` when no locale has it.
+  pub fn message(&self, id: &str) -> Option<String> {
+    let available: Vec<LanguageIdentifier> = self.bundles.iter().map(|bundle| bundle.locales[0].clone()).collect();
+    let default: LanguageIdentifier = DEFAULT_LOCALE.parse().unwrap();
+    let negotiated = negotiate_languages(std::slice::from_ref(&self.locale), &available, Some(&default), NegotiationStrategy::Filtering);
+
+    negotiated.into_iter().find_map(|locale| {
+      let bundle = self.bundles.iter().find(|bundle| &bundle.locales[0] == locale)?;
+      let pattern = bundle.get_message(id)?.value()?;
+      let mut errors = vec![];
+      Some(bundle.format_pattern(pattern, None, &mut errors).to_string())
+    })
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct LocaleConfig {
+  pub locale: String,
+}
+
+impl Default for LocaleConfig {
+  fn default() -> Self {
+    LocaleConfig { locale: system_locale() }
+  }
+}
+
+impl LocaleConfig {
+  pub fn load() -> Self {
+    Config::new(LOCALE_CONFIG_PATH.to_string()).read_config().ok()
+      .and_then(|contents| serde_json::from_str(&contents).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self) -> Result<(), String> {
+    Config::new(LOCALE_CONFIG_PATH.to_string())
+      .write_config(&serde_json::to_string(self).unwrap())
+      .map_err(|err| err.to_string())
+  }
+}
+
+/// The locale from `LC_ALL`, `LC_MESSAGES` or `LANG` (`de_DE.UTF-8` becomes
+/// `de-DE`), falling back to English.
+fn system_locale() -> String {
+  ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+    .filter_map(|name| std::env::var(name).ok())
+    .map(|value| value.split(['.', '@']).next().unwrap_or_default().replace('_', "-"))
+    .find(|locale| !locale.is_empty() && locale != "C" && locale != "POSIX" && parse_locale(locale).is_ok())
+    .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn translations() -> Vec<Translation> {
+    vec![
+      Translation { locale: "en-US".to_string(), source: "echo-name = Echo\necho-say-name = Say".to_string() },
+      Translation { locale: "de".to_string(), source: "echo-name = Echo auf Deutsch".to_string() },
+    ]
+  }
+
+  #[test]
+  fn test_message_falls_back() {
+    let mut localizer = Localizer::new("de-AT".parse().unwrap());
+    localizer.add(&translations());
+    assert_eq!(localizer.message("echo-name"), Some("Echo auf Deutsch".to_string()));
+    assert_eq!(localizer.message("echo-say-name"), Some("Say".to_string()));
+    assert_eq!(localizer.message("echo-description"), None);
+
+    localizer.set_locale("fr".parse().unwrap());
+    assert_eq!(localizer.message("echo-name"), Some("Echo".to_string()));
+  }
+
+  #[test]
+  fn test_validate() {
+    assert!(translations()[0].validate().is_ok());
+    assert!(Translation { locale: "de".to_string(), source: "echo-name = {".to_string() }.validate().is_err());
+    assert!(Translation { locale: "not a locale".to_string(), source: "".to_string() }.validate().is_err());
+  }
+
+  #[test]
+  fn test_read_translations() {
+    let dir = std::env::temp_dir().join("shortcuts_test_read_translations");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join(LOCALES_DIR)).unwrap();
+    fs::write(dir.join("locales/de.ftl"), "echo-name = Echo").unwrap();
+    fs::write(dir.join("locales/README.md"), "").unwrap();
+    fs::write(dir.join("locales/not a locale.ftl"), "").unwrap();
+
+    let translations = read_translations(&dir);
+    assert_eq!(translations, vec![Translation { locale: "de".to_string(), source: "echo-name = Echo".to_string() }]);
+    assert!(read_translations(&dir.join("missing")).is_empty());
+  }
+}
@@ -0,0 +1,117 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const SVG_DATA_PREFIX: &str = "data:image/svg+xml;base64,";
+const PNG_DATA_PREFIX: &str = "data:image/png;base64,";
+
+/// An extension or action icon. Serialized as a string the webview can use
+/// directly: `symbol:<name>` for a named symbol from the UI's icon set, or a
+/// `data:` URL for embedded SVG and PNG images.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub enum Icon {
+  Symbol(String),
+  Svg(String),
+  Png(Vec<u8>),
+}
+
+impl fmt::Display for Icon {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Icon::Symbol(name) => write!(f, "symbol:{}", name),
+      Icon::Svg(markup) => write!(f, "{}{}", SVG_DATA_PREFIX, STANDARD.encode(markup)),
+      Icon::Png(bytes) => write!(f, "{}{}", PNG_DATA_PREFIX, STANDARD.encode(bytes)),
+    }
+  }
+}
+
+impl FromStr for Icon {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let decode = |data: &str| STANDARD.decode(data).map_err(|err| format!("invalid icon data: {}", err));
+    if let Some(name) = value.strip_prefix("symbol:") {
+      return match name.is_empty() {
+        true => Err("icon symbol name is empty".to_string()),
+        false => Ok(Icon::Symbol(name.to_string())),
+      };
+    }
+    if let Some(data) = value.strip_prefix(SVG_DATA_PREFIX) {
+      return String::from_utf8(decode(data)?).map(Icon::Svg).map_err(|err| format!("invalid SVG icon: {}", err));
+    }
+    if let Some(data) = value.strip_prefix(PNG_DATA_PREFIX) {
+      return decode(data).map(Icon::Png);
+    }
+    Err(format!("unsupported icon `{}`, expected `symbol:<name>` or an SVG/PNG data URL", value))
+  }
+}
+
+impl TryFrom<String> for Icon {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<Icon> for String {
+  fn from(icon: Icon) -> Self {
+    icon.to_string()
+  }
+}
+
+/// Descriptive information shown in settings and used for search. Every
+/// field is optional so external extensions can announce as much as they have.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Metadata {
+  #[serde(default)]
+  pub version: Option<Version>,
+  #[serde(default)]
+  pub author: Option<String>,
+  #[serde(default)]
+  pub homepage: Option<String>,
+  /// An SPDX license expression, e.g. `MIT OR Apache-2.0`.
+  #[serde(default)]
+  pub license: Option<String>,
+  #[serde(default)]
+  pub category: Option<String>,
+  #[serde(default)]
+  pub keywords: Vec<String>,
+  #[serde(default)]
+  pub icon: Option<Icon>,
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_icon_round_trip() {
+    for icon in [
+      Icon::Symbol("clipboard".to_string()),
+      Icon::Svg("<svg xmlns=\"http://www.w3.org/2000/svg\"/>".to_string()),
+      Icon::Png(vec![0x89, b'P', b'N', b'G']),
+    ] {
+      assert_eq!(icon.to_string().parse::<Icon>(), Ok(icon));
+    }
+    assert_eq!(Icon::Png(vec![1, 2, 3]).to_string(), "data:image/png;base64,AQID");
+    assert!("symbol:".parse::<Icon>().is_err());
+    assert!("https://example.com/icon.png".parse::<Icon>().is_err());
+    assert!("data:image/png;base64,!!".parse::<Icon>().is_err());
+  }
+
+  #[test]
+  fn test_deserialize_metadata() {
+    let metadata: Metadata = serde_json::from_str(r#"{"version":"1.2.0","keywords":["echo"],"icon":"symbol:speaker"}"#).unwrap();
+    assert_eq!(metadata.version, Some(Version::new(1, 2, 0)));
+    assert_eq!(metadata.keywords, vec!["echo".to_string()]);
+    assert_eq!(metadata.icon, Some(Icon::Symbol("speaker".to_string())));
+    assert_eq!(metadata.author, None);
+    assert!(serde_json::from_str::<Metadata>(r#"{"version":"one"}"#).is_err());
+  }
+}
@@ -0,0 +1,355 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use super::external::MANIFEST_FILE;
+
+// An extension package is a zip archive holding `manifest.json`, any assets
+// and an optional script or WASM payload. `SIGNATURE` carries an ed25519
+// signature over every other entry (see `Package::signing_message`).
+
+pub const SIGNATURE_FILE: &str = "SIGNATURE";
+pub const INSTALL_FILE: &str = ".install.json";
+const SIGNING_CONTEXT: &[u8] = b"shortcuts-package-v1\n";
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct PackageManifest {
+  pub name: String,
+  pub version: String,
+  #[serde(default)]
+  pub description: String,
+  /// Launches the extension process. Packages without one only ship data.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub command: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub args: Vec<String>,
+  /// IDs of the extensions the process may announce, covered by the
+  /// signature like the rest of the manifest.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct PackageSignature {
+  /// Hex encoded ed25519 public key.
+  pub public_key: String,
+  /// Hex encoded ed25519 signature.
+  pub signature: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct TrustedKey {
+  pub name: String,
+  /// Hex encoded ed25519 public key.
+  pub public_key: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct InstalledExtension {
+  pub name: String,
+  pub version: String,
+  pub description: String,
+  /// Name of the trusted key that signed the package, `None` if it was installed unsigned.
+  pub signed_by: Option<String>,
+}
+
+pub fn default_trusted_keys_path() -> PathBuf {
+  dirs::home_dir().unwrap().join(".shortcuts/trusted_keys.json")
+}
+
+pub fn read_trusted_keys(path: &Path) -> Vec<TrustedKey> {
+  fs::read_to_string(path).ok()
+    .and_then(|contents| serde_json::from_str(&contents).ok())
+    .unwrap_or_default()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Verification {
+  Unsigned,
+  /// Signed by the named trusted key.
+  Trusted(String),
+  /// Validly signed, but by a key that is not in the trusted list.
+  Untrusted(String),
+}
+
+pub struct Package {
+  pub manifest: PackageManifest,
+  pub entries: BTreeMap<String, Vec<u8>>,
+  pub signature: Option<PackageSignature>,
+}
+
+impl Package {
+  pub fn read(path: &Path) -> Result<Self, String> {
+    let file = File::open(path).map_err(|err| format!("cannot open {:?}: {}", path, err))?;
+    let mut archive = ZipArchive::new(file).map_err(|err| err.to_string())?;
+    let mut entries = BTreeMap::new();
+    let mut signature = None;
+
+    for index in 0..archive.len() {
+      let mut entry = archive.by_index(index).map_err(|err| err.to_string())?;
+      if entry.is_dir() {
+        continue;
+      }
+      let name = entry.enclosed_name()
+        .filter(|name| name.components().all(|c| matches!(c, Component::Normal(_))))
+        .ok_or_else(|| format!("unsafe path `{}` in package", entry.name()))?
+        .to_string_lossy()
+        .replace('\\', "/");
+      let mut contents = vec![];
+      entry.read_to_end(&mut contents).map_err(|err| err.to_string())?;
+
+      if name == SIGNATURE_FILE {
+        signature = Some(serde_json::from_slice(&contents).map_err(|err| format!("invalid signature: {}", err))?);
+      } else {
+        entries.insert(name, contents);
+      }
+    }
+
+    let manifest: PackageManifest = serde_json::from_slice(
+      entries.get(MANIFEST_FILE).ok_or_else(|| format!("package has no {}", MANIFEST_FILE))?,
+    ).map_err(|err| format!("invalid {}: {}", MANIFEST_FILE, err))?;
+    validate_name(&manifest.name)?;
+
+    Ok(Package { manifest, entries, signature })
+  }
+
+  /// The bytes covered by the signature: every entry except `SIGNATURE`,
+  /// in name order, each framed by its name and length.
+  pub fn signing_message(&self) -> Vec<u8> {
+    signing_message(&self.entries)
+  }
+
+  /// Checks the signature against the trusted keys. Fails if the package
+  /// is signed but the signature does not match its contents.
+  pub fn verify(&self, trusted: &[TrustedKey]) -> Result<Verification, String> {
+    let signature = match &self.signature {
+      Some(signature) => signature,
+      None => return Ok(Verification::Unsigned),
+    };
+
+    let public_key: [u8; 32] = decode_hex(&signature.public_key)?;
+    let key = VerifyingKey::from_bytes(&public_key).map_err(|err| err.to_string())?;
+    let bytes: [u8; 64] = decode_hex(&signature.signature)?;
+    key.verify(&self.signing_message(), &Signature::from_bytes(&bytes))
+      .map_err(|_| format!("package `{}` has an invalid signature", self.manifest.name))?;
+
+    Ok(trusted.iter()
+      .find(|trusted| trusted.public_key.eq_ignore_ascii_case(&signature.public_key))
+      .map(|trusted| Verification::Trusted(trusted.name.clone()))
+      .unwrap_or_else(|| Verification::Untrusted(signature.public_key.clone())))
+  }
+}
+
+fn signing_message(entries: &BTreeMap<String, Vec<u8>>) -> Vec<u8> {
+  let mut message = SIGNING_CONTEXT.to_vec();
+  for (name, contents) in entries {
+    message.extend_from_slice(format!("{}\n{}\n", name, contents.len()).as_bytes());
+    message.extend_from_slice(contents);
+  }
+  message
+}
+
+fn decode_hex<const N: usize>(value: &str) -> Result<[u8; N], String> {
+  let bytes = hex::decode(value).map_err(|err| err.to_string())?;
+  bytes.try_into().map_err(|_| format!("expected {} hex encoded bytes", N))
+}
+
+pub(crate) fn validate_name(name: &str) -> Result<(), String> {
+  let valid = !name.is_empty()
+    && !name.starts_with('.')
+    && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+  match valid {
+    true => Ok(()),
+    false => Err(format!("invalid extension name `{}`", name)),
+  }
+}
+
+/// Builds a package archive from `entries`, signing it when a key is given.
+pub fn write_package(path: &Path, entries: &BTreeMap<String, Vec<u8>>, signing_key: Option<&SigningKey>) -> Result<(), String> {
+  let file = File::create(path).map_err(|err| err.to_string())?;
+  let mut archive = ZipWriter::new(file);
+  let options = SimpleFileOptions::default();
+
+  for (name, contents) in entries {
+    archive.start_file(name.as_str(), options).map_err(|err| err.to_string())?;
+    archive.write_all(contents).map_err(|err| err.to_string())?;
+  }
+
+  if let Some(signing_key) = signing_key {
+    let signature = PackageSignature {
+      public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+      signature: hex::encode(signing_key.sign(&signing_message(entries)).to_bytes()),
+    };
+    archive.start_file(SIGNATURE_FILE, options).map_err(|err| err.to_string())?;
+    archive.write_all(&serde_json::to_vec(&signature).unwrap()).map_err(|err| err.to_string())?;
+  }
+
+  archive.finish().map_err(|err| err.to_string())?;
+  Ok(())
+}
+
+/// Verifies and unpacks a package into `<extensions_dir>/<name>`, replacing
+/// any previous install of the same extension.
+pub fn install(path: &Path, extensions_dir: &Path, trusted: &[TrustedKey], allow_unsigned: bool) -> Result<InstalledExtension, String> {
+  let package = Package::read(path)?;
+  let signed_by = match package.verify(trusted)? {
+    Verification::Trusted(signed_by) => Some(signed_by),
+    Verification::Unsigned if allow_unsigned => None,
+    Verification::Unsigned => return Err(format!("package `{}` is not signed", package.manifest.name)),
+    Verification::Untrusted(key) => {
+      return Err(format!("package `{}` is signed by an untrusted key {}", package.manifest.name, key));
+    },
+  };
+
+  let installed = InstalledExtension {
+    name: package.manifest.name.clone(),
+    version: package.manifest.version.clone(),
+    description: package.manifest.description.clone(),
+    signed_by,
+  };
+
+  let staging = extensions_dir.join(format!(".{}.installing", installed.name));
+  let _ = fs::remove_dir_all(&staging);
+  for (name, contents) in &package.entries {
+    let target = staging.join(name);
+    fs::create_dir_all(target.parent().unwrap()).map_err(|err| err.to_string())?;
+    fs::write(&target, contents).map_err(|err| err.to_string())?;
+  }
+  fs::write(staging.join(INSTALL_FILE), serde_json::to_vec(&installed).unwrap()).map_err(|err| err.to_string())?;
+
+  #[cfg(unix)]
+  if let Some(command) = package.manifest.command.as_deref().filter(|command| command.contains('/')) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(staging.join(command), fs::Permissions::from_mode(0o755));
+  }
+
+  let target = extensions_dir.join(&installed.name);
+  let _ = fs::remove_dir_all(&target);
+  fs::rename(&staging, &target).map_err(|err| err.to_string())?;
+  Ok(installed)
+}
+
+pub fn uninstall(name: &str, extensions_dir: &Path) -> Result<(), String> {
+  validate_name(name)?;
+  let target = extensions_dir.join(name);
+  if !target.join(INSTALL_FILE).is_file() {
+    return Err(format!("extension `{}` is not installed", name));
+  }
+  fs::remove_dir_all(&target).map_err(|err| err.to_string())
+}
+
+pub fn list_installed(extensions_dir: &Path) -> Vec<InstalledExtension> {
+  let mut installed: Vec<InstalledExtension> = fs::read_dir(extensions_dir).into_iter()
+    .flatten()
+    .filter_map(|entry| entry.ok())
+    .filter_map(|entry| fs::read(entry.path().join(INSTALL_FILE)).ok())
+    .filter_map(|contents| serde_json::from_slice(&contents).ok())
+    .collect();
+  installed.sort_by(|a, b| a.name.cmp(&b.name));
+  installed
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shortcuts_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn entries(name: &str) -> BTreeMap<String, Vec<u8>> {
+    let mut entries = BTreeMap::new();
+    let manifest = format!(r#"{{"name":"{}","version":"1.0.0","command":"./main.sh"}}"#, name);
+    entries.insert(MANIFEST_FILE.to_string(), manifest.into_bytes());
+    entries.insert("main.sh".to_string(), b"#!/bin/sh\n".to_vec());
+    entries.insert("assets/icon.svg".to_string(), b"<svg/>".to_vec());
+    entries
+  }
+
+  fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[7; 32])
+  }
+
+  fn trusted() -> Vec<TrustedKey> {
+    vec![TrustedKey {
+      name: "team".to_string(),
+      public_key: hex::encode(signing_key().verifying_key().to_bytes()),
+    }]
+  }
+
+  #[test]
+  fn test_install_signed() {
+    let dir = test_dir("test_install_signed");
+    let path = dir.join("echo.scext");
+    write_package(&path, &entries("echo"), Some(&signing_key())).unwrap();
+
+    let installed = install(&path, &dir.join("extensions"), &trusted(), false).unwrap();
+    assert_eq!(installed.signed_by, Some("team".to_string()));
+    assert_eq!(fs::read(dir.join("extensions/echo/assets/icon.svg")).unwrap(), b"<svg/>");
+    assert_eq!(crate::extensions::external::ProcessManifest::read(&dir.join("extensions/echo")).unwrap().name, "echo");
+    assert!(!dir.join("extensions/echo").join(SIGNATURE_FILE).exists());
+    assert_eq!(list_installed(&dir.join("extensions")), vec![installed]);
+
+    uninstall("echo", &dir.join("extensions")).unwrap();
+    assert!(list_installed(&dir.join("extensions")).is_empty());
+    assert!(uninstall("echo", &dir.join("extensions")).is_err());
+  }
+
+  #[test]
+  fn test_refuse_unsigned() {
+    let dir = test_dir("test_refuse_unsigned");
+    let path = dir.join("echo.scext");
+    write_package(&path, &entries("echo"), None).unwrap();
+
+    assert!(install(&path, &dir.join("extensions"), &trusted(), false).is_err());
+    let installed = install(&path, &dir.join("extensions"), &trusted(), true).unwrap();
+    assert_eq!(installed.signed_by, None);
+  }
+
+  #[test]
+  fn test_refuse_untrusted_key() {
+    let dir = test_dir("test_refuse_untrusted_key");
+    let path = dir.join("echo.scext");
+    write_package(&path, &entries("echo"), Some(&SigningKey::from_bytes(&[9; 32]))).unwrap();
+
+    let err = install(&path, &dir.join("extensions"), &trusted(), false).unwrap_err();
+    assert!(err.contains("untrusted"));
+    let err = install(&path, &dir.join("extensions"), &trusted(), true).unwrap_err();
+    assert!(err.contains("untrusted"));
+    assert!(!dir.join("extensions").join("echo").exists());
+  }
+
+  #[test]
+  fn test_refuse_tampered() {
+    let dir = test_dir("test_refuse_tampered");
+    let path = dir.join("echo.scext");
+    write_package(&path, &entries("echo"), Some(&signing_key())).unwrap();
+
+    let mut package = Package::read(&path).unwrap();
+    package.entries.insert("main.sh".to_string(), b"rm -rf ~\n".to_vec());
+    let err = package.verify(&trusted()).unwrap_err();
+    assert!(err.contains("invalid signature"));
+
+    let mut tampered = package.entries.clone();
+    tampered.insert(SIGNATURE_FILE.to_string(), serde_json::to_vec(package.signature.as_ref().unwrap()).unwrap());
+    write_package(&path, &tampered, None).unwrap();
+    assert!(install(&path, &dir.join("extensions"), &trusted(), true).is_err());
+  }
+
+  #[test]
+  fn test_refuse_invalid_name() {
+    let dir = test_dir("test_refuse_invalid_name");
+    let path = dir.join("bad.scext");
+    write_package(&path, &entries("../escape"), Some(&signing_key())).unwrap();
+    assert!(install(&path, &dir.join("extensions"), &trusted(), false).is_err());
+  }
+}
@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+pub use shortcuts_types::capability::Capability;
+
+/// Denials kept for [`Permissions::denials`].
+const MAX_DENIALS: usize = 50;
+
+/// Whether holding `granted` allows what `requested` asks for. Paths are
+/// compared once symlinks are resolved, and denied when that fails.
+fn covers(granted: &Capability, requested: &Capability) -> bool {
+  match (granted, requested) {
+    (Capability::Filesystem(granted), Capability::Filesystem(path)) => {
+      if path.components().any(|c| c == Component::ParentDir) {
+        return false;
+      }
+      match (granted.canonicalize(), resolve(path)) {
+        (Ok(granted), Some(path)) => path.starts_with(granted),
+        _ => false,
+      }
+    },
+    _ => granted == requested,
+  }
+}
+
+/// `path` with every symlink resolved, so it can't point out of a granted
+/// directory. A file that doesn't exist yet resolves through its directory.
+pub(crate) fn resolve(path: &Path) -> Option<PathBuf> {
+  if let Ok(resolved) = path.canonicalize() {
+    return Some(resolved);
+  }
+  let name = path.file_name()?;
+  path.parent()?.canonicalize().ok().map(|parent| parent.join(name))
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PermissionDenied {
+  pub extensions: Vec<String>,
+  pub capability: Capability,
+}
+
+impl fmt::Display for PermissionDenied {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "permission denied: {:?} has not been granted `{}`", self.extensions, self.capability)
+  }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+/// What provides an extension.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Owner {
+  Builtin,
+  /// The extension process with this `ProcessManifest::owner`.
+  Process(String),
+}
+
+/// Runtime view of the capabilities granted to enabled extensions and of
+/// which process provides each one. Shared with the host services so checks
+/// don't need the extension manager lock.
+#[derive(Debug, Default)]
+pub struct Permissions {
+  grants: RwLock<HashMap<String, HashSet<Capability>>>,
+  owners: RwLock<HashMap<String, Owner>>,
+  denials: Mutex<VecDeque<PermissionDenied>>,
+}
+
+impl Permissions {
+  pub fn new() -> Self {
+    Permissions::default()
+  }
+
+  pub fn set(&self, extension: &str, granted: Vec<Capability>) {
+    self.grants.write().unwrap().insert(extension.to_string(), granted.into_iter().collect());
+  }
+
+  pub fn revoke(&self, extension: &str) {
+    self.grants.write().unwrap().remove(extension);
+  }
+
+  pub fn is_granted(&self, extension: &str, capability: &Capability) -> bool {
+    self.grants.read().unwrap().get(extension)
+      .is_some_and(|granted| granted.iter().any(|g| covers(g, capability)))
+  }
+
+  /// Marks a built-in extension's ID, which no process may claim.
+  pub fn reserve_builtin(&self, extension: &str) {
+    self.owners.write().unwrap().insert(extension.to_string(), Owner::Builtin);
+  }
+
+  /// Makes `process` the only provider of `extensions`, replacing what it
+  /// claimed before. Fails without claiming anything when one of them is
+  /// built in or provided by another process.
+  pub fn claim(&self, process: &str, extensions: &[String]) -> Result<(), String> {
+    let mut owners = self.owners.write().unwrap();
+    for extension in extensions {
+      match owners.get(extension) {
+        Some(Owner::Builtin) => return Err(format!("`{}` is a built-in extension", extension)),
+        Some(Owner::Process(owner)) if owner != process => {
+          return Err(format!("`{}` is provided by the extension process {}", extension, owner));
+        },
+        _ => {},
+      }
+    }
+    let owner = Owner::Process(process.to_string());
+    owners.retain(|_, claimed| *claimed != owner);
+    extensions.iter().for_each(|extension| {
+      owners.insert(extension.clone(), owner.clone());
+    });
+    Ok(())
+  }
+
+  /// Forgets the extensions `process` claimed, once it is gone.
+  pub fn release(&self, process: &str) {
+    let owner = Owner::Process(process.to_string());
+    self.owners.write().unwrap().retain(|_, claimed| *claimed != owner);
+  }
+
+  /// The extensions `process` claimed, sorted.
+  pub fn claimed_by(&self, process: &str) -> Vec<String> {
+    let owner = Owner::Process(process.to_string());
+    let mut extensions: Vec<String> = self.owners.read().unwrap().iter()
+      .filter(|(_, claimed)| **claimed == owner)
+      .map(|(extension, _)| extension.clone())
+      .collect();
+    extensions.sort();
+    extensions
+  }
+
+  /// Succeeds when an extension `process` claimed holds `capability`.
+  /// Denials are logged.
+  pub fn check(&self, process: &str, capability: &Capability) -> Result<(), PermissionDenied> {
+    let extensions = self.claimed_by(process);
+    if extensions.iter().any(|extension| self.is_granted(extension, capability)) {
+      return Ok(());
+    }
+    Err(self.deny(extensions, capability))
+  }
+
+  /// Succeeds when `extension` holds `capability`. Built-in extensions run
+  /// in-process and check their own grants through this before touching
+  /// what a capability guards. Denials are logged.
+  pub fn check_extension(&self, extension: &str, capability: &Capability) -> Result<(), PermissionDenied> {
+    if self.is_granted(extension, capability) {
+      return Ok(());
+    }
+    Err(self.deny(vec![extension.to_string()], capability))
+  }
+
+  /// The latest denials, oldest first.
+  pub fn denials(&self) -> Vec<PermissionDenied> {
+    self.denials.lock().unwrap().iter().cloned().collect()
+  }
+
+  fn deny(&self, extensions: Vec<String>, capability: &Capability) -> PermissionDenied {
+    let denied = PermissionDenied {
+      extensions,
+      capability: capability.clone(),
+    };
+    println!("{}", denied);
+    let mut denials = self.denials.lock().unwrap();
+    if denials.len() == MAX_DENIALS {
+      denials.pop_front();
+    }
+    denials.push_back(denied.clone());
+    denied
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn covers_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("notes")).unwrap();
+    std::fs::create_dir_all(dir.join("notes-other")).unwrap();
+    std::fs::write(dir.join("notes/a.txt"), "").unwrap();
+    std::fs::write(dir.join("secret"), "").unwrap();
+    dir
+  }
+
+  #[test]
+  fn test_filesystem_covers() {
+    let dir = covers_dir("shortcuts_test_filesystem_covers");
+    let fs = |path: &str| Capability::Filesystem(dir.join(path));
+    let granted = fs("notes");
+    assert!(covers(&granted, &fs("notes/a.txt")));
+    assert!(covers(&granted, &fs("notes/new.txt")), "files yet to be written resolve through their directory");
+    assert!(!covers(&granted, &fs("notes/../secret")));
+    assert!(!covers(&granted, &fs("notes/missing/new.txt")));
+    assert!(!covers(&granted, &fs("notes-other")));
+    assert!(!covers(&fs("missing"), &fs("missing/a.txt")));
+    assert!(!covers(&granted, &Capability::ShellExec));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_filesystem_covers_symlinks() {
+    let dir = covers_dir("shortcuts_test_filesystem_covers_symlinks");
+    std::os::unix::fs::symlink(dir.join("secret"), dir.join("notes/escape")).unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("notes/parent")).unwrap();
+    std::os::unix::fs::symlink(dir.join("notes/a.txt"), dir.join("notes/inside")).unwrap();
+
+    let fs = |path: &str| Capability::Filesystem(dir.join(path));
+    let granted = fs("notes");
+    assert!(!covers(&granted, &fs("notes/escape")), "the symlink points out of the directory");
+    assert!(!covers(&granted, &fs("notes/parent/secret")));
+    assert!(!covers(&granted, &fs("notes/parent/new.txt")));
+    assert!(covers(&granted, &fs("notes/inside")));
+  }
+
+  #[test]
+  fn test_check() {
+    let permissions = Permissions::new();
+    permissions.claim("process", &["a".to_string(), "b".to_string()]).unwrap();
+    assert!(permissions.check("process", &Capability::ShellExec).is_err());
+
+    permissions.set("b", vec![Capability::ShellExec]);
+    assert!(permissions.check("process", &Capability::ShellExec).is_ok());
+    assert!(permissions.check("process", &Capability::Network).is_err());
+    assert!(permissions.check("other", &Capability::ShellExec).is_err());
+
+    permissions.revoke("b");
+    assert!(permissions.check("process", &Capability::ShellExec).is_err());
+    assert_eq!(permissions.denials().len(), 4);
+  }
+
+  #[test]
+  fn test_check_extension() {
+    let permissions = Permissions::new();
+    permissions.reserve_builtin("clipboard");
+    let denied = permissions.check_extension("clipboard", &Capability::ClipboardRead).unwrap_err();
+    assert_eq!(denied.to_string(), r#"permission denied: ["clipboard"] has not been granted `clipboard:read`"#);
+    assert_eq!(permissions.denials(), vec![denied]);
+
+    permissions.set("clipboard", vec![Capability::ClipboardRead]);
+    assert!(permissions.check_extension("clipboard", &Capability::ClipboardRead).is_ok());
+    assert!(permissions.check_extension("clipboard", &Capability::ClipboardWrite).is_err());
+    assert_eq!(permissions.denials().len(), 2);
+  }
+
+  #[test]
+  fn test_claim() {
+    let permissions = Permissions::new();
+    permissions.reserve_builtin("clipboard");
+    permissions.set("clipboard", vec![Capability::ClipboardRead]);
+    permissions.set("shell", vec![Capability::ShellExec]);
+    permissions.claim("trusted", &["shell".to_string()]).unwrap();
+
+    let impostor = "impostor";
+    assert!(permissions.claim(impostor, &["clipboard".to_string()]).is_err());
+    assert!(permissions.claim(impostor, &["own".to_string(), "shell".to_string()]).is_err());
+    assert!(permissions.claimed_by(impostor).is_empty(), "nothing is claimed when one ID is taken");
+    assert!(permissions.check(impostor, &Capability::ClipboardRead).is_err());
+    assert!(permissions.check(impostor, &Capability::ShellExec).is_err());
+
+    permissions.claim("trusted", &["shell".to_string(), "more".to_string()]).unwrap();
+    assert_eq!(permissions.claimed_by("trusted"), vec!["more".to_string(), "shell".to_string()]);
+    permissions.release("trusted");
+    assert!(permissions.check("trusted", &Capability::ShellExec).is_err());
+    permissions.claim(impostor, &["shell".to_string()]).unwrap();
+  }
+}
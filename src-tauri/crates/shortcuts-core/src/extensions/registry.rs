@@ -0,0 +1,290 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::modules::config::Config;
+
+use super::package::InstalledExtension;
+
+// A registry index is a JSON file listing extensions and where to get each
+// of their versions:
+//
+//   {"extensions":[{"name":"echo","description":"...","versions":[
+//     {"version":"1.2.0","package":"echo-1.2.0.scext","sha256":"..."}]}]}
+//
+// Package locations are URLs or paths relative to the index, so a plain
+// directory works as a fully offline registry.
+
+pub const INDEX_FILE: &str = "index.json";
+const REGISTRY_CONFIG_PATH: &str = ".shortcuts/registry.json";
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct IndexVersion {
+  pub version: String,
+  pub package: String,
+  pub sha256: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct IndexEntry {
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  pub versions: Vec<IndexVersion>,
+}
+
+impl IndexEntry {
+  /// The highest version by semver precedence; unparsable versions are ignored.
+  pub fn latest(&self) -> Option<&IndexVersion> {
+    self.versions.iter()
+      .filter_map(|entry| Version::parse(&entry.version).ok().map(|version| (version, entry)))
+      .max_by(|(a, _), (b, _)| a.cmp(b))
+      .map(|(_, entry)| entry)
+  }
+
+  pub fn version(&self, version: &str) -> Option<&IndexVersion> {
+    self.versions.iter().find(|entry| entry.version == version)
+  }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum IndexLocation {
+  Path(PathBuf),
+  Url(String),
+}
+
+impl IndexLocation {
+  /// Accepts an `http(s)://` URL, a `file://` URL, a path to an index file
+  /// or a directory containing `index.json`.
+  pub fn parse(location: &str) -> Self {
+    if location.starts_with("http://") || location.starts_with("https://") {
+      return IndexLocation::Url(location.to_string());
+    }
+    let path = PathBuf::from(location.strip_prefix("file://").unwrap_or(location));
+    match path.is_dir() {
+      true => IndexLocation::Path(path.join(INDEX_FILE)),
+      false => IndexLocation::Path(path),
+    }
+  }
+
+  fn resolve(&self, package: &str) -> Result<IndexLocation, String> {
+    if package.starts_with("http://") || package.starts_with("https://") || package.starts_with("file://") {
+      return Ok(IndexLocation::parse(package));
+    }
+    match self {
+      IndexLocation::Path(path) => Ok(IndexLocation::Path(path.parent().unwrap_or(Path::new("")).join(package))),
+      IndexLocation::Url(index) => Url::parse(index).and_then(|index| index.join(package))
+        .map(|url| IndexLocation::Url(url.to_string()))
+        .map_err(|err| format!("cannot resolve {} against {}: {}", package, index, err)),
+    }
+  }
+
+  fn read(&self) -> Result<Vec<u8>, String> {
+    match self {
+      IndexLocation::Path(path) => fs::read(path).map_err(|err| format!("cannot read {:?}: {}", path, err)),
+      IndexLocation::Url(url) => {
+        let mut bytes = vec![];
+        ureq::get(url).call().map_err(|err| format!("cannot fetch {}: {}", url, err))?
+          .into_reader().read_to_end(&mut bytes).map_err(|err| err.to_string())?;
+        Ok(bytes)
+      },
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct RegistryConfig {
+  pub index: String,
+}
+
+impl Default for RegistryConfig {
+  fn default() -> Self {
+    RegistryConfig {
+      index: dirs::home_dir().unwrap().join(".shortcuts/registry").to_string_lossy().to_string(),
+    }
+  }
+}
+
+impl RegistryConfig {
+  pub fn load() -> Self {
+    Config::new(REGISTRY_CONFIG_PATH.to_string()).read_config().ok()
+      .and_then(|contents| serde_json::from_str(&contents).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self) -> Result<(), String> {
+    Config::new(REGISTRY_CONFIG_PATH.to_string())
+      .write_config(&serde_json::to_string(self).unwrap())
+      .map_err(|err| err.to_string())
+  }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RegistryIndex {
+  pub location: IndexLocation,
+  pub extensions: Vec<IndexEntry>,
+}
+
+#[derive(Deserialize)]
+struct IndexFile {
+  #[serde(default)]
+  extensions: Vec<IndexEntry>,
+}
+
+/// An index entry as shown to the user, next to the installed version.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct AvailableExtension {
+  pub name: String,
+  pub description: String,
+  pub versions: Vec<String>,
+  pub latest: Option<String>,
+  pub installed: Option<String>,
+  pub update_available: bool,
+}
+
+impl RegistryIndex {
+  pub fn load(location: &str) -> Result<Self, String> {
+    let location = IndexLocation::parse(location);
+    let index: IndexFile = serde_json::from_slice(&location.read()?)
+      .map_err(|err| format!("invalid registry index: {}", err))?;
+    Ok(RegistryIndex { location, extensions: index.extensions })
+  }
+
+  pub fn get(&self, name: &str) -> Option<&IndexEntry> {
+    self.extensions.iter().find(|entry| entry.name == name)
+  }
+
+  pub fn available(&self, installed: &[InstalledExtension]) -> Vec<AvailableExtension> {
+    self.extensions.iter().map(|entry| {
+      let latest = entry.latest().map(|latest| latest.version.clone());
+      let installed = installed.iter().find(|ext| ext.name == entry.name).map(|ext| ext.version.clone());
+      AvailableExtension {
+        name: entry.name.clone(),
+        description: entry.description.clone(),
+        versions: entry.versions.iter().map(|version| version.version.clone()).collect(),
+        update_available: is_newer(latest.as_deref(), installed.as_deref()),
+        latest,
+        installed,
+      }
+    }).collect()
+  }
+
+  /// Fetches `version` of an extension, or its latest version, into `dir`.
+  pub fn download(&self, name: &str, version: Option<&str>, dir: &Path) -> Result<PathBuf, String> {
+    let entry = self.get(name).ok_or_else(|| format!("`{}` is not in the registry index", name))?;
+    let version = match version {
+      Some(version) => entry.version(version),
+      None => entry.latest(),
+    }.ok_or_else(|| format!("no matching version of `{}` in the registry index", name))?;
+    self.fetch(version, dir)
+  }
+
+  /// Copies or downloads a package into `dir` and checks it against the
+  /// index checksum before returning its path.
+  pub fn fetch(&self, version: &IndexVersion, dir: &Path) -> Result<PathBuf, String> {
+    let bytes = self.location.resolve(&version.package)?.read()?;
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    if !checksum.eq_ignore_ascii_case(&version.sha256) {
+      return Err(format!("checksum mismatch for {}: expected {}, got {}", version.package, version.sha256, checksum));
+    }
+
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    let file_name = Path::new(&version.package).file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_else(|| "package".to_string());
+    let path = dir.join(file_name);
+    fs::write(&path, bytes).map_err(|err| err.to_string())?;
+    Ok(path)
+  }
+}
+
+fn is_newer(latest: Option<&str>, installed: Option<&str>) -> bool {
+  match (latest.and_then(|v| Version::parse(v).ok()), installed) {
+    (Some(latest), Some(installed)) => Version::parse(installed).map(|installed| latest > installed).unwrap_or(true),
+    _ => false,
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("shortcuts_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn write_index(dir: &Path, package: &[u8]) {
+    fs::write(dir.join("echo-1.1.0.scext"), package).unwrap();
+    let checksum = hex::encode(Sha256::digest(package));
+    fs::write(dir.join(INDEX_FILE), format!(r#"{{"extensions":[{{"name":"echo","description":"Echo","versions":[
+      {{"version":"1.0.0","package":"missing.scext","sha256":"00"}},
+      {{"version":"1.1.0","package":"echo-1.1.0.scext","sha256":"{}"}},
+      {{"version":"1.0.5","package":"missing.scext","sha256":"00"}}]}}]}}"#, checksum)).unwrap();
+  }
+
+  fn installed(version: &str) -> InstalledExtension {
+    InstalledExtension {
+      name: "echo".to_string(),
+      version: version.to_string(),
+      description: "".to_string(),
+      signed_by: None,
+    }
+  }
+
+  #[test]
+  fn test_parse_location() {
+    let dir = test_dir("test_parse_location");
+    assert_eq!(IndexLocation::parse(dir.to_str().unwrap()), IndexLocation::Path(dir.join(INDEX_FILE)));
+    assert_eq!(IndexLocation::parse("https://example.com/index.json"), IndexLocation::Url("https://example.com/index.json".to_string()));
+    assert_eq!(
+      IndexLocation::Url("https://example.com/x/index.json".to_string()).resolve("echo.scext"),
+      Ok(IndexLocation::Url("https://example.com/x/echo.scext".to_string())),
+    );
+    for index in ["https://example.com", "https://example.com?channel=beta"] {
+      assert_eq!(
+        IndexLocation::Url(index.to_string()).resolve("echo.scext"),
+        Ok(IndexLocation::Url("https://example.com/echo.scext".to_string())),
+      );
+    }
+    assert!(IndexLocation::Url("https://".to_string()).resolve("echo.scext").is_err());
+  }
+
+  #[test]
+  fn test_available() {
+    let dir = test_dir("test_available");
+    write_index(&dir, b"package");
+    let index = RegistryIndex::load(dir.to_str().unwrap()).unwrap();
+    assert_eq!(index.get("echo").unwrap().latest().unwrap().version, "1.1.0");
+
+    let available = index.available(&[]);
+    assert_eq!(available[0].latest, Some("1.1.0".to_string()));
+    assert_eq!(available[0].installed, None);
+    assert!(!available[0].update_available);
+
+    assert!(index.available(&[installed("1.0.5")])[0].update_available);
+    assert!(!index.available(&[installed("1.1.0")])[0].update_available);
+  }
+
+  #[test]
+  fn test_fetch_verifies_checksum() {
+    let dir = test_dir("test_fetch_verifies_checksum");
+    write_index(&dir, b"package");
+    let index = RegistryIndex::load(dir.join(INDEX_FILE).to_str().unwrap()).unwrap();
+    let entry = index.get("echo").unwrap();
+
+    let path = index.fetch(entry.latest().unwrap(), &dir.join("downloads")).unwrap();
+    assert_eq!(fs::read(path).unwrap(), b"package");
+
+    fs::write(dir.join("echo-1.1.0.scext"), b"tampered").unwrap();
+    assert!(index.fetch(entry.latest().unwrap(), &dir.join("downloads")).unwrap_err().contains("checksum mismatch"));
+    assert!(index.fetch(entry.version("1.0.0").unwrap(), &dir.join("downloads")).is_err());
+  }
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use super::dispatcher::DispatchPolicy;
+use super::metadata::{Icon, Metadata};
+use super::permissions::Capability;
+use super::settings::SettingSchema;
+
+// Newline-delimited JSON-RPC 2.0 spoken over the stdin/stdout of an
+// out-of-process extension. One message per line in both directions.
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Child -> host notification listing the extensions and actions it provides.
+pub const METHOD_ANNOUNCE: &str = "extensions/announce";
+/// Host -> child request sent when a bound shortcut fires.
+pub const METHOD_INVOKE: &str = "extension/invoke";
+/// Host -> child notification sent once the announcement was accepted.
+pub const METHOD_LOAD: &str = "extension/load";
+/// Host -> child notification sent before the process is stopped for a
+/// reload. The process should clean up and exit.
+pub const METHOD_UNLOAD: &str = "extension/unload";
+/// Child -> host requests for host services are namespaced under this prefix.
+pub const HOST_METHOD_PREFIX: &str = "host/";
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Server-defined error: the caller lacks a capability the method requires.
+pub const PERMISSION_DENIED: i64 = -32001;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct RpcRequest {
+  pub jsonrpc: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<u64>,
+  pub method: String,
+  #[serde(default)]
+  pub params: Value,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct RpcResponse {
+  pub jsonrpc: String,
+  pub id: u64,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub result: Option<Value>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct RpcError {
+  pub code: i64,
+  pub message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum RpcMessage {
+  Request(RpcRequest),
+  Response(RpcResponse),
+}
+
+impl RpcRequest {
+  pub fn new(id: u64, method: &str, params: Value) -> Self {
+    RpcRequest {
+      jsonrpc: JSONRPC_VERSION.to_string(),
+      id: Some(id),
+      method: method.to_string(),
+      params,
+    }
+  }
+
+  pub fn notification(method: &str, params: Value) -> Self {
+    RpcRequest {
+      jsonrpc: JSONRPC_VERSION.to_string(),
+      id: None,
+      method: method.to_string(),
+      params,
+    }
+  }
+
+  pub fn is_notification(&self) -> bool {
+    self.id.is_none()
+  }
+}
+
+impl RpcResponse {
+  pub fn ok(id: u64, result: Value) -> Self {
+    RpcResponse {
+      jsonrpc: JSONRPC_VERSION.to_string(),
+      id,
+      result: Some(result),
+      error: None,
+    }
+  }
+
+  pub fn err(id: u64, error: RpcError) -> Self {
+    RpcResponse {
+      jsonrpc: JSONRPC_VERSION.to_string(),
+      id,
+      result: None,
+      error: Some(error),
+    }
+  }
+
+  pub fn into_result(self) -> Result<Value, RpcError> {
+    match self.error {
+      Some(error) => Err(error),
+      None => Ok(self.result.unwrap_or(Value::Null)),
+    }
+  }
+}
+
+impl RpcError {
+  pub fn new(code: i64, message: &str) -> Self {
+    RpcError { code, message: message.to_string() }
+  }
+}
+
+impl fmt::Display for RpcError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} ({})", self.message, self.code)
+  }
+}
+
+impl std::error::Error for RpcError {}
+
+pub fn encode(message: &RpcMessage) -> String {
+  let mut line = serde_json::to_string(message).unwrap();
+  line.push('\n');
+  line
+}
+
+pub fn decode(line: &str) -> Result<RpcMessage, RpcError> {
+  serde_json::from_str(line.trim()).map_err(|err| RpcError::new(PARSE_ERROR, &err.to_string()))
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct AnnouncedAction {
+  /// Stable identifier; defaults to `name` for processes that don't send one.
+  #[serde(default)]
+  pub id: Option<String>,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub default_shortcut: Option<String>,
+  #[serde(default)]
+  pub icon: Option<Icon>,
+  #[serde(default)]
+  pub keywords: Vec<String>,
+  #[serde(default)]
+  pub policy: DispatchPolicy,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct AnnouncedExtension {
+  /// Stable identifier; defaults to `name` for processes that don't send one.
+  #[serde(default)]
+  pub id: Option<String>,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(flatten)]
+  pub metadata: Metadata,
+  #[serde(default)]
+  pub shortcuts: Vec<AnnouncedAction>,
+  #[serde(default)]
+  pub settings: Vec<SettingSchema>,
+  #[serde(default)]
+  pub capabilities: Vec<Capability>,
+}
+
+impl AnnouncedAction {
+  pub fn id(&self) -> &str {
+    self.id.as_deref().unwrap_or(&self.name)
+  }
+}
+
+impl AnnouncedExtension {
+  pub fn id(&self) -> &str {
+    self.id.as_deref().unwrap_or(&self.name)
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct AnnounceParams {
+  pub extensions: Vec<AnnouncedExtension>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct InvokeParams {
+  pub extension: String,
+  pub action: String,
+  #[serde(default)]
+  pub settings: Value,
+}
+
+/// Identifies the process behind a `host/*` request. The capabilities of
+/// the extensions it claimed are the ones that apply to the call.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Caller {
+  /// See `ProcessManifest::owner`.
+  pub process: String,
+}
+
+/// Services the host exposes to extension processes through `host/*` requests.
+pub trait HostServices: Send + Sync {
+  fn call(&self, caller: &Caller, method: &str, params: Value) -> Result<Value, RpcError>;
+
+  /// Makes `process` the provider of the extensions it announced. Fails
+  /// when one is built in or provided by another process.
+  fn claim(&self, process: &str, extensions: &[String]) -> Result<(), String>;
+
+  /// Forgets what `process` claimed.
+  fn release(&self, process: &str);
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn test_decode_request() {
+    let message = decode(r#"{"jsonrpc":"2.0","id":3,"method":"host/log","params":{"message":"hi"}}"#).unwrap();
+    assert_eq!(message, RpcMessage::Request(RpcRequest::new(3, "host/log", json!({ "message": "hi" }))));
+  }
+
+  #[test]
+  fn test_decode_notification() {
+    let message = decode(r#"{"jsonrpc":"2.0","method":"extensions/announce","params":{"extensions":[]}}"#).unwrap();
+    match message {
+      RpcMessage::Request(request) => assert!(request.is_notification()),
+      _ => panic!("expected a notification"),
+    }
+  }
+
+  #[test]
+  fn test_decode_response() {
+    let message = decode(r#"{"jsonrpc":"2.0","id":7,"error":{"code":-32601,"message":"nope"}}"#).unwrap();
+    match message {
+      RpcMessage::Response(response) => {
+        assert_eq!(response.into_result(), Err(RpcError::new(METHOD_NOT_FOUND, "nope")));
+      },
+      _ => panic!("expected a response"),
+    }
+  }
+
+  #[test]
+  fn test_decode_invalid() {
+    assert_eq!(decode("not json").unwrap_err().code, PARSE_ERROR);
+  }
+
+  #[test]
+  fn test_encode_is_one_line() {
+    let line = encode(&RpcMessage::Response(RpcResponse::ok(1, json!({ "a": "b\nc" }))));
+    assert!(line.ends_with('\n'));
+    assert_eq!(line.matches('\n').count(), 1);
+  }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(tag = "type")]
+pub enum SettingKind {
+  #[serde(rename = "string")]
+  Text {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+  },
+  #[serde(rename = "number")]
+  Number {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+  },
+  #[serde(rename = "bool")]
+  Bool,
+  #[serde(rename = "enum")]
+  Choice {
+    options: Vec<String>,
+  },
+  /// A list of strings.
+  #[serde(rename = "list")]
+  List {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_items: Option<usize>,
+  },
+}
+
+/// Declares one user-configurable option of an extension. Serialized flat,
+/// e.g. `{"key":"history_size","type":"number","min":1,"default":200,...}`.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct SettingSchema {
+  pub key: String,
+  pub label: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(flatten)]
+  pub kind: SettingKind,
+  pub default: Value,
+}
+
+impl SettingSchema {
+  pub fn validate(&self, value: &Value) -> Result<(), String> {
+    let invalid = |expected: &str| Err(format!("`{}` expects {}, got {}", self.key, expected, value));
+    match &self.kind {
+      SettingKind::Text { max_length } => match value.as_str() {
+        Some(text) => match max_length {
+          Some(max_length) if text.chars().count() > *max_length => {
+            invalid(&format!("at most {} characters", max_length))
+          },
+          _ => Ok(()),
+        },
+        None => invalid("a string"),
+      },
+      SettingKind::Number { min, max } => match value.as_f64() {
+        Some(number) if min.is_some_and(|min| number < min) => invalid(&format!("a number >= {}", min.unwrap())),
+        Some(number) if max.is_some_and(|max| number > max) => invalid(&format!("a number <= {}", max.unwrap())),
+        Some(_) => Ok(()),
+        None => invalid("a number"),
+      },
+      SettingKind::Bool => match value.is_boolean() {
+        true => Ok(()),
+        false => invalid("a bool"),
+      },
+      SettingKind::Choice { options } => match value.as_str() {
+        Some(option) if options.iter().any(|o| o == option) => Ok(()),
+        _ => invalid(&format!("one of {:?}", options)),
+      },
+      SettingKind::List { max_items } => match value.as_array() {
+        Some(items) if !items.iter().all(Value::is_string) => invalid("a list of strings"),
+        Some(items) if max_items.is_some_and(|max_items| items.len() > max_items) => {
+          invalid(&format!("at most {} items", max_items.unwrap()))
+        },
+        Some(_) => Ok(()),
+        None => invalid("a list of strings"),
+      },
+    }
+  }
+}
+
+/// Checks user supplied values against a schema. Unknown keys are rejected.
+pub fn validate(schema: &[SettingSchema], values: &Map<String, Value>) -> Result<(), String> {
+  for (key, value) in values {
+    match schema.iter().find(|setting| &setting.key == key) {
+      Some(setting) => setting.validate(value)?,
+      None => return Err(format!("unknown setting `{}`", key)),
+    }
+  }
+  Ok(())
+}
+
+/// The effective settings of an extension: its defaults overlaid with the
+/// values the user stored. Handlers read it from their invocation context.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Settings {
+  values: HashMap<String, Value>,
+}
+
+impl Settings {
+  pub fn resolve(schema: &[SettingSchema], stored: &HashMap<String, Value>) -> Self {
+    let values = schema.iter().map(|setting| {
+      let value = match stored.get(&setting.key) {
+        Some(value) if setting.validate(value).is_ok() => value.clone(),
+        _ => setting.default.clone(),
+      };
+      (setting.key.clone(), value)
+    }).collect();
+    Settings { values }
+  }
+
+  pub fn get(&self, key: &str) -> Option<&Value> {
+    self.values.get(key)
+  }
+
+  pub fn get_str(&self, key: &str) -> Option<&str> {
+    self.get(key).and_then(Value::as_str)
+  }
+
+  pub fn get_f64(&self, key: &str) -> Option<f64> {
+    self.get(key).and_then(Value::as_f64)
+  }
+
+  pub fn get_bool(&self, key: &str) -> Option<bool> {
+    self.get(key).and_then(Value::as_bool)
+  }
+
+  pub fn get_list(&self, key: &str) -> Vec<String> {
+    self.get(key).and_then(Value::as_array)
+      .map(|items| items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect())
+      .unwrap_or_default()
+  }
+
+  pub fn to_json(&self) -> Value {
+    json!(self.values)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn schema() -> Vec<SettingSchema> {
+    vec![
+      SettingSchema {
+        key: "history_size".to_string(),
+        label: "History size".to_string(),
+        description: "".to_string(),
+        kind: SettingKind::Number { min: Some(1.0), max: Some(1000.0) },
+        default: json!(200),
+      },
+      SettingSchema {
+        key: "mode".to_string(),
+        label: "Mode".to_string(),
+        description: "".to_string(),
+        kind: SettingKind::Choice { options: vec!["text".to_string(), "rich".to_string()] },
+        default: json!("text"),
+      },
+      SettingSchema {
+        key: "excluded_apps".to_string(),
+        label: "Excluded apps".to_string(),
+        description: "".to_string(),
+        kind: SettingKind::List { max_items: Some(2) },
+        default: json!([]),
+      },
+    ]
+  }
+
+  #[test]
+  fn test_serialize_schema_is_flat() {
+    let value = serde_json::to_value(&schema()[0]).unwrap();
+    assert_eq!(value, json!({
+      "key": "history_size",
+      "label": "History size",
+      "description": "",
+      "type": "number",
+      "min": 1.0,
+      "max": 1000.0,
+      "default": 200,
+    }));
+    assert_eq!(serde_json::from_value::<SettingSchema>(value).unwrap(), schema()[0]);
+  }
+
+  #[test]
+  fn test_deserialize_schema() {
+    let setting: SettingSchema = serde_json::from_str(r#"{"key":"k","label":"K","type":"bool","default":true}"#).unwrap();
+    assert_eq!(setting.kind, SettingKind::Bool);
+  }
+
+  #[test]
+  fn test_validate() {
+    let schema = schema();
+    assert!(schema.iter().all(|setting| setting.validate(&setting.default).is_ok()));
+    assert!(schema[0].validate(&json!(0)).is_err());
+    assert!(schema[0].validate(&json!(1001)).is_err());
+    assert!(schema[0].validate(&json!("10")).is_err());
+    assert!(schema[1].validate(&json!("rich")).is_ok());
+    assert!(schema[1].validate(&json!("html")).is_err());
+    assert!(schema[2].validate(&json!(["a", "b"])).is_ok());
+    assert!(schema[2].validate(&json!(["a", "b", "c"])).is_err());
+    assert!(schema[2].validate(&json!([1])).is_err());
+  }
+
+  #[test]
+  fn test_validate_values() {
+    let mut values = Map::new();
+    values.insert("history_size".to_string(), json!(50));
+    assert!(validate(&schema(), &values).is_ok());
+    values.insert("unknown".to_string(), json!(50));
+    assert!(validate(&schema(), &values).is_err());
+  }
+
+  #[test]
+  fn test_resolve() {
+    let mut stored = HashMap::new();
+    stored.insert("history_size".to_string(), json!(50));
+    stored.insert("mode".to_string(), json!("invalid"));
+    let settings = Settings::resolve(&schema(), &stored);
+    assert_eq!(settings.get_f64("history_size"), Some(50.0));
+    assert_eq!(settings.get_str("mode"), Some("text"));
+    assert!(settings.get_list("excluded_apps").is_empty());
+    assert_eq!(settings.get("unknown"), None);
+  }
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::modules::config::Config;
+
+const USAGE_PATH: &str = ".shortcuts/usage.json";
+/// Days of buckets kept; older ones are dropped when recording.
+pub const RETENTION_DAYS: u64 = 365;
+/// Bindings not invoked for this many days are flagged as unused.
+pub const UNUSED_DAYS: u64 = 30;
+
+/// A UTC calendar day as `YYYY-MM-DD`, so days sort as strings.
+pub type Day = String;
+
+/// Invocations per day, by extension and action ID.
+type Buckets = BTreeMap<String, BTreeMap<String, BTreeMap<Day, u64>>>;
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+struct UsageFile {
+  /// The day counting started, so a fresh install doesn't flag every
+  /// binding as unused.
+  #[serde(default)]
+  since: Option<Day>,
+  #[serde(default)]
+  actions: Buckets,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct ActionUsage {
+  pub extension: String,
+  pub action: String,
+  pub total: u64,
+  pub last_used: Option<Day>,
+  pub daily: BTreeMap<Day, u64>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub struct UsageReport {
+  pub since: Option<Day>,
+  pub actions: Vec<ActionUsage>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+  Csv,
+  Json,
+}
+
+impl ExportFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ExportFormat::Csv => "csv",
+      ExportFormat::Json => "json",
+    }
+  }
+}
+
+/// Daily invocation counts per action, persisted in `.shortcuts/usage.json`
+/// next to `config.json`. Nothing leaves the machine.
+#[derive(Debug)]
+pub struct UsageStats {
+  usage: UsageFile,
+  config: Config,
+}
+
+impl UsageStats {
+  pub fn new(path: Option<String>) -> Self {
+    let path = path.unwrap_or_else(|| USAGE_PATH.to_string());
+    UsageStats { usage: UsageFile::default(), config: Config::new(path) }
+  }
+
+  pub fn load(&mut self) {
+    self.usage = self.config.read_config().ok()
+      .and_then(|contents| serde_json::from_str(&contents).ok())
+      .unwrap_or_default();
+  }
+
+  fn save(&self) {
+    if let Err(err) = self.config.write_config(&serde_json::to_string(&self.usage).unwrap()) {
+      println!("Failed to save usage stats: {}", err);
+    }
+  }
+
+  pub fn record(&mut self, extension: &str, action: &str, at: SystemTime) {
+    let today = day(at);
+    let oldest = day(at - std::time::Duration::from_secs(RETENTION_DAYS * 86_400));
+    self.usage.since.get_or_insert_with(|| today.clone());
+    *self.usage.actions.entry(extension.to_string()).or_default()
+      .entry(action.to_string()).or_default()
+      .entry(today).or_default() += 1;
+    for actions in self.usage.actions.values_mut() {
+      for days in actions.values_mut() {
+        days.retain(|day, _| *day >= oldest);
+      }
+    }
+    self.save();
+  }
+
+  pub fn report(&self) -> UsageReport {
+    let actions = self.usage.actions.iter().flat_map(|(extension, actions)| {
+      actions.iter().map(move |(action, days)| ActionUsage {
+        extension: extension.clone(),
+        action: action.clone(),
+        total: days.values().sum(),
+        last_used: days.keys().next_back().cloned(),
+        daily: days.clone(),
+      })
+    }).collect();
+    UsageReport { since: self.usage.since.clone(), actions }
+  }
+
+  /// Whether an action ran in none of the last `UNUSED_DAYS` days. Always
+  /// false until stats have been collected for that long.
+  pub fn is_unused(&self, extension: &str, action: &str, now: SystemTime) -> bool {
+    let cutoff = day(now - std::time::Duration::from_secs(UNUSED_DAYS * 86_400));
+    match &self.usage.since {
+      Some(since) if *since <= cutoff => {},
+      _ => return false,
+    }
+    let last_used = self.usage.actions.get(extension)
+      .and_then(|actions| actions.get(action))
+      .and_then(|days| days.keys().next_back());
+    last_used.map(|last_used| *last_used < cutoff).unwrap_or(true)
+  }
+
+  /// One row per action and day for CSV, the full report for JSON.
+  pub fn export(&self, format: ExportFormat) -> String {
+    match format {
+      ExportFormat::Csv => {
+        let mut csv = String::from("date,extension,action,count\n");
+        for (extension, actions) in &self.usage.actions {
+          for (action, days) in actions {
+            for (day, count) in days {
+              csv.push_str(&format!("{},{},{},{}\n", day, csv_field(extension), csv_field(action), count));
+            }
+          }
+        }
+        csv
+      },
+      ExportFormat::Json => serde_json::to_string_pretty(&self.report()).unwrap(),
+    }
+  }
+}
+
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+/// The UTC day of `time`.
+pub fn day(time: SystemTime) -> Day {
+  let days = time.duration_since(UNIX_EPOCH).map(|since| since.as_secs() / 86_400).unwrap_or_default() as i64;
+  // Howard Hinnant's days-to-civil conversion.
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let d = doy - (153 * mp + 2) / 5 + 1;
+  let m = if mp < 10 { mp + 3 } else { mp - 9 };
+  let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+  format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn at(days: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(days * 86_400 + 3_600)
+  }
+
+  #[test]
+  fn test_day() {
+    assert_eq!(day(UNIX_EPOCH), "1970-01-01");
+    assert_eq!(day(at(59)), "1970-03-01");
+    assert_eq!(day(at(11_016)), "2000-02-29");
+    assert_eq!(day(at(20_745)), "2026-10-19");
+  }
+
+  #[test]
+  fn test_record_and_report() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_usage_record_and_report.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut stats = UsageStats::new(Some(TEST_FILE_PATH.to_string()));
+    stats.load();
+    stats.record("clipboard", "copy", at(20_000));
+    stats.record("clipboard", "copy", at(20_700));
+    stats.record("clipboard", "copy", at(20_700));
+    stats.record("echo", "say, \"hi\"", at(20_701));
+
+    let mut stats = UsageStats::new(Some(TEST_FILE_PATH.to_string()));
+    stats.load();
+    let report = stats.report();
+    assert_eq!(report.since, Some(day(at(20_000))));
+    assert_eq!(report.actions.len(), 2);
+    assert_eq!(report.actions[0].total, 2, "buckets older than a year are dropped");
+    assert_eq!(report.actions[0].last_used, Some(day(at(20_700))));
+
+    assert!(!stats.is_unused("clipboard", "copy", at(20_730)));
+    assert!(stats.is_unused("clipboard", "copy", at(20_731)));
+    assert!(stats.is_unused("clipboard", "paste", at(20_731)));
+
+    let csv = stats.export(ExportFormat::Csv);
+    assert_eq!(csv.lines().collect::<Vec<_>>(), vec![
+      "date,extension,action,count",
+      "2026-09-04,clipboard,copy,2",
+      "2026-09-05,echo,\"say, \"\"hi\"\"\",1",
+    ]);
+    let json: serde_json::Value = serde_json::from_str(&stats.export(ExportFormat::Json)).unwrap();
+    assert_eq!(json["actions"][1]["daily"]["2026-09-05"], 1);
+  }
+
+  #[test]
+  fn test_not_unused_before_collecting_long_enough() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_usage_not_unused.json";
+    let _ = std::fs::remove_file(dirs::home_dir().unwrap().join(TEST_FILE_PATH));
+    let mut stats = UsageStats::new(Some(TEST_FILE_PATH.to_string()));
+    stats.load();
+    assert!(!stats.is_unused("clipboard", "paste", at(20_000)));
+    stats.record("clipboard", "copy", at(20_000));
+    assert!(!stats.is_unused("clipboard", "paste", at(20_010)));
+  }
+}
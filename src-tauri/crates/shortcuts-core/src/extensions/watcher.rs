@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Polls instead of subscribing to OS file events: extension directories are
+// small, and the same code then works on every platform and network drive.
+
+/// Modification time and size of every file below an extension directory.
+type Fingerprint = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+/// Reports extension directories whose files changed since the last poll.
+/// Every subdirectory of the extensions directory is one extension, except
+/// hidden ones used for downloads and staging. Side-loaded development
+/// extensions are watched directly.
+pub struct ExtensionWatcher {
+  extensions_dir: PathBuf,
+  dev_dirs: Vec<PathBuf>,
+  snapshot: HashMap<PathBuf, Fingerprint>,
+}
+
+impl ExtensionWatcher {
+  pub fn new(extensions_dir: &Path, dev_dirs: &[PathBuf]) -> Self {
+    let mut watcher = ExtensionWatcher {
+      extensions_dir: extensions_dir.to_path_buf(),
+      dev_dirs: dev_dirs.to_vec(),
+      snapshot: HashMap::new(),
+    };
+    watcher.refresh();
+    watcher
+  }
+
+  pub fn watch(&mut self, dir: &Path) {
+    if !self.dev_dirs.iter().any(|dev_dir| dev_dir == dir) {
+      self.dev_dirs.push(dir.to_path_buf());
+      self.snapshot.insert(dir.to_path_buf(), fingerprint(dir));
+    }
+  }
+
+  /// Takes a new snapshot without reporting changes, after the caller made
+  /// them itself (e.g. by installing a package).
+  pub fn refresh(&mut self) {
+    self.snapshot = self.scan();
+  }
+
+  /// The extension directories that were added, removed or modified since
+  /// the last poll, sorted.
+  pub fn poll(&mut self) -> Vec<PathBuf> {
+    let current = self.scan();
+    let changed: BTreeSet<PathBuf> = current.keys().chain(self.snapshot.keys())
+      .filter(|dir| current.get(*dir) != self.snapshot.get(*dir))
+      .cloned()
+      .collect();
+    self.snapshot = current;
+    changed.into_iter().collect()
+  }
+
+  fn scan(&self) -> HashMap<PathBuf, Fingerprint> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(&self.extensions_dir).into_iter().flatten()
+      .filter_map(|entry| entry.ok())
+      .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+      .map(|entry| entry.path())
+      .filter(|path| path.is_dir())
+      .collect();
+    dirs.extend(self.dev_dirs.iter().filter(|dir| dir.is_dir()).cloned());
+    dirs.into_iter().map(|dir| {
+      let fingerprint = fingerprint(&dir);
+      (dir, fingerprint)
+    }).collect()
+  }
+}
+
+fn fingerprint(dir: &Path) -> Fingerprint {
+  let mut fingerprint = Fingerprint::new();
+  let mut pending = vec![dir.to_path_buf()];
+  while let Some(dir) = pending.pop() {
+    for entry in fs::read_dir(&dir).into_iter().flatten().filter_map(|entry| entry.ok()) {
+      let metadata = match entry.metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => continue,
+      };
+      if metadata.is_dir() {
+        pending.push(entry.path());
+      } else {
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        fingerprint.insert(entry.path(), (modified, metadata.len()));
+      }
+    }
+  }
+  fingerprint
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_poll() {
+    let dir = std::env::temp_dir().join("shortcuts_test_watcher_poll");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("extensions/echo")).unwrap();
+    fs::create_dir_all(dir.join("dev")).unwrap();
+    fs::write(dir.join("extensions/echo/manifest.json"), "{}").unwrap();
+
+    let mut watcher = ExtensionWatcher::new(&dir.join("extensions"), &[dir.join("dev")]);
+    assert!(watcher.poll().is_empty());
+
+    fs::write(dir.join("extensions/echo/manifest.json"), r#"{"name":"echo"}"#).unwrap();
+    fs::create_dir_all(dir.join("extensions/.downloads")).unwrap();
+    fs::write(dir.join("extensions/.downloads/echo.scext"), "").unwrap();
+    fs::write(dir.join("dev/main.sh"), "").unwrap();
+    assert_eq!(watcher.poll(), vec![dir.join("dev"), dir.join("extensions/echo")]);
+    assert!(watcher.poll().is_empty());
+
+    fs::remove_dir_all(dir.join("extensions/echo")).unwrap();
+    fs::create_dir_all(dir.join("extensions/other")).unwrap();
+    assert_eq!(watcher.poll(), vec![dir.join("extensions/echo"), dir.join("extensions/other")]);
+
+    fs::write(dir.join("extensions/other/manifest.json"), "{}").unwrap();
+    watcher.refresh();
+    assert!(watcher.poll().is_empty());
+  }
+}
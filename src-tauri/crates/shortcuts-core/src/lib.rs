@@ -0,0 +1,47 @@
+//! Everything about shortcuts and extensions that doesn't need Tauri: the
+//! extension registry, bindings, persistence, permissions, packages and
+//! dispatch, plus the clipboard history with the `clipboard` feature. The
+//! app crate only wires this to the global shortcut plugin and the webview.
+
+#[cfg(feature = "clipboard")]
+pub mod clipboard {
+    pub mod backend;
+    pub mod content;
+    pub mod crypto;
+    pub mod discovery;
+    pub mod filter;
+    pub mod history;
+    #[cfg(target_os = "linux")]
+    pub mod linux;
+    pub mod monitor;
+    pub mod paste;
+    pub mod paste_stack;
+    pub mod registers;
+    pub mod retention;
+    pub mod sync;
+    pub mod transform;
+}
+pub mod modules {
+    pub mod config;
+    pub mod cache_manager;
+}
+pub mod extensions {
+    pub mod dispatcher;
+    pub mod extension;
+    pub mod extension_manager;
+    pub mod external;
+    pub mod history;
+    pub mod host;
+    pub mod locale;
+    pub mod metadata;
+    pub mod package;
+    pub mod permissions;
+    pub mod registry;
+    pub mod rpc;
+    pub mod settings;
+    pub mod usage;
+    pub mod watcher;
+}
+pub use shortcuts_types::shortcut;
+
+pub use shortcut::{Code, Modifiers, Shortcut};
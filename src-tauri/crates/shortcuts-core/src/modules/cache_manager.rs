@@ -1,22 +1,35 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+use serde_json::Value;
+
+use crate::extensions::permissions::Capability;
+use crate::shortcut::Shortcut;
 
 use super::config::*;
 
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct CacheShortcut {
+  /// The action ID. Configs written before IDs existed hold the display name.
   pub name: String,
   pub shortcut: Shortcut,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct CacheExtension {
+  /// The extension ID. Configs written before IDs existed hold the display name.
   pub name: String,
   pub shortcuts: Vec<CacheShortcut>,
-  pub enabled: bool
+  pub enabled: bool,
+  #[serde(default)]
+  pub settings: HashMap<String, Value>,
+  #[serde(default)]
+  pub granted: Vec<Capability>,
+  /// Consecutive handler panics. Reset by a successful run or by enabling
+  /// the extension again.
+  #[serde(default)]
+  pub crashes: u32,
 }
 
 
@@ -78,6 +91,7 @@ impl CacheManager {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::shortcut::{Code, Modifiers};
 
   #[test]
   fn test_add_shortcut() {
@@ -90,7 +104,10 @@ mod tests {
         name: "test".to_string(),
         shortcut: shortcut.clone()
       }],
-      enabled: true
+      enabled: true,
+      settings: HashMap::new(),
+      granted: vec![],
+      crashes: 0,
     });
     assert_eq!(cache_manager.extensions.len(), 1);
   }
@@ -125,7 +142,10 @@ mod tests {
         name: "test".to_string(),
         shortcut: shortcut.clone()
       }],
-      enabled: true
+      enabled: true,
+      settings: HashMap::new(),
+      granted: vec![],
+      crashes: 0,
     };
     cache_manager.add(cache_extension);
     cache_manager.write_to_cache();
@@ -148,7 +168,10 @@ mod tests {
         name: "test".to_string(),
         shortcut: shortcut1.clone()
       }],
-      enabled: true
+      enabled: true,
+      settings: HashMap::new(),
+      granted: vec![],
+      crashes: 0,
     };
 
     let cache_extension2 = CacheExtension {
@@ -157,7 +180,10 @@ mod tests {
         name: "test".to_string(),
         shortcut: shortcut2.clone()
       }],
-      enabled: true
+      enabled: true,
+      settings: HashMap::new(),
+      granted: vec![],
+      crashes: 0,
     };
 
     cache_manager.add(cache_extension1.clone());
@@ -170,4 +196,15 @@ mod tests {
     assert_eq!(cache_manager.extensions[0], cache_extension1);
     assert_eq!(cache_manager.extensions[1], cache_extension2);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_read_from_string_without_settings() {
+    const TEST_FILE_PATH: &str = ".shortcuts/__test__/test_read_from_string_without_settings.json";
+    let mut cache_manager = CacheManager::new(Some(TEST_FILE_PATH.to_string()));
+    cache_manager.read_from_string(r#"[{"name":"test","shortcuts":[],"enabled":true}]"#);
+    assert_eq!(cache_manager.extensions.len(), 1);
+    assert!(cache_manager.extensions[0].settings.is_empty());
+    assert!(cache_manager.extensions[0].granted.is_empty());
+    assert_eq!(cache_manager.extensions[0].crashes, 0);
+  }
+}
@@ -0,0 +1,115 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use shortcuts_core::clipboard::backend::{ClipboardContent, ContentType};
+use shortcuts_core::clipboard::sync::{ClipboardSync, SyncClip, SyncConfig, SyncIdentity, SyncPeer};
+
+const MAX_BYTES: usize = 1024;
+const WAIT: Duration = Duration::from_secs(5);
+
+fn peer(identity: &SyncIdentity, address: Option<String>) -> SyncPeer {
+  SyncPeer { key: identity.public_key(), address }
+}
+
+fn start(identity: &SyncIdentity, peers: Vec<SyncPeer>) -> (ClipboardSync, Receiver<SyncClip>) {
+  start_with(identity, peers, MAX_BYTES)
+}
+
+fn start_with(identity: &SyncIdentity, peers: Vec<SyncPeer>, max_bytes: usize) -> (ClipboardSync, Receiver<SyncClip>) {
+  let (sender, receiver) = mpsc::channel();
+  let config = SyncConfig { port: 0, peers, max_bytes };
+  let sync = ClipboardSync::start(identity, config, move |clip| sender.send(clip).unwrap()).unwrap();
+  (sync, receiver)
+}
+
+fn loopback(sync: &ClipboardSync) -> String {
+  format!("127.0.0.1:{}", sync.local_addr().port())
+}
+
+fn wait_until(condition: impl Fn() -> bool) -> bool {
+  let started = Instant::now();
+  while started.elapsed() < WAIT {
+    if condition() {
+      return true;
+    }
+    thread::sleep(Duration::from_millis(20));
+  }
+  false
+}
+
+#[test]
+fn test_paired_peers_exchange_copies() {
+  let (desktop_identity, laptop_identity) = (SyncIdentity::generate(), SyncIdentity::generate());
+  let (desktop, from_laptop) = start(&desktop_identity, vec![peer(&laptop_identity, None)]);
+  // Only the laptop knows where to find the other, and dials it.
+  let (laptop, from_desktop) = start(&laptop_identity, vec![peer(&desktop_identity, Some(loopback(&desktop)))]);
+  assert!(wait_until(|| desktop.connected() == vec![laptop_identity.public_key()]));
+  assert!(wait_until(|| laptop.connected() == vec![desktop_identity.public_key()]));
+
+  let rich = ClipboardContent::text("hello").with(ContentType::Html, "<b>hello</b>").with_source(Some("editor"));
+  assert_eq!(desktop.send(&rich, 1_000).unwrap(), 1);
+  let clip = from_desktop.recv_timeout(WAIT).unwrap();
+  assert_eq!(clip, SyncClip { content: rich, copied_at: 1_000, origin: desktop_identity.public_key() });
+
+  assert_eq!(laptop.send(&ClipboardContent::text("back"), 2_000).unwrap(), 1);
+  let clip = from_laptop.recv_timeout(WAIT).unwrap();
+  assert_eq!(clip.content.plain_text(), Some("back"));
+  assert_eq!(clip.origin, laptop_identity.public_key());
+
+  laptop.stop();
+  assert!(wait_until(|| desktop.connected().is_empty()), "the desktop notices the laptop left");
+}
+
+#[test]
+fn test_unpaired_peers_are_refused() {
+  let (desktop_identity, stranger_identity) = (SyncIdentity::generate(), SyncIdentity::generate());
+  let (desktop, from_stranger) = start(&desktop_identity, vec![]);
+  // The stranger trusts the desktop, but the desktop doesn't trust it.
+  let (stranger, _) = start(&stranger_identity, vec![peer(&desktop_identity, None)]);
+  assert!(stranger.connect(&loopback(&desktop)).is_err());
+  assert!(desktop.connected().is_empty());
+  assert!(stranger.connected().is_empty());
+  assert_eq!(stranger.send(&ClipboardContent::text("let me in"), 1_000).unwrap(), 0);
+  assert!(from_stranger.recv_timeout(Duration::from_millis(200)).is_err());
+}
+
+#[test]
+fn test_copies_over_the_size_limit_stay_local() {
+  let (desktop_identity, laptop_identity) = (SyncIdentity::generate(), SyncIdentity::generate());
+  let (desktop, _) = start(&desktop_identity, vec![peer(&laptop_identity, None)]);
+  let (laptop, from_desktop) = start(&laptop_identity, vec![peer(&desktop_identity, None)]);
+  assert_eq!(laptop.connect(&loopback(&desktop)).unwrap(), desktop_identity.public_key());
+  assert!(wait_until(|| desktop.connected().len() == 1));
+
+  let large = ClipboardContent::text(&"x".repeat(MAX_BYTES + 1));
+  assert_eq!(desktop.send(&large, 1_000).unwrap(), 0);
+  assert_eq!(desktop.send(&ClipboardContent::text("small"), 2_000).unwrap(), 1);
+  assert_eq!(from_desktop.recv_timeout(WAIT).unwrap().content.plain_text(), Some("small"));
+}
+
+#[test]
+fn test_stalled_peer_is_dropped() {
+  let (desktop_identity, laptop_identity) = (SyncIdentity::generate(), SyncIdentity::generate());
+  let (desktop, _) = start_with(&desktop_identity, vec![peer(&laptop_identity, None)], MAX_BYTES * 1024);
+  // The laptop never returns from its first copy, so it stops reading.
+  let (stall, stalled) = mpsc::channel::<()>();
+  let config = SyncConfig { port: 0, peers: vec![peer(&desktop_identity, None)], max_bytes: MAX_BYTES * 1024 };
+  let stalled = std::sync::Mutex::new(stalled);
+  let laptop = ClipboardSync::start(&laptop_identity, config, move |_| {
+    let _ = stalled.lock().unwrap().recv();
+  }).unwrap();
+  laptop.connect(&loopback(&desktop)).unwrap();
+  assert!(wait_until(|| desktop.connected().len() == 1));
+
+  let started = Instant::now();
+  let large = ClipboardContent::text(&"x".repeat(MAX_BYTES * 512));
+  let mut sent = 0;
+  for copied_at in 0..64 {
+    sent += desktop.send(&large, copied_at).unwrap();
+  }
+  assert!(started.elapsed() < WAIT, "sending never waits on a peer");
+  assert!(sent > 0 && sent < 64, "copies are queued until the laptop falls too far behind");
+  assert!(wait_until(|| desktop.connected().is_empty()), "the stalled laptop was dropped");
+  drop(stall);
+}
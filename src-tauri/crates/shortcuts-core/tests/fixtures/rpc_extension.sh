@@ -0,0 +1,27 @@
+#!/bin/sh
+# Minimal out-of-process extension used by the `extensions::external` tests.
+# Speaks newline-delimited JSON-RPC 2.0 on stdin/stdout.
+
+printf '%s\n' '{"jsonrpc":"2.0","method":"extensions/announce","params":{"extensions":[{"name":"Echo","description":"Echo extension","version":"1.0.0","author":"Shortcuts","keywords":["echo"],"icon":"symbol:speaker","shortcuts":[{"name":"Say","description":"Say hello","default_shortcut":"alt+KeyE","keywords":["greet"]}],"settings":[{"key":"greeting","label":"Greeting","type":"string","default":"hello"}],"capabilities":["shell:exec"]}]}}'
+
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"action":"Crash"'*)
+      exit 1
+      ;;
+    *'"method":"extension/unload"'*)
+      exit 0
+      ;;
+    *'"action":"Version"'*)
+      printf '%s\n' '{"jsonrpc":"2.0","id":1,"method":"host/version"}'
+      IFS= read -r reply
+      result=$(printf '%s' "$reply" | sed -n 's/.*"result":\(.*\)}$/\1/p')
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"host":%s}}\n' "$id" "$result"
+      ;;
+    *'"method":"extension/invoke"'*)
+      greeting=$(printf '%s' "$line" | sed -n 's/.*"greeting":"\([^"]*\)".*/\1/p')
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"said":"%s"}}\n' "$id" "$greeting"
+      ;;
+  esac
+done
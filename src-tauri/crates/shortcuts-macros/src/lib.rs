@@ -0,0 +1,500 @@
+//! Attribute macros for declaring extensions. Use them through
+//! `shortcuts-sdk`; the generated code refers to `::shortcuts_sdk`.
+//!
+//! ```ignore
+//! #[extension(id = "clipboard", name = "Clipboard", capabilities = ["clipboard:read"])]
+//! pub struct Clipboard;
+//!
+//! #[actions]
+//! impl Clipboard {
+//!   #[action(description = "View paste history board", default = "Super+Alt+V")]
+//!   fn history_viewer(context: &InvocationContext) { ... }
+//! }
+//! ```
+//!
+//! `#[extension]` generates the `ID`/`NAME`/`DESCRIPTION` constants, an
+//! `extension()` constructor and a `LazyLock<Extension>` static named after
+//! the struct (`CLIPBOARD`), and submits that static to the built-in
+//! extension inventory so the manager registers it. `#[actions]` turns
+//! every `#[action]` function into a `ShortcutHandler` and adds an ID
+//! constant per action. Default shortcuts, capabilities, versions and icons
+//! are validated at compile time. Metadata arguments (`version`, `author`,
+//! `homepage`, `license`, `category`, `keywords`, `icon`) are optional; the
+//! version defaults to the declaring crate's. `locales = "<dir>"` embeds the
+//! Fluent translations in that directory. `actions = <fn>` appends the
+//! handlers the function returns, for actions only known at runtime. On
+//! actions, `concurrency`, `debounce_ms` and `throttle_ms` set how repeated
+//! triggers are dispatched.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use shortcuts_types::capability::Capability;
+use shortcuts_types::shortcut::Shortcut;
+use syn::{parse_macro_input, Expr, ExprArray, ExprLit, ImplItem, ItemImpl, ItemStruct, Lit, LitStr, Path};
+
+#[derive(Default)]
+struct ExtensionArgs {
+  id: Option<LitStr>,
+  name: Option<LitStr>,
+  description: Option<LitStr>,
+  version: Option<LitStr>,
+  author: Option<LitStr>,
+  homepage: Option<LitStr>,
+  license: Option<LitStr>,
+  category: Option<LitStr>,
+  keywords: Vec<LitStr>,
+  icon: Option<LitStr>,
+  locales: Option<LitStr>,
+  capabilities: Vec<LitStr>,
+  settings: Option<Path>,
+  actions: Option<Path>,
+}
+
+#[derive(Default)]
+struct ActionArgs {
+  id: Option<LitStr>,
+  name: Option<LitStr>,
+  description: Option<LitStr>,
+  default: Option<LitStr>,
+  icon: Option<LitStr>,
+  keywords: Vec<LitStr>,
+  concurrency: Option<LitStr>,
+  debounce_ms: Option<syn::LitInt>,
+  throttle_ms: Option<syn::LitInt>,
+}
+
+#[proc_macro_attribute]
+pub fn extension(args: TokenStream, input: TokenStream) -> TokenStream {
+  let item = parse_macro_input!(input as ItemStruct);
+  let mut parsed = ExtensionArgs::default();
+  let parser = syn::meta::parser(|meta| {
+    if meta.path.is_ident("id") {
+      parsed.id = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("name") {
+      parsed.name = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("description") {
+      parsed.description = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("version") {
+      parsed.version = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("author") {
+      parsed.author = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("homepage") {
+      parsed.homepage = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("license") {
+      parsed.license = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("category") {
+      parsed.category = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("keywords") {
+      parsed.keywords = string_array(meta.value()?.parse()?)?;
+    } else if meta.path.is_ident("icon") {
+      parsed.icon = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("locales") {
+      parsed.locales = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("settings") {
+      parsed.settings = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("actions") {
+      parsed.actions = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("capabilities") {
+      parsed.capabilities = string_array(meta.value()?.parse()?)?;
+    } else {
+      return Err(meta.error("unsupported extension property"));
+    }
+    Ok(())
+  });
+  parse_macro_input!(args with parser);
+
+  match expand_extension(parsed, item) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into(),
+  }
+}
+
+#[proc_macro_attribute]
+pub fn actions(_args: TokenStream, input: TokenStream) -> TokenStream {
+  let item = parse_macro_input!(input as ItemImpl);
+  match expand_actions(item) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into(),
+  }
+}
+
+fn expand_extension(args: ExtensionArgs, item: ItemStruct) -> syn::Result<TokenStream2> {
+  let ident = &item.ident;
+  let vis = &item.vis;
+  let id = args.id.ok_or_else(|| syn::Error::new(ident.span(), "missing `id = \"...\"`"))?;
+  let name = args.name.map(|name| name.value()).unwrap_or_else(|| ident.to_string());
+  let description = args.description.map(|description| description.value()).unwrap_or_default();
+  for capability in &args.capabilities {
+    if let Err(err) = validate_capability(&capability.value()) {
+      return Err(syn::Error::new(capability.span(), err));
+    }
+  }
+  let capabilities = &args.capabilities;
+  let version = match &args.version {
+    Some(version) => {
+      if let Err(err) = semver::Version::parse(&version.value()) {
+        return Err(syn::Error::new(version.span(), format!("invalid version `{}`: {}", version.value(), err)));
+      }
+      quote! { #version }
+    },
+    None => quote! { env!("CARGO_PKG_VERSION") },
+  };
+  let author = optional_string(&args.author);
+  let homepage = optional_string(&args.homepage);
+  let license = optional_string(&args.license);
+  let category = optional_string(&args.category);
+  let keywords = &args.keywords;
+  let icon = icon_tokens(&args.icon)?;
+  let translations = translation_tokens(&args.locales)?;
+  let settings = match args.settings {
+    Some(settings) => quote! { #settings() },
+    None => quote! { vec![] },
+  };
+  let shortcuts = match args.actions {
+    Some(actions) => quote! {
+      {
+        let mut actions = <Self as ::shortcuts_sdk::ExtensionActions>::actions();
+        actions.extend(#actions());
+        actions
+      }
+    },
+    None => quote! { <Self as ::shortcuts_sdk::ExtensionActions>::actions() },
+  };
+  let static_ident = format_ident!("{}", screaming_snake_case(&ident.to_string()));
+
+  Ok(quote! {
+    #item
+
+    impl #ident {
+      pub const ID: &'static str = #id;
+      pub const NAME: &'static str = #name;
+      pub const DESCRIPTION: &'static str = #description;
+
+      pub fn extension() -> ::shortcuts_sdk::Extension {
+        ::shortcuts_sdk::Extension {
+          id: Self::ID.to_string(),
+          name: Self::NAME.to_string(),
+          description: Self::DESCRIPTION.to_string(),
+          metadata: ::shortcuts_sdk::Metadata {
+            version: #version.parse().ok(),
+            author: #author,
+            homepage: #homepage,
+            license: #license,
+            category: #category,
+            keywords: vec![#(#keywords.to_string()),*],
+            icon: #icon,
+          },
+          shortcuts: #shortcuts,
+          settings: #settings,
+          capabilities: vec![#(#capabilities.parse().unwrap()),*],
+          translations: #translations,
+        }
+      }
+    }
+
+    #vis static #static_ident: std::sync::LazyLock<::shortcuts_sdk::Extension> =
+      std::sync::LazyLock::new(#ident::extension);
+
+    ::shortcuts_sdk::inventory::submit! {
+      ::shortcuts_sdk::BuiltinExtension(&#static_ident)
+    }
+  })
+}
+
+fn expand_actions(mut item: ItemImpl) -> syn::Result<TokenStream2> {
+  let self_ty = item.self_ty.clone();
+  let mut handlers = vec![];
+  let mut constants = vec![];
+
+  for impl_item in item.items.iter_mut() {
+    let method = match impl_item {
+      ImplItem::Fn(method) => method,
+      _ => continue,
+    };
+    let position = match method.attrs.iter().position(|attr| attr.path().is_ident("action")) {
+      Some(position) => position,
+      None => continue,
+    };
+    let attr = method.attrs.remove(position);
+
+    let mut args = ActionArgs::default();
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("id") {
+        args.id = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("name") {
+        args.name = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("description") {
+        args.description = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("default") {
+        args.default = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("icon") {
+        args.icon = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("keywords") {
+        args.keywords = string_array(meta.value()?.parse()?)?;
+      } else if meta.path.is_ident("concurrency") {
+        args.concurrency = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("debounce_ms") {
+        args.debounce_ms = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("throttle_ms") {
+        args.throttle_ms = Some(meta.value()?.parse()?);
+      } else {
+        return Err(meta.error("unsupported action property"));
+      }
+      Ok(())
+    })?;
+
+    if method.sig.receiver().is_some() {
+      return Err(syn::Error::new_spanned(&method.sig, "actions are associated functions without `self`"));
+    }
+    let function = &method.sig.ident;
+    let handle = match method.sig.inputs.len() {
+      0 => quote! { |_| <#self_ty>::#function() },
+      1 => quote! { <#self_ty>::#function },
+      _ => return Err(syn::Error::new_spanned(&method.sig.inputs, "actions take at most an `&InvocationContext`")),
+    };
+
+    let policy = policy_tokens(&args)?;
+    let id = args.id.map(|id| id.value()).unwrap_or_else(|| function.to_string());
+    let name = args.name.map(|name| name.value()).unwrap_or_else(|| upper_camel_case(&function.to_string()));
+    let description = args.description.map(|description| description.value()).unwrap_or_default();
+    let default_shortcut = match &args.default {
+      Some(default) => shortcut_tokens(default)?,
+      None => quote! { None },
+    };
+    let icon = icon_tokens(&args.icon)?;
+    let keywords = &args.keywords;
+    let constant = format_ident!("{}", screaming_snake_case(&function.to_string()));
+
+    constants.push(quote! { pub const #constant: &'static str = #id; });
+    handlers.push(quote! {
+      ::shortcuts_sdk::ShortcutHandler {
+        id: #id.to_string(),
+        name: #name.to_string(),
+        description: #description.to_string(),
+        handle: #handle,
+        default_shortcut: #default_shortcut,
+        icon: #icon,
+        keywords: vec![#(#keywords.to_string()),*],
+        policy: #policy,
+      }
+    });
+  }
+
+  Ok(quote! {
+    #item
+
+    impl #self_ty {
+      #(#constants)*
+    }
+
+    impl ::shortcuts_sdk::ExtensionActions for #self_ty {
+      fn actions() -> Vec<::shortcuts_sdk::ShortcutHandler> {
+        vec![#(#handlers),*]
+      }
+    }
+  })
+}
+
+/// Parses a shortcut with the same `Shortcut::from_str` used at runtime and
+/// emits the equivalent constructor call.
+fn shortcut_tokens(literal: &LitStr) -> syn::Result<TokenStream2> {
+  let shortcut: Shortcut = literal.value().parse()
+    .map_err(|err: String| syn::Error::new(literal.span(), err))?;
+  let bits = shortcut.mods.bits();
+  let code = format_ident!("{}", format!("{:?}", shortcut.key));
+  Ok(quote! {
+    Some(::shortcuts_sdk::Shortcut::new(
+      Some(::shortcuts_sdk::Modifiers::from_bits_retain(#bits)),
+      ::shortcuts_sdk::Code::#code,
+    ))
+  })
+}
+
+/// The dispatch policy: `concurrency` is `drop` (the default), `queue`,
+/// `restart` or `parallel`; at most one of `debounce_ms` and `throttle_ms`.
+fn policy_tokens(args: &ActionArgs) -> syn::Result<TokenStream2> {
+  let concurrency = match args.concurrency.as_ref().map(|concurrency| (concurrency, concurrency.value())) {
+    None => quote! { Drop },
+    Some((_, value)) if value == "drop" => quote! { Drop },
+    Some((_, value)) if value == "queue" => quote! { Queue },
+    Some((_, value)) if value == "restart" => quote! { Restart },
+    Some((_, value)) if value == "parallel" => quote! { Parallel },
+    Some((literal, _)) => return Err(syn::Error::new(literal.span(), "expected `drop`, `queue`, `restart` or `parallel`")),
+  };
+  let rate_limit = match (&args.debounce_ms, &args.throttle_ms) {
+    (None, None) => quote! { None },
+    (Some(debounce), None) => quote! { Some(::shortcuts_sdk::RateLimit::Debounce(#debounce)) },
+    (None, Some(throttle)) => quote! { Some(::shortcuts_sdk::RateLimit::Throttle(#throttle)) },
+    (Some(_), Some(throttle)) => return Err(syn::Error::new(throttle.span(), "use either `debounce_ms` or `throttle_ms`")),
+  };
+  Ok(quote! {
+    ::shortcuts_sdk::DispatchPolicy {
+      concurrency: ::shortcuts_sdk::Concurrency::#concurrency,
+      rate_limit: #rate_limit,
+    }
+  })
+}
+
+/// `symbol:<name>` becomes a named symbol; a `.svg` or `.png` path relative
+/// to the declaring crate's manifest is embedded into the binary.
+fn icon_tokens(icon: &Option<LitStr>) -> syn::Result<TokenStream2> {
+  let icon = match icon {
+    Some(icon) => icon,
+    None => return Ok(quote! { None }),
+  };
+  let value = icon.value();
+  if let Some(name) = value.strip_prefix("symbol:") {
+    if name.is_empty() {
+      return Err(syn::Error::new(icon.span(), "icon symbol name is empty"));
+    }
+    return Ok(quote! { Some(::shortcuts_sdk::Icon::Symbol(#name.to_string())) });
+  }
+  let path = quote! { concat!(env!("CARGO_MANIFEST_DIR"), "/", #icon) };
+  match std::path::Path::new(&value).extension().and_then(|extension| extension.to_str()) {
+    Some("svg") => Ok(quote! { Some(::shortcuts_sdk::Icon::Svg(include_str!(#path).to_string())) }),
+    Some("png") => Ok(quote! { Some(::shortcuts_sdk::Icon::Png(include_bytes!(#path).to_vec())) }),
+    _ => Err(syn::Error::new(icon.span(), "expected `symbol:<name>` or a path to an .svg or .png file")),
+  }
+}
+
+/// Embeds every `<locale>.ftl` in a directory relative to the declaring
+/// crate's manifest, checking locale tags and Fluent syntax.
+fn translation_tokens(locales: &Option<LitStr>) -> syn::Result<TokenStream2> {
+  let locales = match locales {
+    Some(locales) => locales,
+    None => return Ok(quote! { vec![] }),
+  };
+  let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+  let dir = std::path::Path::new(&manifest_dir).join(locales.value());
+  let entries = std::fs::read_dir(&dir)
+    .map_err(|err| syn::Error::new(locales.span(), format!("cannot read locales directory {:?}: {}", dir, err)))?;
+
+  let mut files: Vec<std::path::PathBuf> = entries
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| path.extension().is_some_and(|extension| extension == "ftl"))
+    .collect();
+  files.sort();
+
+  let mut translations = vec![];
+  for file in files {
+    let locale = file.file_stem().unwrap().to_string_lossy().to_string();
+    let source = std::fs::read_to_string(&file).map_err(|err| syn::Error::new(locales.span(), err.to_string()))?;
+    validate_translation(&locale, &source)
+      .map_err(|err| syn::Error::new(locales.span(), format!("{:?}: {}", file, err)))?;
+    let path = format!("{}/{}.ftl", locales.value(), locale);
+    translations.push(quote! {
+      ::shortcuts_sdk::Translation {
+        locale: #locale.to_string(),
+        source: include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #path)).to_string(),
+      }
+    });
+  }
+  Ok(quote! { vec![#(#translations),*] })
+}
+
+fn optional_string(value: &Option<LitStr>) -> TokenStream2 {
+  match value {
+    Some(value) => quote! { Some(#value.to_string()) },
+    None => quote! { None },
+  }
+}
+
+fn string_array(array: ExprArray) -> syn::Result<Vec<LitStr>> {
+  array.elems.into_iter().map(|element| match element {
+    Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) => Ok(value),
+    other => Err(syn::Error::new_spanned(other, "expected a string")),
+  }).collect()
+}
+
+/// The same checks as `Translation::validate` in core: the locale tag and
+/// the Fluent syntax.
+fn validate_translation(locale: &str, source: &str) -> Result<(), String> {
+  locale.parse::<unic_langid::LanguageIdentifier>().map_err(|err| format!("invalid locale `{}`: {}", locale, err))?;
+  fluent_syntax::parser::parse(source)
+    .map(|_| ())
+    .map_err(|(_, errors)| format!("invalid Fluent source for {}: {:?}", locale, errors))
+}
+
+fn validate_capability(value: &str) -> Result<(), String> {
+  value.parse::<Capability>().map(|_| ())
+}
+
+fn upper_camel_case(value: &str) -> String {
+  value.split('_').filter(|part| !part.is_empty()).map(|part| {
+    let mut chars = part.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+  }).collect()
+}
+
+fn screaming_snake_case(value: &str) -> String {
+  let mut result = String::new();
+  let mut previous_lower = false;
+  for c in value.chars() {
+    if c.is_uppercase() && previous_lower {
+      result.push('_');
+    }
+    previous_lower = c.is_lowercase() || c.is_ascii_digit();
+    result.extend(c.to_uppercase());
+  }
+  result
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use proc_macro2::Span;
+
+  #[test]
+  fn test_shortcut_tokens() {
+    let tokens = shortcut_tokens(&LitStr::new("Super+Alt+V", Span::call_site())).unwrap().to_string();
+    assert!(tokens.contains("Code :: KeyV"));
+    assert!(shortcut_tokens(&LitStr::new("Super+Alt", Span::call_site())).is_err());
+    assert!(shortcut_tokens(&LitStr::new("Hyper+V", Span::call_site())).is_err());
+  }
+
+  #[test]
+  fn test_icon_tokens() {
+    let symbol = icon_tokens(&Some(LitStr::new("symbol:clipboard", Span::call_site()))).unwrap().to_string();
+    assert!(symbol.contains("Icon :: Symbol"));
+    let svg = icon_tokens(&Some(LitStr::new("icons/clipboard.svg", Span::call_site()))).unwrap().to_string();
+    assert!(svg.contains("include_str"));
+    assert!(icon_tokens(&Some(LitStr::new("symbol:", Span::call_site()))).is_err());
+    assert!(icon_tokens(&Some(LitStr::new("icon.gif", Span::call_site()))).is_err());
+    assert_eq!(icon_tokens(&None).unwrap().to_string(), "None");
+  }
+
+  #[test]
+  fn test_policy_tokens() {
+    let mut args = ActionArgs::default();
+    assert!(policy_tokens(&args).unwrap().to_string().contains("Concurrency :: Drop"));
+
+    args.concurrency = Some(LitStr::new("queue", Span::call_site()));
+    args.throttle_ms = Some(syn::LitInt::new("500", Span::call_site()));
+    let tokens = policy_tokens(&args).unwrap().to_string();
+    assert!(tokens.contains("Concurrency :: Queue"));
+    assert!(tokens.contains("RateLimit :: Throttle (500)"));
+
+    args.debounce_ms = Some(syn::LitInt::new("200", Span::call_site()));
+    assert!(policy_tokens(&args).is_err());
+    args.throttle_ms = None;
+    args.concurrency = Some(LitStr::new("sometimes", Span::call_site()));
+    assert!(policy_tokens(&args).is_err());
+  }
+
+  #[test]
+  fn test_validate_capability() {
+    assert!(validate_capability("clipboard:read").is_ok());
+    assert!(validate_capability("fs:/tmp").is_ok());
+    assert!(validate_capability("fs:tmp").is_err());
+    assert!(validate_capability("root").is_err());
+  }
+
+  #[test]
+  fn test_case_conversion() {
+    assert_eq!(upper_camel_case("history_viewer"), "HistoryViewer");
+    assert_eq!(screaming_snake_case("history_viewer"), "HISTORY_VIEWER");
+    assert_eq!(screaming_snake_case("ClipboardHistory"), "CLIPBOARD_HISTORY");
+  }
+}
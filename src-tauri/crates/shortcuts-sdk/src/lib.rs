@@ -0,0 +1,36 @@
+//! What extension authors build against.
+//!
+//! Built-in extensions declare themselves with the attribute macros:
+//!
+//! ```ignore
+//! use shortcuts_sdk::{actions, extension, InvocationContext};
+//!
+//! #[extension(id = "clipboard", name = "Clipboard", capabilities = ["clipboard:read"])]
+//! pub struct Clipboard;
+//!
+//! #[actions]
+//! impl Clipboard {
+//!   #[action(description = "View paste history board", default = "Super+Alt+V")]
+//!   fn history_viewer(context: &InvocationContext) { ... }
+//! }
+//! ```
+//!
+//! Out-of-process extensions speak the JSON-RPC protocol in [`rpc`] over
+//! stdio instead.
+
+#[cfg(feature = "clipboard")]
+pub use shortcuts_core::clipboard::transform::Transform;
+pub use shortcuts_core::extensions::dispatcher::{CancellationToken, Concurrency, DispatchPolicy, RateLimit};
+pub use shortcuts_core::extensions::extension::{
+    BuiltinExtension, Extension, ExtensionActions, InvocationContext, ShortcutHandler,
+};
+pub use shortcuts_core::extensions::locale::Translation;
+pub use shortcuts_core::extensions::metadata::{Icon, Metadata};
+pub use shortcuts_core::extensions::permissions::Capability;
+pub use shortcuts_core::extensions::rpc;
+pub use shortcuts_core::extensions::settings::{SettingKind, SettingSchema, Settings};
+pub use shortcuts_core::shortcut::{Code, Modifiers, Shortcut};
+pub use shortcuts_macros::{actions, extension};
+
+#[doc(hidden)]
+pub use inventory;
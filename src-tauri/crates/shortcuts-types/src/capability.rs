@@ -0,0 +1,88 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Something an extension may do through the host. Serialized as a string:
+/// `clipboard:read`, `clipboard:write`, `shell:exec`, `fs:<path>`, `network`, `ui:show`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub enum Capability {
+  ClipboardRead,
+  ClipboardWrite,
+  ShellExec,
+  /// Read and write access to everything below the path.
+  Filesystem(PathBuf),
+  Network,
+  ShowUi,
+}
+
+impl fmt::Display for Capability {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Capability::ClipboardRead => write!(f, "clipboard:read"),
+      Capability::ClipboardWrite => write!(f, "clipboard:write"),
+      Capability::ShellExec => write!(f, "shell:exec"),
+      Capability::Filesystem(path) => write!(f, "fs:{}", path.display()),
+      Capability::Network => write!(f, "network"),
+      Capability::ShowUi => write!(f, "ui:show"),
+    }
+  }
+}
+
+impl FromStr for Capability {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value {
+      "clipboard:read" => Ok(Capability::ClipboardRead),
+      "clipboard:write" => Ok(Capability::ClipboardWrite),
+      "shell:exec" => Ok(Capability::ShellExec),
+      "network" => Ok(Capability::Network),
+      "ui:show" => Ok(Capability::ShowUi),
+      _ => match value.strip_prefix("fs:") {
+        Some(path) if Path::new(path).is_absolute() => Ok(Capability::Filesystem(PathBuf::from(path))),
+        Some(path) => Err(format!("filesystem capability needs an absolute path, got `{}`", path)),
+        None => Err(format!("unknown capability `{}`", value)),
+      },
+    }
+  }
+}
+
+impl TryFrom<String> for Capability {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<Capability> for String {
+  fn from(capability: Capability) -> Self {
+    capability.to_string()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_capability() {
+    for value in ["clipboard:read", "clipboard:write", "shell:exec", "network", "ui:show", "fs:/tmp/notes"] {
+      assert_eq!(value.parse::<Capability>().unwrap().to_string(), value);
+    }
+    assert!("fs:notes".parse::<Capability>().is_err());
+    assert!("root".parse::<Capability>().is_err());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde_capability() {
+    let capabilities: Vec<Capability> = serde_json::from_str(r#"["shell:exec","fs:/tmp"]"#).unwrap();
+    assert_eq!(capabilities, vec![Capability::ShellExec, Capability::Filesystem(PathBuf::from("/tmp"))]);
+    assert_eq!(serde_json::to_string(&capabilities).unwrap(), r#"["shell:exec","fs:/tmp"]"#);
+    assert!(serde_json::from_str::<Vec<Capability>>(r#"["everything"]"#).is_err());
+  }
+}
@@ -0,0 +1,6 @@
+//! The shortcut and capability types, with their parsers. Core and the
+//! attribute macros both parse them, so they live here without pulling in
+//! the rest of core.
+
+pub mod capability;
+pub mod shortcut;
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::str::FromStr;
+
+pub use keyboard_types::{Code, Modifiers};
+
+/// A key combination, independent of the backend that registers it with the
+/// OS. Formats as `shift+control+alt+super+KeyV`, the same text the global
+/// shortcut plugin uses, so stored bindings stay readable by both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Shortcut {
+  pub mods: Modifiers,
+  pub key: Code,
+}
+
+impl Shortcut {
+  /// META is folded into SUPER, as the platform backends do.
+  pub fn new(mods: Option<Modifiers>, key: Code) -> Self {
+    let mut mods = mods.unwrap_or_else(Modifiers::empty);
+    if mods.contains(Modifiers::META) {
+      mods.remove(Modifiers::META);
+      mods.insert(Modifiers::SUPER);
+    }
+    Shortcut { mods, key }
+  }
+}
+
+impl fmt::Display for Shortcut {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (modifier, name) in [
+      (Modifiers::SHIFT, "shift"),
+      (Modifiers::CONTROL, "control"),
+      (Modifiers::ALT, "alt"),
+      (Modifiers::SUPER, "super"),
+    ] {
+      if self.mods.contains(modifier) {
+        write!(f, "{}+", name)?;
+      }
+    }
+    write!(f, "{}", self.key)
+  }
+}
+
+impl FromStr for Shortcut {
+  type Err = String;
+
+  /// Accepts modifiers followed by one key, case-insensitively:
+  /// `Super+Alt+V`, `ctrl+shift+KeyK`, `CmdOrCtrl+Space`.
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    let mut mods = Modifiers::empty();
+    let mut key = None;
+    for token in value.split('+').map(str::trim) {
+      if token.is_empty() {
+        return Err(format!("invalid shortcut `{}`: empty token", value));
+      }
+      if key.is_some() {
+        return Err(format!("invalid shortcut `{}`: modifiers must come before a single key", value));
+      }
+      match token.to_uppercase().as_str() {
+        "ALT" | "OPTION" => mods |= Modifiers::ALT,
+        "CONTROL" | "CTRL" => mods |= Modifiers::CONTROL,
+        "SHIFT" => mods |= Modifiers::SHIFT,
+        "SUPER" | "COMMAND" | "CMD" | "META" => mods |= Modifiers::SUPER,
+        "COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => mods |= CMD_OR_CTRL,
+        _ => key = Some(parse_key(token).ok_or_else(|| format!("invalid shortcut `{}`: unknown key `{}`", value, token))?),
+      }
+    }
+    let key = key.ok_or_else(|| format!("invalid shortcut `{}`: missing key", value))?;
+    Ok(Shortcut::new(Some(mods), key))
+  }
+}
+
+impl TryFrom<String> for Shortcut {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<Shortcut> for String {
+  fn from(shortcut: Shortcut) -> Self {
+    shortcut.to_string()
+  }
+}
+
+#[cfg(target_os = "macos")]
+const CMD_OR_CTRL: Modifiers = Modifiers::SUPER;
+#[cfg(not(target_os = "macos"))]
+const CMD_OR_CTRL: Modifiers = Modifiers::CONTROL;
+
+/// W3C code names (`KeyV`, `ArrowUp`, `F5`) plus the usual short forms.
+fn parse_key(token: &str) -> Option<Code> {
+  if let Ok(code) = Code::from_str(token) {
+    return (code != Code::Unidentified).then_some(code);
+  }
+  let upper = token.to_uppercase();
+  let name = match upper.as_str() {
+    "`" => "Backquote".to_string(),
+    "\\" => "Backslash".to_string(),
+    "[" => "BracketLeft".to_string(),
+    "]" => "BracketRight".to_string(),
+    "," => "Comma".to_string(),
+    "=" => "Equal".to_string(),
+    "-" => "Minus".to_string(),
+    "." => "Period".to_string(),
+    "'" => "Quote".to_string(),
+    ";" => "Semicolon".to_string(),
+    "/" => "Slash".to_string(),
+    "ESC" | "ESCAPE" => "Escape".to_string(),
+    "SPACE" => "Space".to_string(),
+    "TAB" => "Tab".to_string(),
+    "ENTER" | "RETURN" => "Enter".to_string(),
+    "BACKSPACE" => "Backspace".to_string(),
+    "DELETE" => "Delete".to_string(),
+    "INSERT" => "Insert".to_string(),
+    "HOME" => "Home".to_string(),
+    "END" => "End".to_string(),
+    "PAGEUP" => "PageUp".to_string(),
+    "PAGEDOWN" => "PageDown".to_string(),
+    "UP" | "ARROWUP" => "ArrowUp".to_string(),
+    "DOWN" | "ARROWDOWN" => "ArrowDown".to_string(),
+    "LEFT" | "ARROWLEFT" => "ArrowLeft".to_string(),
+    "RIGHT" | "ARROWRIGHT" => "ArrowRight".to_string(),
+    letter if letter.len() == 1 && letter.chars().all(|c| c.is_ascii_alphabetic()) => format!("Key{}", letter),
+    digit if digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()) => format!("Digit{}", digit),
+    key if key.len() == 4 && key.starts_with("KEY") => format!("Key{}", &key[3..]),
+    digit if digit.len() == 6 && digit.starts_with("DIGIT") => format!("Digit{}", &digit[5..]),
+    other => other.to_string(),
+  };
+  Code::from_str(&name).ok().filter(|code| *code != Code::Unidentified)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    assert_eq!("Super+Alt+V".parse(), Ok(Shortcut::new(Some(Modifiers::SUPER | Modifiers::ALT), Code::KeyV)));
+    assert_eq!("ctrl+shift+KeyK".parse(), Ok(Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyK)));
+    assert_eq!("alt + 1".parse(), Ok(Shortcut::new(Some(Modifiers::ALT), Code::Digit1)));
+    assert_eq!("Space".parse(), Ok(Shortcut::new(None, Code::Space)));
+    assert_eq!("shift+f5".parse(), Ok(Shortcut::new(Some(Modifiers::SHIFT), Code::F5)));
+    assert!("Super+Alt".parse::<Shortcut>().is_err());
+    assert!("Super++V".parse::<Shortcut>().is_err());
+    assert!("V+Super".parse::<Shortcut>().is_err());
+    assert!("Hyper+V".parse::<Shortcut>().is_err());
+  }
+
+  #[test]
+  fn test_display_round_trip() {
+    let shortcut = Shortcut::new(Some(Modifiers::META | Modifiers::SHIFT), Code::KeyV);
+    assert_eq!(shortcut.mods, Modifiers::SUPER | Modifiers::SHIFT);
+    assert_eq!(shortcut.to_string(), "shift+super+KeyV");
+    assert_eq!(shortcut.to_string().parse(), Ok(shortcut));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde() {
+    let shortcut: Shortcut = serde_json::from_str(r#""alt+KeyE""#).unwrap();
+    assert_eq!(shortcut, Shortcut::new(Some(Modifiers::ALT), Code::KeyE));
+    assert_eq!(serde_json::to_string(&shortcut).unwrap(), r#""alt+KeyE""#);
+    assert!(serde_json::from_str::<Shortcut>(r#""alt+""#).is_err());
+  }
+}
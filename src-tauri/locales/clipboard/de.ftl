@@ -0,0 +1,38 @@
+clipboard-name = Zwischenablage
+clipboard-description = Kopieren, Einfügen und Verlauf der Zwischenablage
+clipboard-copy-name = Kopieren
+clipboard-copy-description = Den markierten Text in die Zwischenablage kopieren
+clipboard-paste-name = Einfügen
+clipboard-paste-description = Den Inhalt der Zwischenablage einfügen
+clipboard-history_viewer-name = Verlauf
+clipboard-history_viewer-description = Den Verlauf der Zwischenablage anzeigen
+clipboard-lock_history-name = Verlauf sperren
+clipboard-lock_history-description = Den Verlauf der Zwischenablage sperren, bis die App neu gestartet wird
+clipboard-pin_latest-name = Letzten Eintrag anheften
+clipboard-pin_latest-description = Den letzten Eintrag der Zwischenablage anheften
+clipboard-transform_plain_text-name = Als reinen Text einfügen
+clipboard-transform_plain_text-description = Die Formatierung der Zwischenablage verwerfen und ihren Text behalten
+clipboard-transform_json_pretty-name = Als formatiertes JSON einfügen
+clipboard-transform_json_pretty-description = Das JSON in der Zwischenablage einrücken
+clipboard-transform_json_compact-name = Als kompaktes JSON einfügen
+clipboard-transform_json_compact-description = Die Leerzeichen aus dem JSON in der Zwischenablage entfernen
+clipboard-transform_lowercase-name = In Kleinbuchstaben einfügen
+clipboard-transform_lowercase-description = Den Text der Zwischenablage in Kleinbuchstaben umwandeln
+clipboard-transform_uppercase-name = In Großbuchstaben einfügen
+clipboard-transform_uppercase-description = Den Text der Zwischenablage in Großbuchstaben umwandeln
+clipboard-transform_trim-name = Ohne Leerraum einfügen
+clipboard-transform_trim-description = Den Leerraum um den Text der Zwischenablage entfernen
+clipboard-transform_url_encode-name = URL-kodiert einfügen
+clipboard-transform_url_encode-description = Den Text der Zwischenablage für eine URL prozentkodieren
+clipboard-transform_url_decode-name = URL-dekodiert einfügen
+clipboard-transform_url_decode-description = Den prozentkodierten Text der Zwischenablage dekodieren
+clipboard-transform_base64_encode-name = Base64-kodiert einfügen
+clipboard-transform_base64_encode-description = Den Text der Zwischenablage als Base64 kodieren
+clipboard-transform_base64_decode-name = Base64-dekodiert einfügen
+clipboard-transform_base64_decode-description = Das Base64 in der Zwischenablage dekodieren
+clipboard-toggle_paste_stack-name = Einfügestapel
+clipboard-toggle_paste_stack-description = Jede Kopie einreihen und nacheinander in Kopierreihenfolge einfügen
+clipboard-copy_to_register-name = In Register kopieren
+clipboard-copy_to_register-description = Die Zwischenablage in ein Register speichern, benannt durch den danach gedrückten Buchstaben
+clipboard-paste_from_register-name = Aus Register einfügen
+clipboard-paste_from_register-description = Aus einem Register einfügen, benannt durch den danach gedrückten Buchstaben
@@ -0,0 +1,38 @@
+clipboard-name = 剪贴板
+clipboard-description = 复制、粘贴和剪贴板历史
+clipboard-copy-name = 复制
+clipboard-copy-description = 将选中的文本复制到剪贴板
+clipboard-paste-name = 粘贴
+clipboard-paste-description = 粘贴剪贴板中的内容
+clipboard-history_viewer-name = 历史记录
+clipboard-history_viewer-description = 查看剪贴板历史
+clipboard-lock_history-name = 锁定历史
+clipboard-lock_history-description = 锁定剪贴板历史，直到应用重新启动
+clipboard-pin_latest-name = 固定最新条目
+clipboard-pin_latest-description = 固定最新的剪贴板条目
+clipboard-transform_plain_text-name = 粘贴为纯文本
+clipboard-transform_plain_text-description = 去掉剪贴板内容的格式，只保留文本
+clipboard-transform_json_pretty-name = 粘贴为格式化的 JSON
+clipboard-transform_json_pretty-description = 缩进剪贴板中的 JSON
+clipboard-transform_json_compact-name = 粘贴为紧凑的 JSON
+clipboard-transform_json_compact-description = 去掉剪贴板中 JSON 的空白
+clipboard-transform_lowercase-name = 粘贴为小写
+clipboard-transform_lowercase-description = 将剪贴板文本转为小写
+clipboard-transform_uppercase-name = 粘贴为大写
+clipboard-transform_uppercase-description = 将剪贴板文本转为大写
+clipboard-transform_trim-name = 去除首尾空白后粘贴
+clipboard-transform_trim-description = 去掉剪贴板文本首尾的空白
+clipboard-transform_url_encode-name = URL 编码后粘贴
+clipboard-transform_url_encode-description = 对剪贴板文本进行 URL 百分号编码
+clipboard-transform_url_decode-name = URL 解码后粘贴
+clipboard-transform_url_decode-description = 解码剪贴板中的百分号编码文本
+clipboard-transform_base64_encode-name = Base64 编码后粘贴
+clipboard-transform_base64_encode-description = 将剪贴板文本编码为 Base64
+clipboard-transform_base64_decode-name = Base64 解码后粘贴
+clipboard-transform_base64_decode-description = 解码剪贴板中的 Base64
+clipboard-toggle_paste_stack-name = 粘贴队列
+clipboard-toggle_paste_stack-description = 将每次复制加入队列，并按复制顺序依次粘贴
+clipboard-copy_to_register-name = 复制到寄存器
+clipboard-copy_to_register-description = 将剪贴板保存到寄存器，寄存器由随后按下的字母指定
+clipboard-paste_from_register-name = 从寄存器粘贴
+clipboard-paste_from_register-description = 从寄存器粘贴，寄存器由随后按下的字母指定
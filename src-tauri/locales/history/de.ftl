@@ -0,0 +1,4 @@
+history-name = Aktionsverlauf
+history-description = Zuletzt ausgeführte Aktionen wiederholen
+history-repeat_last-name = Letzte Aktion wiederholen
+history-repeat_last-description = Die zuletzt ausgeführte Aktion erneut ausführen
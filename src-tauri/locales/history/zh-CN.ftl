@@ -0,0 +1,4 @@
+history-name = 操作历史
+history-description = 重复最近执行的操作
+history-repeat_last-name = 重复上一个操作
+history-repeat_last-description = 再次执行最近一次执行的操作
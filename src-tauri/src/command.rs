@@ -1,6 +1,25 @@
+use serde::Deserialize;
+use std::path::Path;
 use std::sync::Once;
+#[cfg(feature = "clipboard")]
+use std::time::SystemTime;
 use tauri_nspanel::ManagerExt;
-use crate::extensions::extension_manager::EXTENSION_MANAGER;
+#[cfg(feature = "clipboard")]
+use shortcuts_core::clipboard::{
+    backend::{self, ClipboardContent, ContentType},
+    history::{self as clipboard_history, ClipboardEntry},
+    paste_stack::{PasteStackState, PASTE_STACK},
+    registers::Register,
+    sync::SyncStatus,
+};
+use shortcuts_core::extensions::extension_manager::{self, EXTENSION_MANAGER};
+use shortcuts_core::extensions::external::LoadError;
+use shortcuts_core::extensions::history::HistoryEntry;
+use shortcuts_core::extensions::locale::LocaleConfig;
+use shortcuts_core::extensions::package::{self, InstalledExtension};
+use shortcuts_core::extensions::permissions::Capability;
+use shortcuts_core::extensions::registry::{AvailableExtension, RegistryConfig, RegistryIndex};
+use shortcuts_core::extensions::usage::{self, ExportFormat, UsageReport};
 
 use crate::fns::{
     setup_menubar_panel_listeners, swizzle_to_menubar_panel, update_menubar_appearance,
@@ -28,11 +47,247 @@ pub fn show_menubar_panel(app_handle: tauri::AppHandle) {
 
 #[tauri::command]
 pub fn get_extensions() -> String {
-    let extension_manager = &*EXTENSION_MANAGER;
-    extension_manager.to_string()
+    EXTENSION_MANAGER.lock().unwrap().to_string()
+}
+
+#[tauri::command]
+pub fn get_load_errors() -> Vec<LoadError> {
+    EXTENSION_MANAGER.lock().unwrap().load_errors()
+}
+
+#[tauri::command]
+pub fn get_action_history() -> Vec<HistoryEntry> {
+    EXTENSION_MANAGER.lock().unwrap().history()
+}
+
+#[tauri::command]
+pub fn rerun_action(id: u64) -> Result<(), String> {
+    EXTENSION_MANAGER.lock().unwrap().rerun(id)
+}
+
+/// A page of the clipboard history, newest first.
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn get_clipboard_history(limit: usize, offset: usize) -> Result<Vec<ClipboardEntry>, String> {
+    clipboard_history::with_history(|history| history.entries(limit, offset))
+}
+
+/// A page of the clipboard entries containing `query`, best matches first.
+/// `types` keeps only the entries offering one of them.
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn search_clipboard(
+    query: String,
+    types: Option<Vec<ContentType>>,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<ClipboardEntry>, String> {
+    clipboard_history::with_history(|history| history.search(&query, &types.unwrap_or_default(), limit, offset))
+}
+
+/// Puts every representation of an entry back on the system clipboard, if
+/// the clipboard extension is enabled and was granted `clipboard:write`.
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn restore_clipboard_entry(id: u64) -> Result<(), String> {
+    let permissions = {
+        let extension_manager = EXTENSION_MANAGER.lock().unwrap();
+        if !extension_manager.is_enabled("clipboard") {
+            return Err("The clipboard extension is disabled".to_string());
+        }
+        extension_manager.host().permissions()
+    };
+    let content = clipboard_history::with_history(|history| history.content(id))?.ok_or("No such clipboard entry")?;
+    let clipboard = backend::system_for(&permissions, "clipboard", &[Capability::ClipboardWrite])?;
+    let mut clipboard = clipboard.lock().unwrap();
+    clipboard.write(&content)
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn remove_clipboard_entry(id: u64) -> Result<(), String> {
+    clipboard_history::with_history(|history| history.remove(id)).map(|_| ())
+}
+
+/// Pins an entry so the retention rules keep it, or unpins it.
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn pin_clipboard_entry(id: u64, pinned: bool) -> Result<(), String> {
+    match clipboard_history::with_history(|history| history.set_pinned(id, pinned))? {
+        true => Ok(()),
+        false => Err("No such clipboard entry".to_string()),
+    }
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn clear_clipboard_history() -> Result<(), String> {
+    clipboard_history::with_history(|history| history.clear())
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn get_paste_stack() -> PasteStackState {
+    PASTE_STACK.lock().unwrap().state()
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn toggle_paste_stack() -> PasteStackState {
+    let mut stack = PASTE_STACK.lock().unwrap();
+    stack.toggle();
+    stack.state()
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn clear_paste_stack() {
+    PASTE_STACK.lock().unwrap().clear();
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn get_clipboard_registers() -> Result<Vec<Register>, String> {
+    clipboard_history::with_history(|history| history.registers())
+}
+
+/// Replaces a register with `text`, as edited in the panel.
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn set_clipboard_register(name: char, text: String) -> Result<(), String> {
+    let content = ClipboardContent::text(&text);
+    clipboard_history::with_history(|history| history.set_register(name, &content, SystemTime::now()))
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn remove_clipboard_register(name: char) -> Result<(), String> {
+    clipboard_history::with_history(|history| history.remove_register(name)).map(|_| ())
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn get_clipboard_sync() -> SyncStatus {
+    crate::extensions::clipboard::sync_status()
+}
+
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn is_clipboard_history_locked() -> bool {
+    clipboard_history::is_locked()
+}
+
+/// Overwrites the stored clipboard history and destroys its key.
+#[cfg(feature = "clipboard")]
+#[tauri::command]
+pub fn wipe_clipboard_history() -> Result<(), String> {
+    clipboard_history::wipe()
+}
+
+#[tauri::command]
+pub fn get_usage_stats() -> UsageReport {
+    EXTENSION_MANAGER.lock().unwrap().usage_stats()
+}
+
+/// Writes the usage stats to the downloads folder and returns the file path.
+#[tauri::command]
+pub fn export_usage_stats(format: ExportFormat) -> Result<String, String> {
+    let contents = EXTENSION_MANAGER.lock().unwrap().export_usage_stats(format);
+    let dir = dirs::download_dir().or_else(dirs::home_dir).ok_or("no downloads folder")?;
+    let path = dir.join(format!("shortcuts-usage-{}.{}", usage::day(std::time::SystemTime::now()), format.extension()));
+    std::fs::write(&path, contents).map_err(|err| err.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[derive(Deserialize)]
+struct ExtensionState {
+    id: String,
+    enabled: bool,
+    granted: Option<Vec<Capability>>,
 }
 
 #[tauri::command]
 pub fn set_extensions(invoke_message: String) {
     println!("set_extensions: {}", invoke_message);
-}
\ No newline at end of file
+    let states: Vec<ExtensionState> = match serde_json::from_str(&invoke_message) {
+        Ok(states) => states,
+        Err(err) => {
+            println!("set_extensions: invalid payload: {}", err);
+            return;
+        }
+    };
+
+    let mut extension_manager = EXTENSION_MANAGER.lock().unwrap();
+    for state in states {
+        if let Some(granted) = state.granted {
+            if let Err(err) = extension_manager.grant(&state.id, granted) {
+                println!("set_extensions: {}", err);
+            }
+        }
+        if extension_manager.is_enabled(&state.id) != state.enabled {
+            extension_manager.set_enabled(&state.id, state.enabled);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn set_extension_settings(id: String, settings: serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    EXTENSION_MANAGER.lock().unwrap().set_settings(&id, settings)
+}
+
+#[tauri::command]
+pub fn install_extension(path: String, allow_unsigned: bool) -> Result<InstalledExtension, String> {
+    let trusted = package::read_trusted_keys(&package::default_trusted_keys_path());
+    EXTENSION_MANAGER.lock().unwrap().install(Path::new(&path), &trusted, allow_unsigned)
+}
+
+#[tauri::command]
+pub fn uninstall_extension(name: String) -> Result<(), String> {
+    EXTENSION_MANAGER.lock().unwrap().uninstall(&name)
+}
+
+#[tauri::command]
+pub fn list_installed() -> Vec<InstalledExtension> {
+    EXTENSION_MANAGER.lock().unwrap().list_installed()
+}
+
+#[tauri::command]
+pub fn get_registry_index() -> String {
+    RegistryConfig::load().index
+}
+
+#[tauri::command]
+pub fn set_registry_index(location: String) -> Result<(), String> {
+    RegistryConfig { index: location }.save()
+}
+
+#[tauri::command]
+pub fn get_available_extensions() -> Result<Vec<AvailableExtension>, String> {
+    let index = RegistryIndex::load(&RegistryConfig::load().index)?;
+    Ok(index.available(&EXTENSION_MANAGER.lock().unwrap().list_installed()))
+}
+
+#[tauri::command]
+pub fn install_from_index(name: String, version: Option<String>, allow_unsigned: bool) -> Result<InstalledExtension, String> {
+    let index = RegistryIndex::load(&RegistryConfig::load().index)?;
+    let trusted = package::read_trusted_keys(&package::default_trusted_keys_path());
+    extension_manager::install_from_index(&EXTENSION_MANAGER, &index, &name, version.as_deref(), &trusted, allow_unsigned)
+}
+
+#[tauri::command]
+pub fn update_extensions(allow_unsigned: bool) -> Result<Vec<InstalledExtension>, String> {
+    let index = RegistryIndex::load(&RegistryConfig::load().index)?;
+    let trusted = package::read_trusted_keys(&package::default_trusted_keys_path());
+    extension_manager::update_all(&EXTENSION_MANAGER, &index, &trusted, allow_unsigned)
+}
+
+#[tauri::command]
+pub fn get_locale() -> String {
+    EXTENSION_MANAGER.lock().unwrap().locale()
+}
+
+#[tauri::command]
+pub fn set_locale(locale: String) -> Result<(), String> {
+    EXTENSION_MANAGER.lock().unwrap().set_locale(&locale)?;
+    LocaleConfig { locale }.save()
+}
@@ -1,35 +1,595 @@
-use super::extension::{ShortcutHandler, Extension};
-use std::sync::LazyLock;
-use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
-
-pub static CLIPBOARD: LazyLock<Extension> = LazyLock::new(|| Extension {
-  name: "Clipboard".to_string(),
-  description: "Clipboard description".to_string(),
-  shortcuts: vec![
-    ShortcutHandler{
-      name: "Copy".to_string(),
-      description: "Copy the selected text to the clipboard".to_string(),
-      handle: || -> () {
-        println!("Copy the selected text to the clipboard");
-      },
-      default_shortcut: Some(Shortcut::new(Some(Modifiers::META), Code::KeyC)),
-    },
-    ShortcutHandler{
-      name: "Paste".to_string(),
-      description: "Paste the selected text to the clipboard".to_string(),
-      handle: || -> () {
+use serde_json::json;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use shortcuts_core::clipboard::backend::{self, ClipboardContent, SharedBackend};
+use shortcuts_core::clipboard::discovery::Discovery;
+use shortcuts_core::clipboard::filter::{ClipboardFilter, Detector, SensitiveAction, Verdict};
+use shortcuts_core::clipboard::history::{self, ClipboardEntry};
+use shortcuts_core::clipboard::monitor::{ClipboardMonitor, POLL_INTERVAL};
+use shortcuts_core::clipboard::paste_stack::PASTE_STACK;
+use shortcuts_core::clipboard::registers::{RegisterOp, CHORD, CHORD_TIMEOUT};
+use shortcuts_core::clipboard::retention::{self, RetentionPolicy, DEFAULT_CAPACITY};
+use shortcuts_core::clipboard::sync::{self, ClipboardSync, Resolver, SyncClip, SyncConfig, SyncIdentity, SyncPeer, SyncStatus};
+use shortcuts_core::clipboard::{paste, transform};
+use shortcuts_core::extensions::extension_manager::EXTENSION_MANAGER;
+use shortcuts_core::extensions::permissions::Capability;
+use shortcuts_sdk::{actions, extension, Code, Icon, InvocationContext, SettingKind, SettingSchema, Settings, ShortcutHandler};
+
+const ID: &str = "clipboard";
+/// Entries `history_viewer` prints.
+const VIEWER_ENTRIES: usize = 10;
+/// How often expired entries and those beyond the retention rules are removed.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the sync settings are checked for changes.
+const SYNC_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+type ChordListener = Box<dyn Fn(bool) + Send>;
+
+static MONITOR: Mutex<Option<ClipboardMonitor>> = Mutex::new(None);
+/// Grabs the chord keys while a register chord is pending and releases them
+/// after.
+static CHORD_LISTENER: Mutex<Option<ChordListener>> = Mutex::new(None);
+static SYNC: Mutex<Option<RunningSync>> = Mutex::new(None);
+static RESOLVER: Mutex<Resolver> = Mutex::new(Resolver::new());
+static IDENTITY: OnceLock<Result<SyncIdentity, String>> = OnceLock::new();
+
+#[extension(
+  id = "clipboard",
+  name = "Clipboard",
+  description = "Clipboard description",
+  category = "Productivity",
+  keywords = ["copy", "paste", "history"],
+  icon = "symbol:clipboard",
+  locales = "locales/clipboard",
+  capabilities = ["clipboard:read", "clipboard:write"],
+  settings = settings,
+  actions = transform_actions,
+)]
+pub struct Clipboard;
+
+#[actions]
+impl Clipboard {
+  #[action(description = "Copy the selected text to the clipboard", default = "Super+C", icon = "symbol:copy")]
+  fn copy() {
+    println!("Copy the selected text to the clipboard");
+  }
+
+  #[action(description = "Paste the selected text to the clipboard", default = "Super+V", icon = "symbol:paste")]
+  fn paste() {
+    let next = {
+      let mut stack = PASTE_STACK.lock().unwrap();
+      if !stack.is_active() {
         println!("Paste the selected text to the clipboard");
+        return;
+      }
+      stack.pop()
+    };
+    let content = match next {
+      Some(content) => content,
+      None => {
+        println!("The paste stack is empty");
+        return;
+      },
+    };
+    let written = system_clipboard(&[Capability::ClipboardWrite]).and_then(|clipboard| clipboard.lock().unwrap().write(&content));
+    if let Err(err) = written.and_then(|_| paste::paste()) {
+      println!("Failed to paste from the paste stack: {}", err);
+    }
+  }
+
+  #[action(
+    description = "Queue every copy and paste them back one by one in copy order",
+    default = "Super+Alt+S",
+    icon = "symbol:stack",
+    keywords = ["queue", "sequential", "form"],
+  )]
+  fn toggle_paste_stack() {
+    let active = PASTE_STACK.lock().unwrap().toggle();
+    println!("Paste stack {}", if active { "on" } else { "off" });
+  }
+
+  #[action(
+    description = "View paste history board",
+    default = "Super+Alt+V",
+    icon = "symbol:history",
+    keywords = ["recent", "board"],
+  )]
+  fn history_viewer(context: &InvocationContext) {
+    let page = history::with_history(|history| Ok((history.entries(VIEWER_ENTRIES, 0)?, history.len()?)));
+    let (entries, len) = match page {
+      Ok(page) => page,
+      Err(err) => {
+        println!("{}", err);
+        return;
       },
-      default_shortcut: Some(Shortcut::new(Some(Modifiers::META), Code::KeyV)),
+    };
+    println!("Clipboard history ({} of {} entries):", entries.len(), len);
+    for entry in &entries {
+      if context.is_cancelled() {
+        return;
+      }
+      println!("  [{}] {} ({})", entry.id, entry.text, entry.source.as_deref().unwrap_or("unknown"));
+    }
+  }
+
+  #[action(
+    description = "Lock the clipboard history until the app restarts",
+    default = "Super+Alt+L",
+    icon = "symbol:lock",
+    keywords = ["secure", "encrypt"],
+  )]
+  fn lock_history() {
+    history::lock();
+    PASTE_STACK.lock().unwrap().clear();
+    println!("Clipboard history locked");
+  }
+
+  #[action(
+    description = "Save the clipboard into a register, named by the letter pressed next",
+    default = "Super+Alt+C",
+    icon = "symbol:register",
+    keywords = ["vim", "yank", "named"],
+  )]
+  fn copy_to_register() {
+    begin_chord(RegisterOp::Copy);
+  }
+
+  #[action(
+    description = "Paste from a register, named by the letter pressed next",
+    default = "Super+Alt+P",
+    icon = "symbol:register",
+    keywords = ["vim", "put", "named"],
+  )]
+  fn paste_from_register() {
+    begin_chord(RegisterOp::Paste);
+  }
+
+  #[action(description = "Pin the latest clipboard entry", icon = "symbol:pin", keywords = ["favorite", "keep"])]
+  fn pin_latest() {
+    let pinned = history::with_history(|history| match history.latest()? {
+      Some(entry) => history.set_pinned(entry.id, true).map(|_| Some(entry)),
+      None => Ok(None),
+    });
+    match pinned {
+      Ok(Some(entry)) => println!("Pinned [{}] {}", entry.id, entry.text),
+      Ok(None) => println!("No clipboard entry to pin"),
+      Err(err) => println!("{}", err),
+    }
+  }
+}
+
+fn notify_chord(pending: bool) {
+  if let Some(listener) = CHORD_LISTENER.lock().unwrap().as_ref() {
+    listener(pending);
+  }
+}
+
+/// Waits for the letter of a register chord, cancelling it after
+/// `CHORD_TIMEOUT`.
+fn begin_chord(op: RegisterOp) {
+  CHORD.lock().unwrap().begin(op, Instant::now());
+  notify_chord(true);
+  thread::spawn(|| {
+    thread::sleep(CHORD_TIMEOUT);
+    if CHORD.lock().unwrap().expire(Instant::now()) {
+      notify_chord(false);
+    }
+  });
+}
+
+/// Ends a pending register chord with the key pressed, returning whether
+/// one was pending, in which case the key is not handled as a shortcut.
+/// `on_change` is called once a register was saved.
+pub fn complete_chord(code: Code, on_change: impl FnOnce() + Send + 'static) -> bool {
+  let completed = {
+    let mut chord = CHORD.lock().unwrap();
+    if !chord.is_pending() {
+      return false;
+    }
+    chord.complete(code, Instant::now())
+  };
+  // Off the shortcut handler, which must not change the grabbed keys.
+  thread::spawn(move || {
+    notify_chord(false);
+    let result = match completed {
+      Some((RegisterOp::Copy, name)) => save_register(name).map(|_| on_change()),
+      Some((RegisterOp::Paste, name)) => paste_register(name),
+      None => Ok(()),
+    };
+    if let Err(err) = result {
+      println!("Failed to use the register: {}", err);
+    }
+  });
+  true
+}
+
+fn save_register(name: char) -> Result<(), String> {
+  let content = system_clipboard(&[Capability::ClipboardRead])?.lock().unwrap().read()?.ok_or("The clipboard is empty")?;
+  history::with_history(|history| history.set_register(name, &content, SystemTime::now()))?;
+  println!("Copied into register {}", name);
+  Ok(())
+}
+
+fn paste_register(name: char) -> Result<(), String> {
+  let content = history::with_history(|history| history.register(name))?
+    .ok_or_else(|| format!("Register {} is empty", name))?;
+  system_clipboard(&[Capability::ClipboardWrite])?.lock().unwrap().write(&content)?;
+  paste::paste()
+}
+
+/// The system clipboard, once the user granted the extension every one of
+/// `capabilities`. Denials are logged with those of extension processes.
+fn system_clipboard(capabilities: &[Capability]) -> Result<SharedBackend, String> {
+  let permissions = EXTENSION_MANAGER.lock().unwrap().host().permissions();
+  backend::system_for(&permissions, ID, capabilities)
+}
+
+/// One action per registered transform, all run by [`run_transform`].
+fn transform_actions() -> Vec<ShortcutHandler> {
+  transform::transforms().into_iter().map(|transform| ShortcutHandler {
+    icon: Some(Icon::Symbol("wand".to_string())),
+    keywords: vec!["transform".to_string(), "paste".to_string()],
+    ..ShortcutHandler::new(
+      transform.action_id(),
+      transform.name.to_string(),
+      transform.description.to_string(),
+      run_transform,
+    )
+  }).collect()
+}
+
+/// Replaces the clipboard with its text transformed by the transform of the
+/// invoked action, then pastes it unless the settings say otherwise.
+fn run_transform(context: &InvocationContext) {
+  let transform = match transform::for_action(&context.action) {
+    Some(transform) => transform,
+    None => {
+      println!("No clipboard transform for {}", context.action);
+      return;
     },
-    ShortcutHandler{
-      name: "HistoryViewer".to_string(),
-      description: "View paste history board".to_string(),
-      handle: || -> () {
-        println!("View paste history board");
+  };
+  let transformed = system_clipboard(&[Capability::ClipboardRead, Capability::ClipboardWrite]).and_then(|clipboard| {
+    let mut clipboard = clipboard.lock().unwrap();
+    let content = clipboard.read()?.ok_or("The clipboard is empty")?;
+    let text = content.plain_text().ok_or("The clipboard holds no text")?;
+    let transformed = (transform.apply)(text)?;
+    clipboard.write(&ClipboardContent::text(&transformed))
+  });
+  if let Err(err) = transformed {
+    println!("Failed to transform the clipboard with {}: {}", transform.id, err);
+    return;
+  }
+  if context.settings.get_bool("transform_paste").unwrap_or(true) {
+    if let Err(err) = paste::paste() {
+      println!("Failed to paste: {}", err);
+    }
+  }
+}
+
+/// Starts recording copies from the system clipboard into the history and
+/// calls `on_record` for each, queuing them while the paste stack is on.
+/// Copies are dropped while the extension is disabled, lacks
+/// `clipboard:read` or the history is locked, and when the settings' filter
+/// rejects them. Sensitive copies the filter lets expire and unpinned
+/// entries beyond the settings' retention rules are removed in the
+/// background, calling `on_prune`. `on_chord` grabs the `CHORD_KEYS` while
+/// a register chord is pending, and releases them after.
+pub fn start(
+  on_record: impl Fn(&ClipboardEntry) + Send + 'static,
+  on_prune: impl Fn() + Send + 'static,
+  on_chord: impl Fn(bool) + Send + 'static,
+) -> Result<(), String> {
+  *CHORD_LISTENER.lock().unwrap() = Some(Box::new(on_chord));
+  let monitor = ClipboardMonitor::start(backend::system()?, POLL_INTERVAL, move |content| {
+    let (settings, permissions) = {
+      let manager = EXTENSION_MANAGER.lock().unwrap();
+      if !manager.is_enabled(ID) || history::is_locked() {
+        return;
+      }
+      (manager.settings_for(ID), manager.host().permissions())
+    };
+    if permissions.check_extension(ID, &Capability::ClipboardRead).is_err() {
+      return;
+    }
+    let expires_after = match filter(&settings).check(&content) {
+      Verdict::Keep => None,
+      Verdict::Skip(_) => return,
+      Verdict::Expire(after) => Some(after),
+    };
+    PASTE_STACK.lock().unwrap().push(&content);
+    let now = SystemTime::now();
+    // Sensitive copies never leave the device.
+    if expires_after.is_none() {
+      send_to_peers(&content, now);
+    }
+    // The pruning thread applies the retention rules.
+    let recorded = history::with_history(|history| {
+      history.record(content, now)
+        .and_then(|entry| match expires_after {
+          Some(after) => {
+            history.set_expiry(entry.id, Some(now + after))?;
+            history.get(entry.id).map(|expiring| expiring.unwrap_or(entry))
+          },
+          None => Ok(entry),
+        })
+    });
+    match recorded {
+      Ok(entry) => on_record(&entry),
+      Err(err) => println!("Failed to record a copy: {}", err),
+    }
+  });
+  *MONITOR.lock().unwrap() = Some(monitor);
+
+  thread::spawn(move || loop {
+    if !history::is_locked() {
+      let policy = retention_policy(&EXTENSION_MANAGER.lock().unwrap().settings_for(ID));
+      let pruned = history::with_history(|history| {
+        history.set_retention(policy);
+        history.prune(SystemTime::now())
+      });
+      match pruned {
+        Ok(0) => {},
+        Ok(_) => on_prune(),
+        Err(err) => println!("Failed to prune the clipboard history: {}", err),
+      }
+    }
+    thread::sleep(PRUNE_INTERVAL);
+  });
+
+  thread::spawn(|| {
+    let identity = match identity() {
+      Ok(identity) => identity,
+      Err(err) => {
+        println!("Clipboard sync is unavailable: {}", err);
+        return;
       },
-      default_shortcut: Some(Shortcut::new(Some(Modifiers::META | Modifiers::ALT), Code::KeyV)),
+    };
+    loop {
+      supervise_sync(identity);
+      thread::sleep(SYNC_CHECK_INTERVAL);
+    }
+  });
+  Ok(())
+}
+
+/// Clipboard sync while it is on, with the settings it was started with.
+/// Dropping it stops syncing.
+struct RunningSync {
+  config: SyncConfig,
+  mdns: bool,
+  sync: Arc<ClipboardSync>,
+  _discovery: Option<Discovery>,
+}
+
+/// This device's sync identity, created on first use.
+fn identity() -> Result<&'static SyncIdentity, String> {
+  IDENTITY.get_or_init(|| SyncIdentity::load_or_create(None)).as_ref().map_err(Clone::clone)
+}
+
+pub fn sync_status() -> SyncStatus {
+  let public_key = identity().map(SyncIdentity::public_key).ok();
+  match SYNC.lock().unwrap().as_ref() {
+    Some(running) => SyncStatus {
+      enabled: true,
+      public_key,
+      port: Some(running.sync.local_addr().port()),
+      connected: running.sync.connected(),
     },
-  ],
-});
+    None => SyncStatus { public_key, ..SyncStatus::default() },
+  }
+}
+
+/// The sync settings, or `None` while sync or the extension is off.
+/// Malformed peers are ignored.
+fn sync_config() -> Option<(SyncConfig, bool)> {
+  let manager = EXTENSION_MANAGER.lock().unwrap();
+  let settings = manager.settings_for(ID);
+  if !manager.is_enabled(ID) || !settings.get_bool("sync_enabled").unwrap_or(false) {
+    return None;
+  }
+  let config = SyncConfig {
+    port: settings.get_f64("sync_port").map(|port| port as u16).unwrap_or(sync::DEFAULT_PORT),
+    peers: settings.get_list("sync_peers").iter().filter_map(|peer| peer.parse::<SyncPeer>().ok()).collect(),
+    max_bytes: (settings.get_f64("sync_max_kb").unwrap_or(1024.0) * 1024.0) as usize,
+  };
+  Some((config, settings.get_bool("sync_mdns").unwrap_or(true)))
+}
+
+/// Starts, stops or restarts clipboard sync to match the settings.
+fn supervise_sync(identity: &SyncIdentity) {
+  let wanted = sync_config();
+  let mut current = SYNC.lock().unwrap();
+  let running = current.as_ref().map(|running| (&running.config, running.mdns));
+  if running == wanted.as_ref().map(|(config, mdns)| (config, *mdns)) {
+    return;
+  }
+  *current = None;
+  let Some((config, mdns)) = wanted else {
+    println!("Clipboard sync off");
+    return;
+  };
+  let sync = match ClipboardSync::start(identity, config.clone(), receive_from_peer) {
+    Ok(sync) => Arc::new(sync),
+    Err(err) => {
+      println!("Failed to start clipboard sync: {}", err);
+      return;
+    },
+  };
+  let discovery = match mdns {
+    true => {
+      let found = Arc::downgrade(&sync);
+      Discovery::start(&identity.public_key(), sync.local_addr().port(), move |key, address| {
+        if let Some(sync) = found.upgrade() {
+          sync.set_address(key, &address.to_string());
+        }
+      }).map_err(|err| println!("Failed to discover clipboard sync peers: {}", err)).ok()
+    },
+    false => None,
+  };
+  println!("Clipboard sync on, listening on port {}", sync.local_addr().port());
+  *current = Some(RunningSync { config, mdns, sync, _discovery: discovery });
+}
+
+fn millis(time: SystemTime) -> u64 {
+  time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or(0)
+}
 
+fn send_to_peers(content: &ClipboardContent, copied_at: SystemTime) {
+  let Some(sync) = SYNC.lock().unwrap().as_ref().map(|running| running.sync.clone()) else { return };
+  let Ok(identity) = identity() else { return };
+  let copied_at = millis(copied_at);
+  if !RESOLVER.lock().unwrap().local(content, copied_at, &identity.public_key()) {
+    return;
+  }
+  if let Err(err) = sync.send(content, copied_at) {
+    println!("Failed to sync a copy: {}", err);
+  }
+}
+
+/// Puts a peer's copy on the clipboard unless a later one was made, after
+/// which the monitor records it like any other copy.
+fn receive_from_peer(clip: SyncClip) {
+  if !RESOLVER.lock().unwrap().remote(&clip) {
+    return;
+  }
+  let written = system_clipboard(&[Capability::ClipboardWrite]).and_then(|clipboard| clipboard.lock().unwrap().write(&clip.content));
+  if let Err(err) = written {
+    println!("Failed to apply a copy from {}: {}", clip.origin, err);
+  }
+}
+
+fn filter(settings: &Settings) -> ClipboardFilter {
+  let action = match settings.get_str("sensitive_action") {
+    Some("expire") => SensitiveAction::Expire(Duration::from_secs_f64(settings.get_f64("sensitive_expiry").unwrap_or(30.0))),
+    _ => SensitiveAction::Skip,
+  };
+  ClipboardFilter {
+    honor_concealed: settings.get_bool("honor_concealed").unwrap_or(true),
+    excluded_apps: settings.get_list("excluded_apps"),
+    detectors: settings.get_list("sensitive_patterns").iter().filter_map(|name| name.parse().ok()).collect(),
+    action,
+  }
+}
+
+/// The retention rules in `settings`; zero disables the age and size limits,
+/// and malformed per-type limits are ignored.
+fn retention_policy(settings: &Settings) -> RetentionPolicy {
+  let positive = |key| settings.get_f64(key).filter(|value| *value > 0.0);
+  RetentionPolicy {
+    max_count: settings.get_f64("history_size").map(|size| size as usize).unwrap_or(DEFAULT_CAPACITY),
+    max_age: positive("max_age_days").map(|days| Duration::from_secs_f64(days * 86400.0)),
+    max_bytes: positive("max_size_mb").map(|megabytes| (megabytes * 1024.0 * 1024.0) as u64),
+    type_limits: retention::parse_type_limits(&settings.get_list("type_limits")).unwrap_or_default(),
+  }
+}
+
+fn settings() -> Vec<SettingSchema> {
+  vec![
+    SettingSchema {
+      key: "history_size".to_string(),
+      label: "Clipboard history size".to_string(),
+      description: "Number of entries kept in the clipboard history".to_string(),
+      kind: SettingKind::Number { min: Some(1.0), max: Some(100000.0) },
+      default: json!(DEFAULT_CAPACITY),
+    },
+    SettingSchema {
+      key: "max_age_days".to_string(),
+      label: "Maximum age".to_string(),
+      description: "Days unpinned entries are kept for, or 0 to keep them until they are pushed out".to_string(),
+      kind: SettingKind::Number { min: Some(0.0), max: Some(3650.0) },
+      default: json!(0),
+    },
+    SettingSchema {
+      key: "max_size_mb".to_string(),
+      label: "Maximum history size".to_string(),
+      description: "Megabytes of content kept in unpinned entries, or 0 for no limit".to_string(),
+      kind: SettingKind::Number { min: Some(0.0), max: Some(100000.0) },
+      default: json!(0),
+    },
+    SettingSchema {
+      key: "type_limits".to_string(),
+      label: "Per-type limits".to_string(),
+      description: "Unpinned entries kept per content type, written as type:count, e.g. image:20".to_string(),
+      kind: SettingKind::List { max_items: None },
+      default: json!([]),
+    },
+    SettingSchema {
+      key: "transform_paste".to_string(),
+      label: "Paste transformed text".to_string(),
+      description: "Paste right away after a transform action rewrites the clipboard".to_string(),
+      kind: SettingKind::Bool,
+      default: json!(true),
+    },
+    SettingSchema {
+      key: "sync_enabled".to_string(),
+      label: "Sync with other devices".to_string(),
+      description: "Exchange copies with paired devices on the local network".to_string(),
+      kind: SettingKind::Bool,
+      default: json!(false),
+    },
+    SettingSchema {
+      key: "sync_peers".to_string(),
+      label: "Paired devices".to_string(),
+      description: "Public keys of the devices to sync with, optionally followed by @host:port".to_string(),
+      kind: SettingKind::List { max_items: None },
+      default: json!([]),
+    },
+    SettingSchema {
+      key: "sync_port".to_string(),
+      label: "Sync port".to_string(),
+      description: "TCP port paired devices connect to".to_string(),
+      kind: SettingKind::Number { min: Some(1.0), max: Some(65535.0) },
+      default: json!(sync::DEFAULT_PORT),
+    },
+    SettingSchema {
+      key: "sync_mdns".to_string(),
+      label: "Discover devices".to_string(),
+      description: "Find paired devices on the local network over mDNS instead of by address".to_string(),
+      kind: SettingKind::Bool,
+      default: json!(true),
+    },
+    SettingSchema {
+      key: "sync_max_kb".to_string(),
+      label: "Largest synced copy".to_string(),
+      description: "Kilobytes above which copies are neither sent nor accepted".to_string(),
+      kind: SettingKind::Number { min: Some(1.0), max: Some(102400.0) },
+      default: json!(1024),
+    },
+    SettingSchema {
+      key: "excluded_apps".to_string(),
+      label: "Excluded apps".to_string(),
+      description: "Applications whose copies are never recorded".to_string(),
+      kind: SettingKind::List { max_items: None },
+      default: json!([]),
+    },
+    SettingSchema {
+      key: "honor_concealed".to_string(),
+      label: "Ignore concealed copies".to_string(),
+      description: "Never record copies password managers mark as concealed or transient".to_string(),
+      kind: SettingKind::Bool,
+      default: json!(true),
+    },
+    SettingSchema {
+      key: "sensitive_patterns".to_string(),
+      label: "Sensitive patterns".to_string(),
+      description: "Secrets to look for in copies: credit_card, jwt, aws_key, private_key".to_string(),
+      kind: SettingKind::List { max_items: Some(Detector::ALL.len()) },
+      default: json!(Detector::ALL.iter().map(Detector::as_str).collect::<Vec<_>>()),
+    },
+    SettingSchema {
+      key: "sensitive_action".to_string(),
+      label: "Sensitive copies".to_string(),
+      description: "Whether copies matching a sensitive pattern are skipped or kept until they expire".to_string(),
+      kind: SettingKind::Choice { options: vec!["skip".to_string(), "expire".to_string()] },
+      default: json!("skip"),
+    },
+    SettingSchema {
+      key: "sensitive_expiry".to_string(),
+      label: "Sensitive copy lifetime".to_string(),
+      description: "Seconds sensitive copies are kept for when they expire".to_string(),
+      kind: SettingKind::Number { min: Some(1.0), max: Some(86400.0) },
+      default: json!(30),
+    },
+  ]
+}
@@ -0,0 +1,23 @@
+use shortcuts_core::extensions::extension_manager::EXTENSION_MANAGER;
+use shortcuts_sdk::{actions, extension, InvocationContext};
+
+#[extension(
+  id = "history",
+  name = "Action History",
+  description = "Repeat recently run actions",
+  category = "Productivity",
+  keywords = ["repeat", "again", "recent"],
+  icon = "symbol:history",
+  locales = "locales/history",
+)]
+pub struct History;
+
+#[actions]
+impl History {
+  #[action(description = "Run the last action again", default = "Super+Alt+R", icon = "symbol:repeat")]
+  fn repeat_last(context: &InvocationContext) {
+    if let Err(err) = EXTENSION_MANAGER.lock().unwrap().repeat_last(&context.extension) {
+      println!("Nothing to repeat: {}", err);
+    }
+  }
+}
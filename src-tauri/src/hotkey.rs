@@ -0,0 +1,9 @@
+use shortcuts_core::shortcut::Shortcut;
+
+// The core keeps its own shortcut type. Both it and the plugin build on
+// keyboard-types codes and modifiers, so the conversion is lossless.
+
+pub fn from_global(shortcut: &tauri_plugin_global_shortcut::Shortcut) -> Shortcut {
+    Shortcut::new(Some(shortcut.mods), shortcut.key)
+}
+
@@ -1,14 +1,216 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
+mod command;
+mod fns;
+mod hotkey;
+mod tray;
+mod extensions {
+    #[cfg(feature = "clipboard")]
+    pub mod clipboard;
+    #[cfg(feature = "history")]
+    pub mod history;
+}
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tauri_nspanel::ManagerExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
+#[cfg(feature = "clipboard")]
+use shortcuts_core::clipboard::registers::CHORD_KEYS;
+use shortcuts_core::extensions::extension_manager::EXTENSION_MANAGER;
+use shortcuts_core::extensions::host::Requirement;
+use shortcuts_core::extensions::permissions::Capability;
+
+/// How often the extensions directory is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The directories passed with `--dev-extension <path>`, which may repeat.
+fn dev_extensions(mut args: impl Iterator<Item = String>) -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    while let Some(arg) = args.next() {
+        if arg == "--dev-extension" {
+            match args.next() {
+                Some(path) => dirs.push(PathBuf::from(path)),
+                None => println!("--dev-extension requires a path"),
+            }
+        } else if let Some(path) = arg.strip_prefix("--dev-extension=") {
+            dirs.push(PathBuf::from(path));
+        }
+    }
+    dirs
+}
+
+/// Reloads changed extensions and tells the webview to refetch them when
+/// the extension list or the load errors change.
+fn watch_extensions(app_handle: tauri::AppHandle) {
+    EXTENSION_MANAGER.lock().unwrap().watch();
+    thread::spawn(move || {
+        let mut errors = EXTENSION_MANAGER.lock().unwrap().load_errors();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let (changed, current) = {
+                let mut manager = EXTENSION_MANAGER.lock().unwrap();
+                (manager.reload_changed(), manager.load_errors())
+            };
+            if !changed.is_empty() || current != errors {
+                errors = current;
+                let _ = app_handle.emit("extensions-changed", ());
+            }
+        }
+    });
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    for dir in dev_extensions(std::env::args().skip(1)) {
+        if let Err(err) = EXTENSION_MANAGER.lock().unwrap().load_dev_extension(&dir) {
+            println!("Failed to load dev extension {}", err);
+        }
+    }
+    println!("{}", EXTENSION_MANAGER.lock().unwrap().to_string());
     tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            command::init,
+            command::show_menubar_panel,
+            command::get_extensions,
+            command::get_load_errors,
+            command::get_action_history,
+            command::rerun_action,
+            #[cfg(feature = "clipboard")]
+            command::get_clipboard_history,
+            #[cfg(feature = "clipboard")]
+            command::search_clipboard,
+            #[cfg(feature = "clipboard")]
+            command::restore_clipboard_entry,
+            #[cfg(feature = "clipboard")]
+            command::remove_clipboard_entry,
+            #[cfg(feature = "clipboard")]
+            command::pin_clipboard_entry,
+            #[cfg(feature = "clipboard")]
+            command::clear_clipboard_history,
+            #[cfg(feature = "clipboard")]
+            command::get_paste_stack,
+            #[cfg(feature = "clipboard")]
+            command::toggle_paste_stack,
+            #[cfg(feature = "clipboard")]
+            command::clear_paste_stack,
+            #[cfg(feature = "clipboard")]
+            command::get_clipboard_registers,
+            #[cfg(feature = "clipboard")]
+            command::set_clipboard_register,
+            #[cfg(feature = "clipboard")]
+            command::remove_clipboard_register,
+            #[cfg(feature = "clipboard")]
+            command::get_clipboard_sync,
+            #[cfg(feature = "clipboard")]
+            command::is_clipboard_history_locked,
+            #[cfg(feature = "clipboard")]
+            command::wipe_clipboard_history,
+            command::get_usage_stats,
+            command::export_usage_stats,
+            command::set_extensions,
+            command::set_extension_settings,
+            command::install_extension,
+            command::uninstall_extension,
+            command::list_installed,
+            command::get_registry_index,
+            command::set_registry_index,
+            command::get_available_extensions,
+            command::install_from_index,
+            command::update_extensions,
+            command::get_locale,
+            command::set_locale
+        ])
+        .plugin(tauri_nspanel::init())
+        .setup(|app| {
+            app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+            let app_handle = app.app_handle();
+            app_handle.plugin(
+                tauri_plugin_global_shortcut::Builder::new()
+                        .with_handler(|app, shortcut, event| {
+                            if event.state == ShortcutState::Pressed  {
+                                #[cfg(feature = "clipboard")]
+                                if shortcut.mods.is_empty() {
+                                    let registers_handle = app.clone();
+                                    let completed = extensions::clipboard::complete_chord(shortcut.key, move || {
+                                        let _ = registers_handle.emit("clipboard-registers-changed", ());
+                                    });
+                                    if completed {
+                                        return;
+                                    }
+                                }
+                                EXTENSION_MANAGER.lock().unwrap().listen(&hotkey::from_global(shortcut));
+                            }
+                        })
+                        .build(),
+            )?;
+
+            let panel_handle = app_handle.clone();
+            EXTENSION_MANAGER.lock().unwrap().host().register(
+                "host/ui.show",
+                Requirement::Capability(Capability::ShowUi),
+                move |_, _| {
+                    if let Ok(panel) = panel_handle.get_webview_panel("main") {
+                        panel.show();
+                    }
+                    Ok(serde_json::Value::Null)
+                },
+            );
+            let failure_handle = app_handle.clone();
+            EXTENSION_MANAGER.lock().unwrap().set_failure_listener(move |failure| {
+                let body = match failure.disabled {
+                    true => format!("{} crashed repeatedly and was disabled: {}", failure.extension, failure.message),
+                    false => format!("{} crashed in {}: {}", failure.extension, failure.action, failure.message),
+                };
+                let _ = failure_handle.notification().builder().title("Extension crashed").body(body).show();
+                if failure.disabled {
+                    let _ = failure_handle.emit("extensions-changed", ());
+                }
+            });
+            let history_handle = app_handle.clone();
+            EXTENSION_MANAGER.lock().unwrap().set_history_listener(move |entry| {
+                let _ = history_handle.emit("action-history-changed", entry);
+            });
+            #[cfg(feature = "clipboard")]
+            {
+                let clipboard_handle = app_handle.clone();
+                let prune_handle = app_handle.clone();
+                let chord_handle = app_handle.clone();
+                if let Err(err) = extensions::clipboard::start(
+                    move |entry| {
+                        let _ = clipboard_handle.emit("clipboard-changed", entry);
+                    },
+                    move || {
+                        let _ = prune_handle.emit("clipboard-changed", ());
+                    },
+                    move |pending| {
+                        let keys: Vec<_> = CHORD_KEYS.iter()
+                            .map(|code| tauri_plugin_global_shortcut::Shortcut::new(None, *code))
+                            .collect();
+                        let shortcuts = chord_handle.global_shortcut();
+                        let grabbed = match pending {
+                            true => shortcuts.register_multiple(keys),
+                            false => shortcuts.unregister_multiple(keys),
+                        };
+                        if let Err(err) = grabbed {
+                            println!("Failed to grab the register keys: {}", err);
+                        }
+                    },
+                ) {
+                    println!("Failed to watch the clipboard: {}", err);
+                }
+            }
+            EXTENSION_MANAGER.lock().unwrap().start_external();
+            watch_extensions(app_handle.clone());
+
+            tray::create(app_handle)?;
+
+            Ok(())
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
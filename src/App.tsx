@@ -1,26 +1,33 @@
 import { useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 import "./App.css";
 import { useExtensionStore } from "./store/extension";
 import { default as ExtensionComponent } from "./components/extension";
+import { default as ClipboardComponent } from "./components/clipboard";
+import { default as HistoryComponent } from "./components/history";
+import { default as UsageComponent } from "./components/usage";
 import { ThemeProvider } from "@/components/theme-provider";
-import type { Extension } from "./types/extension";
 
 function App() {
-  const { extensions, setExtensions } = useExtensionStore((state) => state);
+  const { extensions, refresh } = useExtensionStore((state) => state);
 
   useEffect(() => {
     invoke("init");
-    invoke("get_extensions").then((messages) => {
-      const json = JSON.parse(messages as string) as Extension[];
-      setExtensions(json);
-    });
+    refresh();
+    const unListen = listen("extensions-changed", () => refresh());
+    return () => {
+      unListen.then((f) => f());
+    };
   }, []);
 
   return (
     <ThemeProvider defaultTheme="dark" storageKey="vite-ui-theme">
       <ExtensionComponent data={extensions} />
+      <ClipboardComponent />
+      <HistoryComponent />
+      <UsageComponent />
     </ThemeProvider>
   );
 }
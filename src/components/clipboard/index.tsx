@@ -0,0 +1,164 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Cross2Icon, DrawingPinFilledIcon, DrawingPinIcon, ReloadIcon } from "@radix-ui/react-icons";
+import { Input } from "../ui/input";
+import PasteStack from "./paste-stack";
+import Registers from "./registers";
+import Sync from "./sync";
+import { CONTENT_TYPES } from "../../types/clipboard";
+import type { ClipboardEntry, ContentType } from "../../types/clipboard";
+
+/** Entries fetched per page; more are loaded on demand. */
+const PAGE_SIZE = 50;
+
+const fetchPage = (query: string, types: ContentType[], offset: number) =>
+  invoke<ClipboardEntry[]>("search_clipboard", {
+    query,
+    types,
+    limit: PAGE_SIZE,
+    offset,
+  });
+
+const Clipboard: React.FC = () => {
+  const [query, setQuery] = useState("");
+  const [types, setTypes] = useState<ContentType[]>([]);
+  const [entries, setEntries] = useState<ClipboardEntry[]>([]);
+  const [hasMore, setHasMore] = useState(false);
+  const [locked, setLocked] = useState(false);
+
+  const refresh = async () => {
+    const isLocked = await invoke<boolean>("is_clipboard_history_locked");
+    setLocked(isLocked);
+    if (isLocked) {
+      setEntries([]);
+      setHasMore(false);
+      return;
+    }
+    const page = await fetchPage(query, types, 0);
+    setEntries(page);
+    setHasMore(page.length === PAGE_SIZE);
+  };
+
+  const wipe = () => {
+    if (window.confirm("Erase the stored clipboard history for good?")) {
+      invoke("wipe_clipboard_history").then(refresh);
+    }
+  };
+
+  const loadMore = () =>
+    fetchPage(query, types, entries.length).then((page) => {
+      setEntries([...entries, ...page]);
+      setHasMore(page.length === PAGE_SIZE);
+    });
+
+  useEffect(() => {
+    refresh();
+    const unListen = listen("clipboard-changed", () => refresh());
+    return () => {
+      unListen.then((f) => f());
+    };
+  }, [query, types]);
+
+  const toggleType = (type: ContentType) =>
+    setTypes(
+      types.includes(type) ? types.filter((t) => t !== type) : [...types, type],
+    );
+
+  return (
+    <div className="p-4">
+      <div className="flex flex-row items-center py-2">
+        <h2>Clipboard</h2>
+        <button
+          className="ml-auto px-2 text-sm"
+          onClick={() => invoke("clear_clipboard_history").then(refresh)}
+        >
+          Clear
+        </button>
+        <button className="px-2 text-sm" onClick={wipe}>
+          Wipe
+        </button>
+      </div>
+      {locked && (
+        <div className="flex flex-row items-center py-2 text-sm">
+          <p className="text-slate-400">
+            The clipboard history is locked until Shortcuts restarts.
+          </p>
+        </div>
+      )}
+      <PasteStack />
+      <Registers />
+      <Sync />
+      <Input
+        placeholder="Search"
+        value={query}
+        onChange={(event) => setQuery(event.target.value)}
+      />
+      <div className="flex flex-row gap-2 py-2 text-sm">
+        {CONTENT_TYPES.map((type) => (
+          <button
+            key={type}
+            className={types.includes(type) ? "font-bold" : "text-slate-400"}
+            onClick={() => toggleType(type)}
+          >
+            {type}
+          </button>
+        ))}
+      </div>
+      <div className="divide-y divide-slate-600">
+        {entries.map((entry) => (
+          <div key={entry.id} className="flex flex-row items-center py-2 text-sm">
+            {entry.thumbnail && (
+              <img className="mr-2 max-h-16" src={entry.thumbnail} alt="" />
+            )}
+            <div className="min-w-0">
+              <p className="truncate">{entry.text}</p>
+              <p className="text-slate-400">
+                {new Date(entry.copied_at).toLocaleString()}
+                {entry.source && ` · ${entry.source}`}
+                {` · ${entry.types.join(", ")}`}
+                {entry.expires_at &&
+                  ` · expires ${new Date(entry.expires_at).toLocaleTimeString()}`}
+              </p>
+            </div>
+            <button
+              className="ml-auto p-2"
+              title={entry.pinned ? "Unpin" : "Pin"}
+              onClick={() =>
+                invoke("pin_clipboard_entry", {
+                  id: entry.id,
+                  pinned: !entry.pinned,
+                }).then(refresh)
+              }
+            >
+              {entry.pinned ? <DrawingPinFilledIcon /> : <DrawingPinIcon />}
+            </button>
+            <button
+              className="p-2"
+              title="Restore"
+              onClick={() => invoke("restore_clipboard_entry", { id: entry.id })}
+            >
+              <ReloadIcon />
+            </button>
+            <button
+              className="p-2"
+              title="Remove"
+              onClick={() =>
+                invoke("remove_clipboard_entry", { id: entry.id }).then(refresh)
+              }
+            >
+              <Cross2Icon />
+            </button>
+          </div>
+        ))}
+      </div>
+      {hasMore && (
+        <button className="w-full py-2 text-sm" onClick={loadMore}>
+          Load more
+        </button>
+      )}
+    </div>
+  );
+};
+
+export default Clipboard;
@@ -0,0 +1,58 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import type { PasteStackState } from "../../types/clipboard";
+
+/** The copies queued by the paste stack, pasted top to bottom. */
+const PasteStack: React.FC = () => {
+  const [stack, setStack] = useState<PasteStackState>({
+    active: false,
+    items: [],
+  });
+
+  const refresh = () => invoke<PasteStackState>("get_paste_stack").then(setStack);
+
+  useEffect(() => {
+    refresh();
+    // Copies change the queue, and so do the toggle and paste actions.
+    const unListens = ["clipboard-changed", "action-history-changed"].map(
+      (event) => listen(event, () => refresh()),
+    );
+    return () => {
+      unListens.forEach((unListen) => unListen.then((f) => f()));
+    };
+  }, []);
+
+  return (
+    <div className="py-2 text-sm">
+      <div className="flex flex-row items-center">
+        <p>Paste stack {stack.active ? "on" : "off"}</p>
+        {stack.active && (
+          <button
+            className="ml-auto px-2"
+            onClick={() => invoke("clear_paste_stack").then(refresh)}
+          >
+            Clear
+          </button>
+        )}
+        <button
+          className={stack.active ? "px-2" : "ml-auto px-2"}
+          onClick={() => invoke<PasteStackState>("toggle_paste_stack").then(setStack)}
+        >
+          {stack.active ? "Stop" : "Start"}
+        </button>
+      </div>
+      {stack.active && (
+        <ol className="list-decimal pl-6 text-slate-400">
+          {stack.items.map((item, index) => (
+            <li key={index} className="truncate">
+              {item.text}
+            </li>
+          ))}
+        </ol>
+      )}
+    </div>
+  );
+};
+
+export default PasteStack;
@@ -0,0 +1,85 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { Cross2Icon } from "@radix-ui/react-icons";
+import { Input } from "../ui/input";
+import type { ClipboardRegister } from "../../types/clipboard";
+
+const NAMES = "abcdefghijklmnopqrstuvwxyz".split("");
+
+/** The named registers. Editing one replaces it with plain text. */
+const Registers: React.FC = () => {
+  const [registers, setRegisters] = useState<ClipboardRegister[]>([]);
+  const [name, setName] = useState("a");
+  const [text, setText] = useState("");
+
+  const refresh = () =>
+    invoke<ClipboardRegister[]>("get_clipboard_registers").then(setRegisters);
+
+  useEffect(() => {
+    refresh();
+    const unListen = listen("clipboard-registers-changed", () => refresh());
+    return () => {
+      unListen.then((f) => f());
+    };
+  }, []);
+
+  const edit = (register: ClipboardRegister) => {
+    setName(register.name);
+    setText(register.text);
+  };
+
+  const save = () =>
+    invoke("set_clipboard_register", { name, text }).then(() => {
+      setText("");
+      refresh();
+    });
+
+  return (
+    <div className="py-2 text-sm">
+      <p>Registers</p>
+      <div className="divide-y divide-slate-600">
+        {registers.map((register) => (
+          <div key={register.name} className="flex flex-row items-center py-1">
+            <span className="w-6 font-mono">{register.name}</span>
+            <button
+              className="min-w-0 truncate text-left"
+              title="Edit"
+              onClick={() => edit(register)}
+            >
+              {register.text}
+            </button>
+            <button
+              className="ml-auto p-2"
+              title="Remove"
+              onClick={() =>
+                invoke("remove_clipboard_register", { name: register.name }).then(refresh)
+              }
+            >
+              <Cross2Icon />
+            </button>
+          </div>
+        ))}
+      </div>
+      <div className="flex flex-row items-center gap-2 pt-2">
+        <select value={name} onChange={(event) => setName(event.target.value)}>
+          {NAMES.map((letter) => (
+            <option key={letter} value={letter}>
+              {letter}
+            </option>
+          ))}
+        </select>
+        <Input
+          placeholder="Register text"
+          value={text}
+          onChange={(event) => setText(event.target.value)}
+        />
+        <button className="px-2" onClick={save}>
+          Save
+        </button>
+      </div>
+    </div>
+  );
+};
+
+export default Registers;
@@ -0,0 +1,52 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { ClipboardSyncStatus } from "../../types/clipboard";
+
+/** How often the connected devices are refreshed. */
+const REFRESH_INTERVAL = 3000;
+
+/** This device's sync key and the paired devices connected to it. */
+const Sync: React.FC = () => {
+  const [status, setStatus] = useState<ClipboardSyncStatus | null>(null);
+
+  useEffect(() => {
+    const refresh = () => invoke<ClipboardSyncStatus>("get_clipboard_sync").then(setStatus);
+    refresh();
+    // Peers come and go without an event.
+    const interval = setInterval(refresh, REFRESH_INTERVAL);
+    return () => clearInterval(interval);
+  }, []);
+
+  if (!status?.enabled) {
+    return null;
+  }
+
+  return (
+    <div className="py-2 text-sm">
+      <div className="flex flex-row items-center">
+        <p>Sync on port {status.port}</p>
+        {status.public_key && (
+          <button
+            className="ml-auto px-2"
+            onClick={() => navigator.clipboard.writeText(status.public_key!)}
+          >
+            Copy key
+          </button>
+        )}
+      </div>
+      {status.connected.length === 0 ? (
+        <p className="text-slate-400">No devices connected</p>
+      ) : (
+        <ul className="pl-2 text-slate-400">
+          {status.connected.map((key) => (
+            <li key={key} className="truncate">
+              {key}
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+};
+
+export default Sync;
@@ -16,16 +16,21 @@ interface ExtensionProps {
 }
 
 const Extension: React.FC<ExtensionProps> = () => {
-  const { extensions, setExtensions, invokeSetExtensions } = useExtensionStore(
-    (state) => state
-  );
+  const { extensions, loadErrors, setExtensions, invokeSetExtensions } =
+    useExtensionStore((state) => state);
   return (
     <div className="p-4">
+      {loadErrors.map((loadError) => (
+        <div key={loadError.path} className="py-2 text-sm text-red-400">
+          <p className="font-mono">{loadError.path}</p>
+          <p>{loadError.error}</p>
+        </div>
+      ))}
       {extensions.map((extension) => {
         return (
-          <div className="divide-y-2 divide-slate-600" key={extension.name}>
+          <div className="divide-y-2 divide-slate-600" key={extension.id}>
             <div
-              key={extension.name}
+              key={extension.id}
               className="flex flex-row items-center py-4"
             >
               <h2>{extension.name}</h2>
@@ -39,6 +44,11 @@ const Extension: React.FC<ExtensionProps> = () => {
                   </TooltipTrigger>
                   <TooltipContent>
                     <p>{extension.description}</p>
+                    {extension.capabilities.length > 0 && (
+                      <p className="text-slate-400">
+                        Enabling allows: {extension.capabilities.join(", ")}
+                      </p>
+                    )}
                   </TooltipContent>
                 </Tooltip>
               </TooltipProvider>
@@ -46,9 +56,15 @@ const Extension: React.FC<ExtensionProps> = () => {
                 className="ml-auto"
                 checked={extension.enabled}
                 onCheckedChange={() => {
+                  // Enabling grants what the extension declares, as
+                  // listed in its tooltip.
                   const newExtensions = extensions.map((o) =>
-                    o.name === extension.name
-                      ? { ...o, enabled: !o.enabled }
+                    o.id === extension.id
+                      ? {
+                          ...o,
+                          enabled: !o.enabled,
+                          granted: o.enabled ? o.granted : o.capabilities,
+                        }
                       : o
                   );
                   setExtensions(newExtensions);
@@ -62,14 +78,14 @@ const Extension: React.FC<ExtensionProps> = () => {
                   return (
                     <Shortcut
                       {...shortcut}
-                      key={shortcut.name}
+                      key={shortcut.id}
                       onChange={(value) => {
                         const newExtensions = extensions.map((o) =>
-                          o.name === extension.name
+                          o.id === extension.id
                             ? {
                                 ...o,
                                 shortcuts: o.shortcuts.map((s) =>
-                                  s.name === shortcut.name
+                                  s.id === shortcut.id
                                     ? { ...s, shortcut: value }
                                     : s
                                 ),
@@ -0,0 +1,57 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { ReloadIcon } from "@radix-ui/react-icons";
+import type { HistoryEntry } from "../../types/history";
+
+const History: React.FC = () => {
+  const [entries, setEntries] = useState<HistoryEntry[]>([]);
+
+  useEffect(() => {
+    const refresh = () =>
+      invoke<HistoryEntry[]>("get_action_history").then(setEntries);
+    refresh();
+    const unListen = listen("action-history-changed", () => refresh());
+    return () => {
+      unListen.then((f) => f());
+    };
+  }, []);
+
+  if (entries.length === 0) {
+    return null;
+  }
+  return (
+    <div className="p-4">
+      <h2 className="py-2">History</h2>
+      <div className="divide-y divide-slate-600">
+        {entries.map((entry) => (
+          <div key={entry.id} className="flex flex-row items-center py-2 text-sm">
+            <div>
+              <p>
+                {entry.extension} / {entry.action}
+              </p>
+              <p className="text-slate-400">
+                {new Date(entry.started_at).toLocaleString()} ·{" "}
+                {entry.duration_ms} ms · {entry.outcome}
+              </p>
+              {entry.error && <p className="text-red-400">{entry.error}</p>}
+            </div>
+            <button
+              className="ml-auto p-2"
+              title="Re-run"
+              onClick={() =>
+                invoke("rerun_action", { id: entry.id }).catch((error) =>
+                  console.error(error)
+                )
+              }
+            >
+              <ReloadIcon />
+            </button>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+};
+
+export default History;
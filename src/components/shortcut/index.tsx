@@ -11,7 +11,7 @@ import type { Shortcut as ShortcutProps } from "../../types/extension";
 
 const Shortcut: React.FC<
   ShortcutProps & { onChange: (value: string) => void }
-> = ({ name, description, shortcut, onChange }) => {
+> = ({ name, description, shortcut, unused, onChange }) => {
   return (
     <div className="flex flex-row items-center">
       <h2>{name}</h2>
@@ -28,6 +28,11 @@ const Shortcut: React.FC<
           </TooltipContent>
         </Tooltip>
       </TooltipProvider>
+      {unused && (
+        <span className="ml-2 text-xs text-slate-400">
+          Unused for 30 days
+        </span>
+      )}
       <ShortcutInput className="ml-auto" value={shortcut} onChange={onChange} />
     </div>
   );
@@ -0,0 +1,62 @@
+import React, { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import type { ExportFormat, UsageReport } from "../../types/usage";
+
+const Usage: React.FC = () => {
+  const [report, setReport] = useState<UsageReport | null>(null);
+  const [exported, setExported] = useState<string | null>(null);
+
+  useEffect(() => {
+    const refresh = () =>
+      invoke<UsageReport>("get_usage_stats").then(setReport);
+    refresh();
+    const unListen = listen("action-history-changed", () => refresh());
+    return () => {
+      unListen.then((f) => f());
+    };
+  }, []);
+
+  const exportStats = (format: ExportFormat) =>
+    invoke<string>("export_usage_stats", { format })
+      .then(setExported)
+      .catch((error) => console.error(error));
+
+  if (!report || report.actions.length === 0) {
+    return null;
+  }
+  const actions = [...report.actions].sort((a, b) => b.total - a.total);
+  return (
+    <div className="p-4">
+      <div className="flex flex-row items-center py-2">
+        <h2>Usage since {report.since}</h2>
+        <button className="ml-auto px-2 text-sm" onClick={() => exportStats("csv")}>
+          Export CSV
+        </button>
+        <button className="px-2 text-sm" onClick={() => exportStats("json")}>
+          Export JSON
+        </button>
+      </div>
+      {exported && (
+        <p className="pb-2 text-xs text-slate-400">Saved to {exported}</p>
+      )}
+      <div className="divide-y divide-slate-600">
+        {actions.map((usage) => (
+          <div
+            key={`${usage.extension}/${usage.action}`}
+            className="flex flex-row items-center py-2 text-sm"
+          >
+            <p>
+              {usage.extension} / {usage.action}
+            </p>
+            <p className="ml-auto text-slate-400">
+              {usage.total} · last {usage.last_used}
+            </p>
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+};
+
+export default Usage;
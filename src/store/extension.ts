@@ -1,19 +1,35 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import type { Extension } from "../types/extension";
+import type { Extension, LoadError } from "../types/extension";
 
 interface ExtensionStore {
   extensions: Extension[];
+  loadErrors: LoadError[];
   setExtensions: (extensions: Extension[]) => void;
   invokeSetExtensions: () => void;
+  /** Switches the locale and reloads the translated extension list. */
+  setLocale: (locale: string) => Promise<void>;
+  /** Refetches the extensions and load errors, e.g. after a hot reload. */
+  refresh: () => Promise<void>;
 }
 
 export const useExtensionStore = create<ExtensionStore>((set, get) => ({
   extensions: [],
+  loadErrors: [],
   setExtensions: (extensions: Extension[]) => set({ extensions }),
   invokeSetExtensions: () => {
     invoke("set_extensions", {
       invokeMessage: JSON.stringify(get().extensions),
     });
   },
+  setLocale: async (locale: string) => {
+    await invoke("set_locale", { locale });
+    const messages = await invoke<string>("get_extensions");
+    set({ extensions: JSON.parse(messages) as Extension[] });
+  },
+  refresh: async () => {
+    const messages = await invoke<string>("get_extensions");
+    const loadErrors = await invoke<LoadError[]>("get_load_errors");
+    set({ extensions: JSON.parse(messages) as Extension[], loadErrors });
+  },
 }));
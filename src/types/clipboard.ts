@@ -0,0 +1,50 @@
+export type ContentType = "text" | "html" | "rtf" | "image" | "files";
+
+export const CONTENT_TYPES: ContentType[] = ["text", "html", "rtf", "image", "files"];
+
+export interface ClipboardEntry {
+  id: number;
+  /** The plain text, file paths or text of the HTML copied. */
+  text: string;
+  /** The application it was copied from, when known. */
+  source: string | null;
+  /** Unix time in milliseconds of the latest copy. */
+  copied_at: number;
+  /** The representations kept; restoring the entry offers all of them. */
+  types: ContentType[];
+  /** A `data:` URL of the image thumbnail. */
+  thumbnail: string | null;
+  /** Unix time in milliseconds a sensitive entry is removed at. */
+  expires_at: number | null;
+  /** Pinned entries are listed first and never pruned. */
+  pinned: boolean;
+}
+
+export interface PasteStackItem {
+  text: string;
+  types: ContentType[];
+}
+
+export interface PasteStackState {
+  active: boolean;
+  /** The next item to paste comes first. */
+  items: PasteStackItem[];
+}
+
+export interface ClipboardRegister {
+  /** `a` to `z`. */
+  name: string;
+  text: string;
+  types: ContentType[];
+  /** Unix time in milliseconds it was last set. */
+  updated_at: number;
+}
+
+export interface ClipboardSyncStatus {
+  enabled: boolean;
+  /** This device's key, to add to the paired devices of the others. */
+  public_key: string | null;
+  port: number | null;
+  /** Keys of the devices connected now. */
+  connected: string[];
+}
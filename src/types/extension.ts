@@ -1,13 +1,58 @@
 export interface Shortcut {
+  /** Stable action ID; `name` is only for display. */
+  id: string;
   name: string;
   description: string;
   shortcut: string;
+  icon: Icon | null;
+  keywords: string[];
+  /** Bound, but not invoked in the last 30 days. */
+  unused: boolean;
 }
 
+/** `symbol:<name>` for a named symbol, otherwise an SVG or PNG data URL. */
+export type Icon = string;
+
+export type SettingKind =
+  | { type: "string"; max_length?: number }
+  | { type: "number"; min?: number; max?: number }
+  | { type: "bool" }
+  | { type: "enum"; options: string[] }
+  | { type: "list"; max_items?: number };
+
+export type Setting = SettingKind & {
+  key: string;
+  label: string;
+  description: string;
+  default: unknown;
+  value: unknown;
+};
+
 export interface Extension {
+  /** Stable extension ID; `name` is only for display. */
+  id: string;
   name: string;
   description: string;
   shortcuts: Shortcut[];
+  settings: Setting[];
+  /** Capabilities the extension declares, e.g. `clipboard:read` or `fs:/path`. */
+  capabilities: string[];
+  /** Capabilities the user granted; sent back through `set_extensions`. */
+  granted: string[];
+
+  version: string | null;
+  author: string | null;
+  homepage: string | null;
+  license: string | null;
+  category: string | null;
+  keywords: string[];
+  icon: Icon | null;
 
   enabled: boolean;
 }
+
+/** An extension that failed to load or whose process failed to start. */
+export interface LoadError {
+  path: string;
+  error: string;
+}
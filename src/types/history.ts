@@ -0,0 +1,19 @@
+export type Trigger =
+  | { shortcut: string }
+  | "repeat"
+  | "rerun";
+
+export type Outcome = "succeeded" | "failed" | "panicked" | "cancelled";
+
+/** One finished action invocation, newest first in `get_action_history`. */
+export interface HistoryEntry {
+  id: number;
+  extension: string;
+  action: string;
+  trigger: Trigger;
+  /** Unix time in milliseconds. */
+  started_at: number;
+  duration_ms: number;
+  outcome: Outcome;
+  error: string | null;
+}
@@ -0,0 +1,18 @@
+/** A UTC day, `YYYY-MM-DD`. */
+export type Day = string;
+
+export interface ActionUsage {
+  extension: string;
+  action: string;
+  total: number;
+  last_used: Day | null;
+  daily: Record<Day, number>;
+}
+
+export interface UsageReport {
+  /** The day counting started. */
+  since: Day | null;
+  actions: ActionUsage[];
+}
+
+export type ExportFormat = "csv" | "json";